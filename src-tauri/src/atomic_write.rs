@@ -0,0 +1,198 @@
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Предел цепочки символических ссылок, как ELOOP в Linux
+const MAX_SYMLINK_DEPTH: usize = 40;
+
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Ошибка записи файла. ReadOnly - отдельно, чтобы интерфейс мог предложить
+/// "сохранить как" или сохранить с правами администратора
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum WriteError {
+    ReadOnly { path: String, message: String },
+    Failed { message: String },
+}
+
+impl WriteError {
+    pub fn message(&self) -> &str {
+        match self {
+            WriteError::ReadOnly { message, .. } | WriteError::Failed { message } => message,
+        }
+    }
+}
+
+impl From<String> for WriteError {
+    fn from(message: String) -> Self {
+        WriteError::Failed { message }
+    }
+}
+
+fn read_only(path: &Path) -> WriteError {
+    WriteError::ReadOnly {
+        path: path.to_string_lossy().to_string(),
+        message: format!("Файл доступен только для чтения: {}", path.display()),
+    }
+}
+
+fn failed(action: &str, path: &Path, error: std::io::Error) -> WriteError {
+    if error.kind() == ErrorKind::PermissionDenied {
+        return read_only(path);
+    }
+    WriteError::Failed {
+        message: format!("Ошибка при {} '{}': {}", action, path.display(), error),
+    }
+}
+
+/// Настоящий файл за цепочкой символических ссылок (в том числе еще не существующий)
+fn resolve_symlinks(path: &Path) -> Result<PathBuf, WriteError> {
+    let mut current = path.to_path_buf();
+    for _ in 0..MAX_SYMLINK_DEPTH {
+        match fs::symlink_metadata(&current) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let target = fs::read_link(&current).map_err(|e| failed("чтении ссылки", &current, e))?;
+                current = match current.parent() {
+                    Some(parent) => parent.join(target),
+                    None => target,
+                };
+            }
+            _ => return Ok(current),
+        }
+    }
+    Err(WriteError::Failed {
+        message: format!("Слишком длинная цепочка символических ссылок: {}", path.display()),
+    })
+}
+
+#[cfg(unix)]
+fn is_writable(path: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    let Ok(path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    unsafe { libc::access(path.as_ptr(), libc::W_OK) == 0 }
+}
+
+#[cfg(not(unix))]
+fn is_writable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| !metadata.permissions().readonly())
+}
+
+/// Временный файл рядом с целевым: rename атомарен только в пределах одной файловой системы
+fn create_temp(target: &Path) -> std::io::Result<(PathBuf, File)> {
+    let dir = target.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = target.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    loop {
+        let temp = dir.join(format!(
+            ".{}.{}.{}.tmp",
+            name,
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        match OpenOptions::new().write(true).create_new(true).open(&temp) {
+            Ok(file) => return Ok((temp, file)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Переносит права и владельца старого файла на новый. false - владельца сохранить нельзя
+fn copy_attributes(file: &File, metadata: &fs::Metadata) -> std::io::Result<bool> {
+    file.set_permissions(metadata.permissions())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let current = file.metadata()?;
+        if (current.uid(), current.gid()) != (metadata.uid(), metadata.gid()) {
+            // Чужой файл, доступный на запись через группу: chown запрещен
+            if std::os::unix::fs::fchown(file, Some(metadata.uid()), Some(metadata.gid())).is_err() {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Запись поверх файла на месте - когда заменить его нельзя
+fn write_in_place(target: &Path, bytes: &[u8]) -> Result<(), WriteError> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(target)
+        .map_err(|e| failed("открытии файла", target, e))?;
+    file.write_all(bytes).map_err(|e| failed("записи в файл", target, e))?;
+    file.sync_all().map_err(|e| failed("записи в файл", target, e))
+}
+
+#[cfg(unix)]
+fn sync_dir(target: &Path) {
+    if let Some(dir) = target.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_target: &Path) {}
+
+/// Записывает файл атомарно: во временный файл в той же папке, fsync, затем rename
+/// поверх целевого. Символические ссылки не заменяются - пишется файл, на который они
+/// указывают; права и владелец сохраняются.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), WriteError> {
+    let target = resolve_symlinks(path)?;
+    let metadata = fs::metadata(&target).ok();
+    if metadata.is_some() && !is_writable(&target) {
+        return Err(read_only(&target));
+    }
+
+    // Замена файла разорвала бы жесткие ссылки
+    #[cfg(unix)]
+    if metadata.as_ref().is_some_and(|metadata| std::os::unix::fs::MetadataExt::nlink(metadata) > 1) {
+        return write_in_place(&target, bytes);
+    }
+
+    let (temp, mut file) = match create_temp(&target) {
+        Ok(created) => created,
+        // Папка недоступна на запись, но сам файл - доступен
+        Err(e) if e.kind() == ErrorKind::PermissionDenied && metadata.is_some() => {
+            return write_in_place(&target, bytes);
+        }
+        Err(e) => return Err(failed("создании файла", &target, e)),
+    };
+
+    let written = (|| {
+        file.write_all(bytes)?;
+        let owner_kept = match &metadata {
+            Some(metadata) => copy_attributes(&file, metadata)?,
+            None => true,
+        };
+        file.sync_all()?;
+        Ok::<bool, std::io::Error>(owner_kept)
+    })();
+    drop(file);
+
+    let result = match written {
+        Ok(true) => fs::rename(&temp, &target).map_err(|e| failed("сохранении файла", &target, e)),
+        Ok(false) => {
+            let _ = fs::remove_file(&temp);
+            return write_in_place(&target, bytes);
+        }
+        Err(e) => Err(failed("записи в файл", &target, e)),
+    };
+    match result {
+        Ok(()) => {
+            sync_dir(&target);
+            Ok(())
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            Err(e)
+        }
+    }
+}
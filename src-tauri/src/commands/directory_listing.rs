@@ -0,0 +1,341 @@
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::commands::ignore_rules::{IgnoreRules, IgnoreStatus};
+use crate::types::FileItem;
+
+// Сколько папок держать в кэше; дольше всех не открывавшиеся вытесняются
+const CACHE_LIMIT: usize = 64;
+// Элементов в одном событии "directory-listing-chunk"
+const DEFAULT_CHUNK_SIZE: usize = 500;
+// Как часто чтение большой папки проверяет отмену
+const CANCEL_CHECK_INTERVAL: usize = 1024;
+
+/// Естественный порядок имен: без учета регистра, числа сравниваются как числа ("file2" < "file10")
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut left, mut right) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let ordering = match (left.peek().copied(), right.peek().copied()) {
+            (None, None) => break,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x, y) = (take_number(&mut left), take_number(&mut right));
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            (Some(x), Some(y)) => {
+                left.next();
+                right.next();
+                if x.is_ascii() && y.is_ascii() {
+                    x.to_ascii_lowercase().cmp(&y.to_ascii_lowercase())
+                } else {
+                    x.to_lowercase().cmp(y.to_lowercase())
+                }
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    // Равные без учета регистра и ведущих нулей имена все равно упорядочены однозначно
+    a.cmp(b)
+}
+
+fn take_number(chars: &mut Peekable<Chars>) -> String {
+    let mut number = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        number.push(digit);
+    }
+    number
+}
+
+/// Папки первыми, дальше естественный порядок имен
+fn compare_items(a: &FileItem, b: &FileItem) -> Ordering {
+    b.is_directory
+        .cmp(&a.is_directory)
+        .then_with(|| natural_cmp(&a.name, &b.name))
+}
+
+/// Один и тот же каталог с разными правилами показа - разные записи кэша
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct ListingKey {
+    path: PathBuf,
+    workspace_root: Option<String>,
+    show_ignored: bool,
+}
+
+struct CachedListing {
+    // Время изменения каталога при чтении: меняется при создании, удалении и переименовании элементов
+    modified: Option<SystemTime>,
+    entries: Arc<Vec<FileItem>>,
+    last_used: Instant,
+}
+
+type ListingCache = Mutex<HashMap<ListingKey, CachedListing>>;
+
+/// Кэш отсортированных списков папок и флаги отмены потоковых чтений
+pub struct DirectoryState {
+    cache: Arc<ListingCache>,
+    cancel_flags: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl Default for DirectoryState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DirectoryState {
+    pub fn new() -> Self {
+        DirectoryState {
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            cancel_flags: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Сбрасывает кэш указанных папок; None - весь кэш (например, после правки .gitignore)
+    pub fn invalidate(&self, dirs: Option<&[PathBuf]>) {
+        let Ok(mut listings) = self.cache.lock() else {
+            return;
+        };
+        match dirs {
+            Some(dirs) => listings.retain(|key, _| !dirs.contains(&key.path)),
+            None => listings.clear(),
+        }
+    }
+}
+
+fn modified_of(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Читает папку целиком: без отдельного stat на каждый элемент, с правилами игнорирования.
+/// None - чтение отменено
+fn read_listing(key: &ListingKey, cancel: &AtomicBool) -> Result<Option<Vec<FileItem>>, String> {
+    let rules = IgnoreRules::for_path(&key.path, key.workspace_root.as_deref());
+    // В игнорируемой папке игнорируется все
+    let dir_ignored = match rules.status_with_parents(&key.path, true) {
+        IgnoreStatus::Excluded => return Ok(Some(Vec::new())),
+        status => status == IgnoreStatus::Ignored,
+    };
+    let read_dir = fs::read_dir(&key.path)
+        .map_err(|e| format!("Не удалось прочитать папку {}: {}", key.path.display(), e))?;
+
+    let mut items = Vec::new();
+    for (index, entry) in read_dir.enumerate() {
+        if index % CANCEL_CHECK_INTERVAL == 0 && cancel.load(atomic::Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        // Тип берется из записи каталога; stat нужен только для символических ссылок
+        let is_directory = match entry.file_type() {
+            Ok(file_type) if file_type.is_symlink() => path.is_dir(),
+            Ok(file_type) => file_type.is_dir(),
+            Err(_) => continue,
+        };
+        let ignored = match rules.status(&path, is_directory) {
+            IgnoreStatus::Visible => dir_ignored,
+            IgnoreStatus::Ignored if key.show_ignored => true,
+            _ => continue,
+        };
+        items.push(FileItem {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            is_directory,
+            path: path.to_string_lossy().into_owned(),
+            children: None,
+            ignored,
+            total_children: None,
+        });
+    }
+    if cancel.load(atomic::Ordering::Relaxed) {
+        return Ok(None);
+    }
+    items.sort_by(compare_items);
+    Ok(Some(items))
+}
+
+/// Список из кэша, если папка не менялась с прошлого чтения, иначе читает заново
+fn cached_listing(cache: &ListingCache, key: ListingKey, cancel: &AtomicBool) -> Result<Option<Arc<Vec<FileItem>>>, String> {
+    // Время берется до чтения: изменения во время чтения сбросят кэш при следующем обращении
+    let modified = modified_of(&key.path);
+    if let Ok(mut listings) = cache.lock() {
+        if let Some(listing) = listings
+            .get_mut(&key)
+            .filter(|listing| listing.modified.is_some() && listing.modified == modified)
+        {
+            listing.last_used = Instant::now();
+            return Ok(Some(listing.entries.clone()));
+        }
+    }
+
+    let Some(entries) = read_listing(&key, cancel)? else {
+        return Ok(None);
+    };
+    let entries = Arc::new(entries);
+    if let Ok(mut listings) = cache.lock() {
+        if listings.len() >= CACHE_LIMIT && !listings.contains_key(&key) {
+            let oldest = listings
+                .iter()
+                .min_by_key(|(_, listing)| listing.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                listings.remove(&oldest);
+            }
+        }
+        listings.insert(
+            key,
+            CachedListing {
+                modified,
+                entries: entries.clone(),
+                last_used: Instant::now(),
+            },
+        );
+    }
+    Ok(Some(entries))
+}
+
+/// Страница отсортированного содержимого папки и общее число элементов
+pub async fn list_page(
+    state: &DirectoryState,
+    path: PathBuf,
+    workspace_root: Option<String>,
+    show_ignored: bool,
+    offset: usize,
+    limit: Option<usize>,
+) -> Result<(Vec<FileItem>, usize), String> {
+    let cache = state.cache.clone();
+    let key = ListingKey {
+        path,
+        workspace_root,
+        show_ignored,
+    };
+    tokio::task::spawn_blocking(move || {
+        let entries = cached_listing(&cache, key, &AtomicBool::new(false))?.unwrap_or_default();
+        let page = entries
+            .iter()
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        Ok((page, entries.len()))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Часть содержимого папки, событие "directory-listing-chunk"
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryChunk {
+    pub listing_id: String,
+    pub path: String,
+    pub offset: usize,
+    pub total: usize,
+    pub entries: Vec<FileItem>,
+    pub done: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingResult {
+    pub total: usize,
+    pub cancelled: bool,
+}
+
+fn stream_listing(app: &AppHandle, cache: &ListingCache, key: ListingKey, listing_id: &str, chunk_size: usize, cancel: &AtomicBool) -> Result<ListingResult, String> {
+    let path = key.path.to_string_lossy().to_string();
+    let Some(entries) = cached_listing(cache, key, cancel)? else {
+        return Ok(ListingResult { total: 0, cancelled: true });
+    };
+    let total = entries.len();
+    let mut offset = 0;
+    loop {
+        if cancel.load(atomic::Ordering::Relaxed) {
+            return Ok(ListingResult { total, cancelled: true });
+        }
+        let end = (offset + chunk_size).min(total);
+        let chunk = DirectoryChunk {
+            listing_id: listing_id.to_string(),
+            path: path.clone(),
+            offset,
+            total,
+            entries: entries[offset..end].to_vec(),
+            done: end == total,
+        };
+        app.emit("directory-listing-chunk", chunk).map_err(|e| e.to_string())?;
+        if end == total {
+            return Ok(ListingResult { total, cancelled: false });
+        }
+        offset = end;
+    }
+}
+
+/// Отдает содержимое папки частями по `chunk_size` в событиях "directory-listing-chunk",
+/// уже отсортированным: папки первыми, имена в естественном порядке. Игнорируемые по .gitignore
+/// элементы приходят с пометкой `ignored`, `show_ignored: false` их скрывает.
+/// Свернутый узел отменяет чтение через cancel_directory_listing с тем же `listing_id`
+#[tauri::command]
+pub async fn list_directory(
+    app: AppHandle,
+    state: State<'_, DirectoryState>,
+    path: String,
+    listing_id: String,
+    workspace_root: Option<String>,
+    show_ignored: Option<bool>,
+    chunk_size: Option<usize>,
+) -> Result<ListingResult, String> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    state
+        .cancel_flags
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(listing_id.clone(), cancel.clone());
+
+    let cache = state.cache.clone();
+    let key = ListingKey {
+        path: PathBuf::from(path),
+        workspace_root,
+        show_ignored: show_ignored.unwrap_or(true),
+    };
+    let chunk_size = chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE).max(1);
+    let thread_id = listing_id.clone();
+    let result = tokio::task::spawn_blocking(move || stream_listing(&app, &cache, key, &thread_id, chunk_size, &cancel))
+        .await
+        .map_err(|e| e.to_string());
+    if let Ok(mut flags) = state.cancel_flags.lock() {
+        flags.remove(&listing_id);
+    }
+    result?
+}
+
+#[tauri::command]
+pub fn cancel_directory_listing(state: State<'_, DirectoryState>, listing_id: String) -> Result<bool, String> {
+    let flags = state.cancel_flags.lock().map_err(|e| e.to_string())?;
+    Ok(match flags.get(&listing_id) {
+        Some(flag) => {
+            flag.store(true, atomic::Ordering::Relaxed);
+            true
+        }
+        None => false,
+    })
+}
+
+/// Сбрасывает кэш папок; без `paths` - весь кэш
+#[tauri::command]
+pub fn invalidate_directory_cache(state: State<'_, DirectoryState>, paths: Option<Vec<String>>) {
+    let dirs: Option<Vec<PathBuf>> = paths.map(|paths| paths.iter().map(PathBuf::from).collect());
+    state.invalidate(dirs.as_deref());
+}
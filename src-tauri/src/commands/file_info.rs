@@ -0,0 +1,312 @@
+use serde::Serialize;
+use std::fs::{self, File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::commands::text_encoding::{self, TextEncoding};
+
+// Сколько байт начала файла смотреть, чтобы определить тип
+const SNIFF_SIZE: usize = 8192;
+// Строки больших файлов не считаются: это чтение всего файла ради подсказки
+const MAX_LINE_COUNT_SIZE: u64 = 64 << 20;
+
+/// Сведения о файле для подсказки в проводнике, нижней панели и выбора просмотрщика
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileInfo {
+    pub path: String,
+    pub name: String,
+    pub is_dir: bool,
+    pub is_file: bool,
+    pub size: u64,
+    // мс с начала эпохи Unix
+    pub modified: Option<u64>,
+    pub created: Option<u64>,
+    // Изменение метаданных (ctime), только Unix
+    pub changed: Option<u64>,
+    pub readonly: bool,
+    // "rwxr-xr-x" и режим, только Unix
+    pub permissions: Option<String>,
+    pub mode: Option<u32>,
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    pub is_symlink: bool,
+    pub symlink_target: Option<String>,
+    // Ссылка указывает на несуществующий файл
+    pub broken_symlink: bool,
+    // По содержимому, а не только по расширению
+    pub mime_type: Option<String>,
+    pub is_text: bool,
+    pub encoding: Option<String>,
+    pub line_count: Option<u64>,
+}
+
+fn millis(time: std::io::Result<SystemTime>) -> Option<u64> {
+    let duration = time.ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(duration.as_millis() as u64)
+}
+
+/// Права и владелец - только в Unix
+#[derive(Default)]
+struct UnixDetails {
+    changed: Option<u64>,
+    permissions: Option<String>,
+    mode: Option<u32>,
+    owner: Option<String>,
+    group: Option<String>,
+}
+
+#[cfg(unix)]
+fn unix_details(metadata: &Metadata) -> UnixDetails {
+    use std::os::unix::fs::MetadataExt;
+    let mode = metadata.mode();
+    let permissions: String = [0o400, 0o200, 0o100, 0o040, 0o020, 0o010, 0o004, 0o002, 0o001]
+        .iter()
+        .zip("rwxrwxrwx".chars())
+        .map(|(bit, flag)| if mode & bit != 0 { flag } else { '-' })
+        .collect();
+    UnixDetails {
+        changed: u64::try_from(metadata.ctime() * 1000 + metadata.ctime_nsec() / 1_000_000).ok(),
+        permissions: Some(permissions),
+        mode: Some(mode & 0o7777),
+        owner: Some(user_name(metadata.uid()).unwrap_or_else(|| metadata.uid().to_string())),
+        group: Some(group_name(metadata.gid()).unwrap_or_else(|| metadata.gid().to_string())),
+    }
+}
+
+#[cfg(not(unix))]
+fn unix_details(_metadata: &Metadata) -> UnixDetails {
+    UnixDetails::default()
+}
+
+#[cfg(unix)]
+fn user_name(uid: u32) -> Option<String> {
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result = std::ptr::null_mut();
+    let status = unsafe { libc::getpwuid_r(uid, &mut entry, buffer.as_mut_ptr(), buffer.len(), &mut result) };
+    if status != 0 || result.is_null() {
+        return None;
+    }
+    let name = unsafe { std::ffi::CStr::from_ptr(entry.pw_name) };
+    Some(name.to_string_lossy().to_string())
+}
+
+#[cfg(unix)]
+fn group_name(gid: u32) -> Option<String> {
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    let mut entry: libc::group = unsafe { std::mem::zeroed() };
+    let mut result = std::ptr::null_mut();
+    let status = unsafe { libc::getgrgid_r(gid, &mut entry, buffer.as_mut_ptr(), buffer.len(), &mut result) };
+    if status != 0 || result.is_null() {
+        return None;
+    }
+    let name = unsafe { std::ffi::CStr::from_ptr(entry.gr_name) };
+    Some(name.to_string_lossy().to_string())
+}
+
+/// Тип по сигнатуре в начале файла
+pub fn sniff_binary(head: &[u8]) -> Option<&'static str> {
+    let at = |offset: usize, magic: &[u8]| head.get(offset..offset + magic.len()) == Some(magic);
+    let mime = match () {
+        _ if at(0, b"\x89PNG\r\n\x1a\n") => "image/png",
+        _ if at(0, b"\xFF\xD8\xFF") => "image/jpeg",
+        _ if at(0, b"GIF87a") || at(0, b"GIF89a") => "image/gif",
+        _ if at(0, b"RIFF") && at(8, b"WEBP") => "image/webp",
+        _ if at(0, b"RIFF") && at(8, b"WAVE") => "audio/wav",
+        _ if at(0, b"RIFF") && at(8, b"AVI ") => "video/x-msvideo",
+        // У BMP байты 6..10 зарезервированы и равны нулю: текст, начинающийся с "BM", не подходит
+        _ if at(0, b"BM") && at(6, b"\x00\x00\x00\x00") => "image/bmp",
+        _ if at(0, b"\x00\x00\x01\x00") => "image/x-icon",
+        _ if at(0, b"II*\x00") || at(0, b"MM\x00*") => "image/tiff",
+        _ if at(4, b"ftypqt") => "video/quicktime",
+        _ if at(4, b"ftypavif") => "image/avif",
+        _ if at(4, b"ftypheic") => "image/heic",
+        _ if at(4, b"ftyp") => "video/mp4",
+        _ if at(0, b"\x1A\x45\xDF\xA3") => "video/webm",
+        _ if at(0, b"ID3") || at(0, b"\xFF\xFB") || at(0, b"\xFF\xF3") => "audio/mpeg",
+        _ if at(0, b"OggS") => "audio/ogg",
+        _ if at(0, b"fLaC") => "audio/flac",
+        _ if at(0, b"%PDF-") => "application/pdf",
+        _ if at(0, b"PK\x03\x04") || at(0, b"PK\x05\x06") => "application/zip",
+        _ if at(0, b"\x1F\x8B") => "application/gzip",
+        _ if at(0, b"BZh") => "application/x-bzip2",
+        _ if at(0, b"\xFD7zXZ\x00") => "application/x-xz",
+        _ if at(0, b"\x28\xB5\x2F\xFD") => "application/zstd",
+        _ if at(0, b"7z\xBC\xAF\x27\x1C") => "application/x-7z-compressed",
+        _ if at(0, b"Rar!\x1A\x07") => "application/vnd.rar",
+        _ if at(257, b"ustar") => "application/x-tar",
+        _ if at(0, b"\x7FELF") => "application/x-elf",
+        _ if at(0, b"MZ") && head.len() >= 64 && head.contains(&0) => "application/vnd.microsoft.portable-executable",
+        _ if at(0, b"\xCF\xFA\xED\xFE") || at(0, b"\xCE\xFA\xED\xFE") => "application/x-mach-binary",
+        _ if at(0, b"\x00asm") => "application/wasm",
+        _ if at(0, b"wOFF") => "font/woff",
+        _ if at(0, b"wOF2") => "font/woff2",
+        _ if at(0, b"\x00\x01\x00\x00\x00") => "font/ttf",
+        _ if at(0, b"OTTO") => "font/otf",
+        _ if at(0, b"SQLite format 3\x00") => "application/vnd.sqlite3",
+        _ if at(0, b"\xCA\xFE\xBA\xBE") => "application/java-vm",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// Тип текстового файла: SVG и XML по содержимому, остальное - по расширению
+fn sniff_text(path: &Path, head: &str) -> &'static str {
+    let start = head.trim_start_matches('\u{feff}').trim_start();
+    if start.starts_with("<svg") || (start.starts_with("<?xml") && head.contains("<svg")) {
+        return "image/svg+xml";
+    }
+    if start.starts_with("#!") {
+        return "text/x-shellscript";
+    }
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "json" => "application/json",
+        "js" | "mjs" | "cjs" | "jsx" => "text/javascript",
+        "ts" | "tsx" => "text/typescript",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "md" | "markdown" => "text/markdown",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "toml" => "application/toml",
+        "csv" => "text/csv",
+        "py" => "text/x-python",
+        "rs" => "text/x-rust",
+        "sh" | "bash" => "text/x-shellscript",
+        _ if start.starts_with("<?xml") => "application/xml",
+        _ if start.starts_with("<!DOCTYPE html") || start.starts_with("<html") => "text/html",
+        _ => "text/plain",
+    }
+}
+
+/// Число строк, не загружая файл в память. Строки разделяет \n, как в режиме больших
+/// файлов (\r\n - тоже один перевод строки, одиночный \r - нет). None - файл больше
+/// MAX_LINE_COUNT_SIZE
+fn count_lines(path: &Path, encoding: TextEncoding) -> Option<u64> {
+    // Переводы строк сравниваются целыми единицами кодировки: в UTF-16 - по 2 байта, в UTF-32 - по 4
+    let lf = encoding.with_bom(false).encode("\n").bytes;
+    let unit = lf.len();
+    let mut file = File::open(path).ok()?;
+    if file.metadata().ok()?.len() > MAX_LINE_COUNT_SIZE {
+        return None;
+    }
+    let bom = encoding.bom_bytes();
+    let mut head = vec![0u8; bom.len()];
+    if bom.is_empty() || file.read_exact(&mut head).is_err() || head != bom {
+        file.seek(SeekFrom::Start(0)).ok()?;
+    }
+
+    let mut buffer = vec![0u8; 64 * 1024];
+    // Начало символа, разрезанного границей чтения, переносится в начало буфера
+    let mut carried = 0;
+    let (mut breaks, mut at_break, mut any) = (0u64, false, false);
+    loop {
+        let read = file.read(&mut buffer[carried..]).ok()?;
+        if read == 0 {
+            break;
+        }
+        let filled = carried + read;
+        let usable = filled - filled % unit;
+        for code in buffer[..usable].chunks_exact(unit) {
+            any = true;
+            at_break = code == lf.as_slice();
+            breaks += at_break as u64;
+        }
+        buffer.copy_within(usable..filled, 0);
+        carried = filled - usable;
+    }
+    // Последняя строка без перевода строки тоже строка
+    Some(breaks + (any && !at_break) as u64)
+}
+
+fn read_head(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(SNIFF_SIZE);
+    File::open(path)?.take(SNIFF_SIZE as u64).read_to_end(&mut head)?;
+    Ok(head)
+}
+
+pub fn file_info(path: &Path) -> Result<FileInfo, String> {
+    let link_metadata = fs::symlink_metadata(path).map_err(|_| format!("Путь не существует: {}", path.display()))?;
+    let is_symlink = link_metadata.file_type().is_symlink();
+    let target_metadata = if is_symlink { fs::metadata(path).ok() } else { None };
+    let metadata = target_metadata.as_ref().unwrap_or(&link_metadata);
+    let unix = unix_details(metadata);
+
+    let mut info = FileInfo {
+        path: path.to_string_lossy().to_string(),
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        is_dir: metadata.is_dir(),
+        is_file: metadata.is_file(),
+        size: if metadata.is_dir() { 0 } else { metadata.len() },
+        modified: millis(metadata.modified()),
+        created: millis(metadata.created()),
+        changed: unix.changed,
+        readonly: metadata.permissions().readonly(),
+        permissions: unix.permissions,
+        mode: unix.mode,
+        owner: unix.owner,
+        group: unix.group,
+        is_symlink,
+        symlink_target: is_symlink
+            .then(|| fs::read_link(path).ok())
+            .flatten()
+            .map(|target| target.to_string_lossy().to_string()),
+        broken_symlink: is_symlink && target_metadata.is_none(),
+        mime_type: None,
+        is_text: false,
+        encoding: None,
+        line_count: None,
+    };
+    if !info.is_file {
+        return Ok(info);
+    }
+
+    let head = read_head(path).map_err(|e| format!("Не удалось открыть файл: {}", e))?;
+    if let Some(mime) = sniff_binary(&head) {
+        info.mime_type = Some(mime.to_string());
+        return Ok(info);
+    }
+    match text_encoding::detect_sample(&head) {
+        Ok(encoding) => {
+            let (text, _) = encoding.decode(&head);
+            info.mime_type = Some(sniff_text(path, &text).to_string());
+            info.is_text = true;
+            info.encoding = Some(encoding.label.to_string());
+            info.line_count = count_lines(path, encoding);
+        }
+        Err(_) => info.mime_type = Some("application/octet-stream".to_string()),
+    }
+    Ok(info)
+}
+
+#[tauri::command]
+pub async fn fs_get_file_info(path: String) -> Result<FileInfo, String> {
+    tokio::task::spawn_blocking(move || file_info(Path::new(&path)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn fs_is_directory(path: String) -> Result<bool, String> {
+    let metadata = fs::metadata(&path).map_err(|_| format!("Путь не существует: {}", path))?;
+    Ok(metadata.is_dir())
+}
+
+/// Ошибка, если папки нет: фронтенд проверяет именно успешность вызова
+#[tauri::command]
+pub fn check_directory_exists(path: String) -> Result<bool, String> {
+    if Path::new(&path).is_dir() {
+        Ok(true)
+    } else {
+        Err(format!("Папка '{}' не существует", path))
+    }
+}
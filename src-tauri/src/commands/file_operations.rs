@@ -1,12 +1,25 @@
+use serde::Serialize;
 use std::fs;
 use std::path::Path;
-use tauri::command;
+use tauri::{command, State};
 use std::io::Write;
 
+use crate::atomic_write::{write_atomic, WriteError};
+use crate::commands::fs_journal::{FsJournalState, FsOperation};
+use crate::commands::line_endings::{self, LineEnding};
+use crate::commands::text_encoding::{self, TextEncoding, UnmappableChar};
+use crate::reading::FileVersion;
+
 #[command]
-pub fn create_file(path: String) -> Result<(), String> {
+pub fn create_file(
+    journal: State<'_, FsJournalState>,
+    path: String,
+    workspace_root: Option<String>,
+) -> Result<(), String> {
     println!("Attempting to create file at: {}", path);
     let file_path = Path::new(&path);
+    // Существующий файл File::create только очищает - отменять здесь нечего
+    let existed = file_path.exists();
     
     // Ensure parent directory exists
     if let Some(parent) = file_path.parent() {
@@ -35,6 +48,9 @@ pub fn create_file(path: String) -> Result<(), String> {
     match file.write_all("".as_bytes()) {
         Ok(_) => {
             println!("File initialized with empty content: {}", path);
+            if !existed {
+                journal.record(workspace_root.as_deref(), FsOperation::created(file_path));
+            }
             Ok(())
         },
         Err(e) => {
@@ -46,7 +62,11 @@ pub fn create_file(path: String) -> Result<(), String> {
 }
 
 #[command]
-pub fn create_folder(path: String) -> Result<(), String> {
+pub fn create_folder(
+    journal: State<'_, FsJournalState>,
+    path: String,
+    workspace_root: Option<String>,
+) -> Result<(), String> {
     println!("Attempting to create folder at: {}", path);
     let folder_path = Path::new(&path);
     
@@ -59,6 +79,7 @@ pub fn create_folder(path: String) -> Result<(), String> {
     match fs::create_dir_all(folder_path) {
         Ok(_) => {
             println!("Folder created successfully: {}", path);
+            journal.record(workspace_root.as_deref(), FsOperation::created(folder_path));
             Ok(())
         },
         Err(e) => {
@@ -69,21 +90,142 @@ pub fn create_folder(path: String) -> Result<(), String> {
     }
 }
 
+/// Результат сохранения текстового файла
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveResult {
+    pub encoding: String,
+    pub line_ending: Option<LineEnding>,
+    pub bom: bool,
+    // Символы, замененные на '?' при сохранении с allow_lossy
+    pub replaced_count: usize,
+    pub replaced: Vec<UnmappableChar>,
+    // Версия записанного файла - ожидаемая версия для следующего сохранения
+    pub version: FileVersion,
+}
+
+/// Ошибка сохранения. Conflict - файл изменили на диске после чтения: интерфейс может
+/// сравнить версии, перезаписать (сохранить без expected_version) или перечитать файл
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum SaveError {
+    ReadOnly { path: String, message: String },
+    Failed { message: String },
+    Conflict(Box<SaveConflict>),
+}
+
+/// Что сейчас на диске вместо ожидаемой версии
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveConflict {
+    pub path: String,
+    pub message: String,
+    pub disk_content: String,
+    pub disk_encoding: String,
+    pub disk_version: FileVersion,
+}
+
+impl From<WriteError> for SaveError {
+    fn from(error: WriteError) -> Self {
+        match error {
+            WriteError::ReadOnly { path, message } => SaveError::ReadOnly { path, message },
+            WriteError::Failed { message } => SaveError::Failed { message },
+        }
+    }
+}
+
+impl From<String> for SaveError {
+    fn from(message: String) -> Self {
+        SaveError::Failed { message }
+    }
+}
+
+/// Сохраняет текст в кодировке `encoding`; без нее - в той, в которой файл уже записан
+/// (новый файл - в UTF-8). Если часть символов не представима в кодировке, файл
+/// не сохраняется, пока не передан `allow_lossy`.
+///
+/// Переводы строк и BOM по умолчанию остаются такими, как в файле на диске:
+/// `line_ending` ("LF", "CRLF", "CR") и `bom` задают их явно.
+///
+/// Запись атомарная (см. `atomic_write`); файл только для чтения дает ошибку `readOnly`.
+/// С `expected_version` (версия из read_text_document или прошлого сохранения) файл,
+/// измененный на диске с тех пор, не перезаписывается - возвращается `conflict`.
 #[command]
-pub fn save_file(path: String, content: String) -> Result<(), String> {
+pub fn save_file(
+    path: String,
+    content: String,
+    encoding: Option<String>,
+    allow_lossy: Option<bool>,
+    line_ending: Option<String>,
+    bom: Option<bool>,
+    expected_version: Option<FileVersion>,
+) -> Result<SaveResult, SaveError> {
     let file_path = Path::new(&path);
-    
-    // Create a file with explicit UTF-8 encoding
-    let mut file = match std::fs::File::create(file_path) {
-        Ok(file) => file,
-        Err(e) => return Err(format!("Ошибка при создании файла: {}", e)),
+    let existing = fs::read(file_path).ok();
+    let on_disk = existing.as_deref().and_then(|bytes| text_encoding::detect(bytes).ok());
+
+    // Удаленный с диска файл просто создается заново
+    if let (Some(expected), Some(bytes)) = (&expected_version, &existing) {
+        let disk_version = FileVersion::of(file_path, bytes);
+        if !disk_version.same_content(expected) {
+            let disk_encoding = on_disk.unwrap_or(TextEncoding::UTF8);
+            return Err(SaveError::Conflict(Box::new(SaveConflict {
+                path: path.clone(),
+                message: format!("Файл '{}' изменен на диске", path),
+                disk_content: disk_encoding.decode(bytes).0,
+                disk_encoding: disk_encoding.label.to_string(),
+                disk_version,
+            })));
+        }
+    }
+
+    let mut encoding = match encoding {
+        Some(name) => TextEncoding::from_name(&name)?,
+        None => on_disk.unwrap_or(TextEncoding::UTF8),
     };
-    
-    // Write content with UTF-8 encoding
-    match file.write_all(content.as_bytes()) {
-        Ok(_) => Ok(()),
-        Err(e) => Err(format!("Ошибка при сохранении файла: {}", e)),
+    if let Some(bom) = bom {
+        encoding = encoding.with_bom(bom);
     }
+
+    let target = match line_ending {
+        Some(name) => Some(LineEnding::from_name(&name)?),
+        // Смешанные переводы строк не трогаем: неизвестно, к какому виду приводить
+        None => existing
+            .as_deref()
+            .zip(on_disk)
+            .and_then(|(bytes, encoding)| line_endings::detect(&encoding.decode(bytes).0))
+            .filter(|line_ending| *line_ending != LineEnding::Mixed),
+    };
+    let content = match target {
+        Some(target) => line_endings::convert(&content, target),
+        None => content,
+    };
+
+    let encoded = encoding.encode(&content);
+    if encoded.unmappable_count > 0 && !allow_lossy.unwrap_or(false) {
+        let examples: Vec<String> = encoded
+            .unmappable
+            .iter()
+            .take(5)
+            .map(|c| format!("'{}' (строка {}, столбец {})", c.character, c.line, c.column))
+            .collect();
+        return Err(SaveError::from(format!(
+            "Символов, не представимых в кодировке {}: {}. Например: {}",
+            encoding.label,
+            encoded.unmappable_count,
+            examples.join(", ")
+        )));
+    }
+
+    write_atomic(file_path, &encoded.bytes)?;
+    Ok(SaveResult {
+        encoding: encoding.label.to_string(),
+        line_ending: line_endings::detect(&content),
+        bom: encoding.bom,
+        replaced_count: encoded.unmappable_count,
+        replaced: encoded.unmappable,
+        version: FileVersion::of(file_path, &encoded.bytes),
+    })
 }
 
 #[command]
@@ -0,0 +1,555 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use walkdir::WalkDir;
+
+use crate::commands::fs_journal::{FsJournalState, FsOperation};
+use crate::commands::trash::{self, TrashedItem};
+
+// Копирование идет блоками, чтобы между ними проверять отмену и сообщать прогресс
+const CHUNK_SIZE: usize = 1024 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Что делать, если в папке назначения уже есть элемент с таким именем
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictPolicy {
+    Skip,
+    // Существующий элемент уходит в корзину
+    Overwrite,
+    // Новое имя "name (1).ext"
+    KeepBoth,
+    // Ничего не делать и сообщить об ошибке - для элементов, о которых пользователя не спросили
+    Fail,
+}
+
+/// Прогресс копирования или перемещения, событие "file-transfer-progress"
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferProgress {
+    pub transfer_id: String,
+    pub current_path: String,
+    pub processed_files: u64,
+    pub total_files: u64,
+    pub processed_bytes: u64,
+    pub total_bytes: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferredItem {
+    pub source: String,
+    pub destination: String,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferResult {
+    pub transferred: Vec<TransferredItem>,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+    pub cancelled: bool,
+}
+
+/// Элемент, который уже есть в папке назначения
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferConflict {
+    pub source: String,
+    pub destination: String,
+    pub source_is_directory: bool,
+    pub destination_is_directory: bool,
+}
+
+/// Флаги отмены идущих копирований, ключ - идентификатор, который дал фронтенд
+pub struct TransferState {
+    cancel_flags: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl Default for TransferState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransferState {
+    pub fn new() -> Self {
+        TransferState {
+            cancel_flags: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// "name.ext" -> "name (1).ext": первое свободное имя в папке
+pub fn unique_destination(dir: &Path, name: &str, is_directory: bool) -> PathBuf {
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !is_directory && !stem.is_empty() => (stem, Some(extension)),
+        _ => (name, None),
+    };
+    (1..)
+        .map(|number| match extension {
+            Some(extension) => dir.join(format!("{} ({}).{}", stem, number, extension)),
+            None => dir.join(format!("{} ({})", stem, number)),
+        })
+        .find(|candidate| candidate.symlink_metadata().is_err())
+        .unwrap_or_default()
+}
+
+/// Общий размер и число файлов - для процентов прогресса
+fn measure(sources: &[PathBuf]) -> (u64, u64) {
+    sources
+        .iter()
+        .flat_map(|source| WalkDir::new(source).into_iter().filter_map(|entry| entry.ok()))
+        .filter(|entry| !entry.file_type().is_dir())
+        .fold((0, 0), |(files, bytes), entry| {
+            (files + 1, bytes + entry.metadata().map_or(0, |metadata| metadata.len()))
+        })
+}
+
+/// Копирование с прогрессом и отменой
+struct Copier<'a> {
+    app: Option<&'a AppHandle>,
+    cancel: Arc<AtomicBool>,
+    progress: TransferProgress,
+    last_report: Instant,
+}
+
+impl Copier<'_> {
+    fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    fn report(&mut self, path: &Path, force: bool) {
+        let Some(app) = self.app else {
+            return;
+        };
+        if !force && self.last_report.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        self.last_report = Instant::now();
+        self.progress.current_path = path.to_string_lossy().to_string();
+        let _ = app.emit("file-transfer-progress", self.progress.clone());
+    }
+
+    fn copy_file(&mut self, from: &Path, to: &Path) -> Result<(), String> {
+        let mut reader = File::open(from).map_err(|e| format!("Не удалось открыть {}: {}", from.display(), e))?;
+        let mut writer = File::create(to).map_err(|e| format!("Не удалось создать {}: {}", to.display(), e))?;
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        loop {
+            if self.cancelled() {
+                return Err("Отменено".to_string());
+            }
+            let read = reader
+                .read(&mut buffer)
+                .map_err(|e| format!("Ошибка чтения {}: {}", from.display(), e))?;
+            if read == 0 {
+                break;
+            }
+            writer
+                .write_all(&buffer[..read])
+                .map_err(|e| format!("Ошибка записи {}: {}", to.display(), e))?;
+            self.progress.processed_bytes += read as u64;
+            self.report(from, false);
+        }
+        if let Ok(metadata) = fs::metadata(from) {
+            let _ = writer.set_permissions(metadata.permissions());
+        }
+        self.progress.processed_files += 1;
+        Ok(())
+    }
+
+    /// Копирует файл или папку целиком; символические ссылки копируются как ссылки
+    fn copy_tree(&mut self, from: &Path, to: &Path) -> Result<(), String> {
+        for entry in WalkDir::new(from) {
+            if self.cancelled() {
+                return Err("Отменено".to_string());
+            }
+            let entry = entry.map_err(|e| e.to_string())?;
+            let relative = entry.path().strip_prefix(from).map_err(|e| e.to_string())?;
+            let target = if relative.as_os_str().is_empty() {
+                to.to_path_buf()
+            } else {
+                to.join(relative)
+            };
+            let file_type = entry.file_type();
+            if file_type.is_dir() {
+                fs::create_dir_all(&target).map_err(|e| format!("Не удалось создать {}: {}", target.display(), e))?;
+            } else if file_type.is_symlink() {
+                copy_symlink(entry.path(), &target)?;
+                self.progress.processed_files += 1;
+            } else {
+                self.copy_file(entry.path(), &target)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> Result<(), String> {
+    let target = fs::read_link(from).map_err(|e| e.to_string())?;
+    std::os::unix::fs::symlink(target, to).map_err(|e| format!("Не удалось создать ссылку {}: {}", to.display(), e))
+}
+
+#[cfg(windows)]
+fn copy_symlink(from: &Path, to: &Path) -> Result<(), String> {
+    let target = fs::read_link(from).map_err(|e| e.to_string())?;
+    let created = if from.is_dir() {
+        std::os::windows::fs::symlink_dir(target, to)
+    } else {
+        std::os::windows::fs::symlink_file(target, to)
+    };
+    created.map_err(|e| format!("Не удалось создать ссылку {}: {}", to.display(), e))
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Перемещение: rename, а между файловыми системами - копирование и удаление исходного
+fn move_with(copier: &mut Copier, from: &Path, to: &Path) -> Result<(), String> {
+    match fs::rename(from, to) {
+        Ok(()) => {
+            let (files, bytes) = measure(&[to.to_path_buf()]);
+            copier.progress.processed_files += files;
+            copier.progress.processed_bytes += bytes;
+            Ok(())
+        }
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            if let Err(e) = copier.copy_tree(from, to) {
+                let _ = remove_path(to);
+                return Err(e);
+            }
+            remove_path(from).map_err(|e| format!("Скопировано, но не удалось удалить {}: {}", from.display(), e))
+        }
+        Err(e) => Err(format!("Ошибка при перемещении {}: {}", from.display(), e)),
+    }
+}
+
+/// Перемещает файл или папку, в том числе на другой диск (для отмены операций)
+pub fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+    let mut copier = Copier {
+        app: None,
+        cancel: Arc::new(AtomicBool::new(false)),
+        progress: empty_progress(String::new()),
+        last_report: Instant::now(),
+    };
+    move_with(&mut copier, from, to)
+}
+
+fn empty_progress(transfer_id: String) -> TransferProgress {
+    TransferProgress {
+        transfer_id,
+        current_path: String::new(),
+        processed_files: 0,
+        total_files: 0,
+        processed_bytes: 0,
+        total_bytes: 0,
+    }
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn destination_for(source: &Path, destination_dir: &Path) -> Result<PathBuf, String> {
+    let name = source
+        .file_name()
+        .ok_or_else(|| format!("Некорректный путь: {}", source.display()))?;
+    Ok(destination_dir.join(name))
+}
+
+/// Элементы, которые при копировании в `destination_dir` столкнутся с уже существующими
+#[tauri::command]
+pub fn check_transfer_conflicts(sources: Vec<String>, destination_dir: String) -> Result<Vec<TransferConflict>, String> {
+    let mut conflicts = Vec::new();
+    for source in &sources {
+        let source = Path::new(source);
+        let destination = destination_for(source, Path::new(&destination_dir))?;
+        if let Ok(existing) = destination.symlink_metadata() {
+            conflicts.push(TransferConflict {
+                source: source.to_string_lossy().to_string(),
+                destination: destination.to_string_lossy().to_string(),
+                source_is_directory: source.is_dir(),
+                destination_is_directory: existing.is_dir(),
+            });
+        }
+    }
+    Ok(conflicts)
+}
+
+/// Параметры копирования и перемещения
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferOptions {
+    // Для конфликтов по умолчанию; без него конфликт - ошибка элемента
+    pub policy: Option<ConflictPolicy>,
+    // Решения пользователя по отдельным элементам, ключ - исходный путь
+    #[serde(default)]
+    pub item_policies: HashMap<String, ConflictPolicy>,
+    // Идентификатор для событий прогресса и отмены
+    pub transfer_id: Option<String>,
+    pub workspace_root: Option<String>,
+}
+
+struct TransferRequest {
+    sources: Vec<PathBuf>,
+    destination_dir: PathBuf,
+    is_move: bool,
+    policy: ConflictPolicy,
+    item_policies: HashMap<String, ConflictPolicy>,
+    workspace_root: Option<String>,
+}
+
+impl TransferRequest {
+    fn new(sources: Vec<PathBuf>, destination_dir: PathBuf, is_move: bool, options: TransferOptions) -> Self {
+        TransferRequest {
+            sources,
+            destination_dir,
+            is_move,
+            policy: options.policy.unwrap_or(ConflictPolicy::Fail),
+            item_policies: options.item_policies,
+            workspace_root: options.workspace_root,
+        }
+    }
+}
+
+fn run_transfer(app: &AppHandle, transfer_id: String, cancel: Arc<AtomicBool>, request: TransferRequest) -> TransferResult {
+    let mut result = TransferResult::default();
+    let (total_files, total_bytes) = measure(&request.sources);
+    let mut copier = Copier {
+        app: Some(app),
+        cancel,
+        progress: TransferProgress {
+            total_files,
+            total_bytes,
+            ..empty_progress(transfer_id)
+        },
+        last_report: Instant::now(),
+    };
+    let journal = app.try_state::<FsJournalState>();
+    let record = |operation: FsOperation| {
+        if let Some(journal) = &journal {
+            journal.record(request.workspace_root.as_deref(), operation);
+        }
+    };
+
+    for source in &request.sources {
+        if copier.cancelled() {
+            result.cancelled = true;
+            break;
+        }
+        let source_text = source.to_string_lossy().to_string();
+        let Ok(metadata) = source.symlink_metadata() else {
+            result.errors.push(format!("{}: не существует", source_text));
+            continue;
+        };
+        if metadata.is_dir() && request.destination_dir.starts_with(source) {
+            result.errors.push(format!("{}: нельзя скопировать папку в саму себя", source_text));
+            continue;
+        }
+        let mut destination = match destination_for(source, &request.destination_dir) {
+            Ok(destination) => destination,
+            Err(e) => {
+                result.errors.push(e);
+                continue;
+            }
+        };
+
+        // Заменяемый элемент: уходит в корзину только после успешного копирования во временное имя рядом
+        let mut replaced: Option<PathBuf> = None;
+        if destination.symlink_metadata().is_ok() {
+            let policy = request.item_policies.get(&source_text).copied().unwrap_or(request.policy);
+            let same_item = destination == *source;
+            match policy {
+                // Вставка в ту же папку - всегда копия рядом
+                _ if same_item && !request.is_move => {
+                    destination = unique_destination(&request.destination_dir, &file_name_of(source), metadata.is_dir());
+                }
+                _ if same_item => {
+                    result.skipped.push(source_text);
+                    continue;
+                }
+                ConflictPolicy::Skip => {
+                    result.skipped.push(source_text);
+                    continue;
+                }
+                ConflictPolicy::Fail => {
+                    result.errors.push(format!("{}: уже существует", destination.display()));
+                    continue;
+                }
+                ConflictPolicy::KeepBoth => {
+                    destination = unique_destination(&request.destination_dir, &file_name_of(source), metadata.is_dir());
+                }
+                ConflictPolicy::Overwrite => {
+                    let temporary = format!(".{}.part", file_name_of(source));
+                    let temporary = unique_destination(&request.destination_dir, &temporary, metadata.is_dir());
+                    replaced = Some(std::mem::replace(&mut destination, temporary));
+                }
+            }
+        }
+
+        let done = if request.is_move {
+            move_with(&mut copier, source, &destination)
+        } else {
+            let copied = copier.copy_tree(source, &destination);
+            if copied.is_err() {
+                // Недокопированный элемент не оставляем
+                let _ = remove_path(&destination);
+            }
+            copied
+        };
+        let done = match (done, replaced) {
+            (Ok(()), Some(target)) => replace_with(&destination, &target, source, request.is_move).map(|item| {
+                record(FsOperation::trashed(item));
+                destination = target;
+            }),
+            (done, _) => done,
+        };
+        match done {
+            Ok(()) => {
+                record(if request.is_move {
+                    FsOperation::renamed(source, &destination)
+                } else {
+                    FsOperation::created(&destination)
+                });
+                result.transferred.push(TransferredItem {
+                    source: source_text,
+                    destination: destination.to_string_lossy().to_string(),
+                });
+            }
+            Err(_) if copier.cancelled() => {
+                result.cancelled = true;
+                break;
+            }
+            Err(e) => result.errors.push(format!("{}: {}", source_text, e)),
+        }
+    }
+
+    copier.report(&request.destination_dir, true);
+    result
+}
+
+/// Отправляет `target` в корзину и ставит на его место скопированный во временное имя `temporary`.
+/// Если корзина не приняла `target`, временная копия убирается (перемещенное возвращается в `source`)
+fn replace_with(temporary: &Path, target: &Path, source: &Path, is_move: bool) -> Result<TrashedItem, String> {
+    let item = match trash::move_to_trash(target) {
+        Ok(item) => item,
+        Err(e) => {
+            let _ = if is_move { move_path(temporary, source) } else { remove_path(temporary).map_err(|e| e.to_string()) };
+            return Err(format!("{}: {}", target.display(), e));
+        }
+    };
+    fs::rename(temporary, target).map_err(|e| {
+        format!("{} перемещен в корзину, но не удалось переименовать {}: {}", target.display(), temporary.display(), e)
+    })?;
+    Ok(item)
+}
+
+async fn start_transfer(
+    app: AppHandle,
+    state: State<'_, TransferState>,
+    transfer_id: Option<String>,
+    request: TransferRequest,
+) -> Result<TransferResult, String> {
+    if !request.destination_dir.is_dir() {
+        return Err(format!("Папка '{}' не существует", request.destination_dir.display()));
+    }
+    let transfer_id = transfer_id.unwrap_or_else(|| format!("transfer-{}", chrono::Utc::now().timestamp_millis()));
+    let cancel = Arc::new(AtomicBool::new(false));
+    state
+        .cancel_flags
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(transfer_id.clone(), cancel.clone());
+
+    let thread_app = app.clone();
+    let thread_id = transfer_id.clone();
+    let result = tokio::task::spawn_blocking(move || run_transfer(&thread_app, thread_id, cancel, request))
+        .await
+        .map_err(|e| e.to_string());
+    if let Ok(mut flags) = state.cancel_flags.lock() {
+        flags.remove(&transfer_id);
+    }
+    result
+}
+
+/// Копирует файлы и папки в `destination_dir`. Конфликты решаются по `options.policy`
+/// и `options.itemPolicies`; прогресс приходит событием "file-transfer-progress"
+/// с `options.transferId`, по нему же работает отмена
+#[tauri::command]
+pub async fn copy_files(
+    app: AppHandle,
+    state: State<'_, TransferState>,
+    sources: Vec<String>,
+    destination_dir: String,
+    options: Option<TransferOptions>,
+) -> Result<TransferResult, String> {
+    let options = options.unwrap_or_default();
+    let transfer_id = options.transfer_id.clone();
+    let sources = sources.iter().map(PathBuf::from).collect();
+    let request = TransferRequest::new(sources, PathBuf::from(destination_dir), false, options);
+    start_transfer(app, state, transfer_id, request).await
+}
+
+/// Перемещает файлы и папки в `destination_dir`; параметры как у copy_files
+#[tauri::command]
+pub async fn move_files(
+    app: AppHandle,
+    state: State<'_, TransferState>,
+    sources: Vec<String>,
+    destination_dir: String,
+    options: Option<TransferOptions>,
+) -> Result<TransferResult, String> {
+    let options = options.unwrap_or_default();
+    let transfer_id = options.transfer_id.clone();
+    let sources = sources.iter().map(PathBuf::from).collect();
+    let request = TransferRequest::new(sources, PathBuf::from(destination_dir), true, options);
+    start_transfer(app, state, transfer_id, request).await
+}
+
+/// Создает копию рядом: "name (1).ext"; возвращает путь копии
+#[tauri::command]
+pub async fn duplicate_file(
+    app: AppHandle,
+    state: State<'_, TransferState>,
+    path: String,
+    workspace_root: Option<String>,
+) -> Result<String, String> {
+    let source = PathBuf::from(&path);
+    let parent = source.parent().ok_or_else(|| format!("Некорректный путь: {}", path))?;
+    let options = TransferOptions {
+        policy: Some(ConflictPolicy::KeepBoth),
+        workspace_root,
+        ..TransferOptions::default()
+    };
+    let request = TransferRequest::new(vec![source.clone()], parent.to_path_buf(), false, options);
+    let result = start_transfer(app, state, None, request).await?;
+    match (result.transferred.into_iter().next(), result.errors.into_iter().next()) {
+        (Some(item), _) => Ok(item.destination),
+        (None, Some(error)) => Err(error),
+        (None, None) => Err(format!("Не удалось создать копию {}", path)),
+    }
+}
+
+#[tauri::command]
+pub fn cancel_file_transfer(state: State<'_, TransferState>, transfer_id: String) -> Result<bool, String> {
+    let flags = state.cancel_flags.lock().map_err(|e| e.to_string())?;
+    Ok(match flags.get(&transfer_id) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    })
+}
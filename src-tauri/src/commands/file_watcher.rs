@@ -0,0 +1,611 @@
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::directory_listing::DirectoryState;
+use crate::commands::ignore_rules::{self, IgnoreRules, IgnoreStatus};
+
+// Пауза без событий, после которой накопленные изменения отправляются
+const DEBOUNCE: Duration = Duration::from_millis(150);
+// Непрерывный поток событий (сборка, npm install) все равно отправляется не реже этого
+const MAX_DELAY: Duration = Duration::from_secs(1);
+
+/// Изменение в файловой системе рабочей области
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum FsChange {
+    Created { path: String, is_directory: bool },
+    Modified { path: String },
+    Deleted { path: String },
+    Renamed { from: String, to: String, is_directory: bool },
+}
+
+/// Пачка изменений, событие "fs-changes"
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FsChangeBatch {
+    pub root: String,
+    pub changes: Vec<FsChange>,
+    // Часть событий потеряна (переполнение очереди ОС) - дерево нужно перечитать целиком
+    pub rescan: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Change {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// Накопленные за время debounce изменения в порядке появления
+#[derive(Default)]
+struct PendingChanges {
+    entries: Vec<Option<PendingEntry>>,
+    by_path: HashMap<PathBuf, usize>,
+    // Начало переименования (inotify и Windows присылают половинки отдельно) и cookie inotify
+    rename_from: Option<(PathBuf, Option<usize>)>,
+    // cookie переименования, собранного из половинок: inotify следом присылает его же целиком
+    paired: Option<usize>,
+    rescan: bool,
+}
+
+enum PendingEntry {
+    Change(PathBuf, Change),
+    Renamed(PathBuf, PathBuf),
+}
+
+impl PendingChanges {
+    /// Объединяет изменение с уже накопленным для того же пути
+    fn record(&mut self, path: PathBuf, change: Change) {
+        let Some(&index) = self.by_path.get(&path) else {
+            self.by_path.insert(path.clone(), self.entries.len());
+            self.entries.push(Some(PendingEntry::Change(path, change)));
+            return;
+        };
+        let Some(PendingEntry::Change(_, previous)) = &self.entries[index] else {
+            return;
+        };
+        let merged = match (*previous, change) {
+            (Change::Created, Change::Modified) => Some(Change::Created),
+            // Временный файл, который создали и сразу удалили, дереву неинтересен
+            (Change::Created, Change::Deleted) => None,
+            // Сохранение через удаление и создание заново
+            (Change::Deleted, Change::Created) => Some(Change::Modified),
+            (Change::Modified, Change::Created) => Some(Change::Modified),
+            (_, change) => Some(change),
+        };
+        match merged {
+            Some(merged) => self.entries[index] = Some(PendingEntry::Change(path, merged)),
+            None => {
+                self.entries[index] = None;
+                self.by_path.remove(&path);
+            }
+        }
+    }
+
+    fn rename(&mut self, from: PathBuf, to: PathBuf) {
+        // Файл, созданный в этой же пачке, для дерева просто создан под новым именем
+        if let Some(&index) = self.by_path.get(&from) {
+            if matches!(self.entries[index], Some(PendingEntry::Change(_, Change::Created))) {
+                self.entries[index] = None;
+                self.by_path.remove(&from);
+                self.record(to, Change::Created);
+                return;
+            }
+        }
+        self.entries.push(Some(PendingEntry::Renamed(from, to)));
+    }
+
+    /// "from" без пары: элемент переместили за пределы рабочей области
+    fn moved_out(&mut self, path: PathBuf) {
+        // inotify присылает для самой перемещенной папки еще и MOVE_SELF - тоже как "from"
+        let renamed = self
+            .entries
+            .iter()
+            .any(|entry| matches!(entry, Some(PendingEntry::Renamed(from, _)) if *from == path));
+        if !renamed {
+            self.record(path, Change::Deleted);
+        }
+    }
+
+    fn add(&mut self, event: Event) {
+        if event.need_rescan() {
+            self.rescan = true;
+        }
+        let tracker = event.tracker();
+        let mut paths = event.paths.into_iter();
+        match event.kind {
+            EventKind::Create(_) => paths.for_each(|path| self.record(path, Change::Created)),
+            EventKind::Remove(_) => paths.for_each(|path| self.record(path, Change::Deleted)),
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                // Уже учтено по половинкам
+                if tracker.is_some() && tracker == self.paired.take() {
+                    return;
+                }
+                if let (Some(from), Some(to)) = (paths.next(), paths.next()) {
+                    self.rename(from, to);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                let Some(path) = paths.next() else {
+                    return;
+                };
+                match self.rename_from.take() {
+                    // MOVE_SELF той же папки, пока ждем вторую половину
+                    Some(pending) if pending.0 == path => self.rename_from = Some(pending),
+                    Some((from, _)) => {
+                        self.moved_out(from);
+                        self.rename_from = Some((path, tracker));
+                    }
+                    None => self.rename_from = Some((path, tracker)),
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                for to in paths {
+                    match self.rename_from.take() {
+                        // У Windows cookie нет, половинки просто идут подряд
+                        Some((from, from_tracker)) if from_tracker == tracker => {
+                            self.rename(from, to);
+                            self.paired = tracker;
+                        }
+                        // Одно переместили наружу, другое - внутрь рабочей области
+                        Some((from, _)) => {
+                            self.moved_out(from);
+                            self.record(to, Change::Created);
+                        }
+                        None => self.record(to, Change::Created),
+                    }
+                }
+            }
+            // FSEvents не сообщает, какая половина переименования пришла - смотрим на диск
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                for path in paths {
+                    let change = if path.exists() { Change::Created } else { Change::Deleted };
+                    self.record(path, change);
+                }
+            }
+            EventKind::Modify(ModifyKind::Metadata(_)) | EventKind::Access(_) => {}
+            EventKind::Modify(_) | EventKind::Any | EventKind::Other => {
+                paths.for_each(|path| self.record(path, Change::Modified))
+            }
+        }
+    }
+
+    fn take(&mut self) -> (Vec<FsChange>, bool) {
+        if let Some((from, _)) = self.rename_from.take() {
+            self.moved_out(from);
+        }
+        self.paired = None;
+        let text = |path: &Path| path.to_string_lossy().to_string();
+        let changes = self
+            .entries
+            .drain(..)
+            .flatten()
+            .map(|entry| match entry {
+                PendingEntry::Change(path, Change::Created) => FsChange::Created {
+                    is_directory: path.is_dir(),
+                    path: text(&path),
+                },
+                PendingEntry::Change(path, Change::Modified) => FsChange::Modified { path: text(&path) },
+                PendingEntry::Change(path, Change::Deleted) => FsChange::Deleted { path: text(&path) },
+                PendingEntry::Renamed(from, to) => FsChange::Renamed {
+                    from: text(&from),
+                    is_directory: to.is_dir(),
+                    to: text(&to),
+                },
+            })
+            .collect();
+        self.by_path.clear();
+        (changes, std::mem::take(&mut self.rescan))
+    }
+}
+
+/// Путь исключен настройками (files.exclude) или лежит во всегда пропускаемой папке (.git, node_modules, ...).
+/// Игнорируемые по .gitignore файлы дерево показывает приглушенными, их изменения отправляются;
+/// в игнорируемые папки наблюдение на Linux не заходит (см. watch_tree)
+fn is_ignored(root: &Path, rules: &IgnoreRules, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return false;
+    };
+    let is_dir = path.is_dir();
+    let mut dirs = relative.components().map(|component| component.as_os_str()).collect::<Vec<_>>();
+    // Последний компонент - сам элемент: файл с именем "build" не пропускается
+    if !is_dir {
+        dirs.pop();
+    }
+    dirs.into_iter().any(ignore_rules::is_always_skipped)
+        || rules.status_with_parents(path, is_dir) == IgnoreStatus::Excluded
+}
+
+/// Отбрасывает пути в игнорируемых каталогах; переименование из игнорируемого
+/// каталога для дерева - создание, в игнорируемый - удаление
+fn filter_event(root: &Path, rules: &IgnoreRules, mut event: Event) -> Option<Event> {
+    if let EventKind::Modify(ModifyKind::Name(RenameMode::Both)) = event.kind {
+        if let [from, to] = event.paths.as_slice() {
+            match (is_ignored(root, rules, from), is_ignored(root, rules, to)) {
+                (true, true) => return None,
+                (true, false) => event.kind = EventKind::Create(notify::event::CreateKind::Any),
+                (false, true) => event.kind = EventKind::Remove(notify::event::RemoveKind::Any),
+                (false, false) => return Some(event),
+            }
+        }
+    }
+    event.paths.retain(|path| !is_ignored(root, rules, path));
+    (!event.paths.is_empty() || event.need_rescan()).then_some(event)
+}
+
+/// Наблюдение за одной рабочей областью
+struct WorkspaceWatcher {
+    // Единственная сильная ссылка: вместе с наблюдателем закрывается канал событий,
+    // и поток debounce завершается
+    _watcher: Arc<Mutex<RecommendedWatcher>>,
+}
+
+/// Наблюдатели открытых рабочих областей, ключ - корень
+pub struct WatcherState {
+    watchers: Mutex<HashMap<String, WorkspaceWatcher>>,
+}
+
+impl Default for WatcherState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WatcherState {
+    pub fn new() -> Self {
+        WatcherState {
+            watchers: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+// inotify следит за каждым каталогом отдельно, и число наблюдений ограничено
+// (fs.inotify.max_user_watches), поэтому игнорируемые каталоги не добавляются вовсе.
+// FSEvents и ReadDirectoryChangesW рекурсивны сами по себе.
+#[cfg(target_os = "linux")]
+fn watch_tree(watcher: &Mutex<RecommendedWatcher>, rules: &IgnoreRules, dir: &Path) {
+    let Ok(mut watcher) = watcher.lock() else {
+        return;
+    };
+    let dirs = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|entry| entry.file_type().is_dir() && rules.allows(entry))
+        .filter_map(|entry| entry.ok());
+    for entry in dirs {
+        if let Err(e) = watcher.watch(entry.path(), RecursiveMode::NonRecursive) {
+            eprintln!("[watcher] {}: {}", entry.path().display(), e);
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn watch_tree(watcher: &Mutex<RecommendedWatcher>, _rules: &IgnoreRules, dir: &Path) {
+    if let Ok(mut watcher) = watcher.lock() {
+        if let Err(e) = watcher.watch(dir, RecursiveMode::Recursive) {
+            eprintln!("[watcher] {}: {}", dir.display(), e);
+        }
+    }
+}
+
+/// Изменился .gitignore, .ignore или настройки рабочей области
+fn rules_changed(changes: &[FsChange]) -> bool {
+    changes.iter().any(|change| match change {
+        FsChange::Created { path, .. } | FsChange::Modified { path } | FsChange::Deleted { path } => {
+            ignore_rules::is_rules_file(Path::new(path))
+        }
+        FsChange::Renamed { from, to, .. } => {
+            ignore_rules::is_rules_file(Path::new(from)) || ignore_rules::is_rules_file(Path::new(to))
+        }
+    })
+}
+
+/// Сбрасывает кэш списков папок, которых коснулись изменения
+fn invalidate_listings(app: &AppHandle, changes: &[FsChange], rules_changed: bool) {
+    let Some(directories) = app.try_state::<DirectoryState>() else {
+        return;
+    };
+    let mut dirs = Vec::new();
+    for change in changes {
+        let paths = match change {
+            FsChange::Created { path, .. } | FsChange::Deleted { path } => vec![path],
+            FsChange::Renamed { from, to, .. } => vec![from, to],
+            // Изменение содержимого файла не меняет список папки
+            FsChange::Modified { .. } => continue,
+        };
+        for path in paths.into_iter().map(Path::new) {
+            // Удаленная или переименованная папка сама тоже устарела
+            dirs.push(path.to_path_buf());
+            dirs.extend(path.parent().map(Path::to_path_buf));
+        }
+    }
+    if rules_changed {
+        directories.invalidate(None);
+    } else {
+        directories.invalidate(Some(&dirs));
+    }
+}
+
+/// Собирает события в пачки и отправляет их во фронтенд
+fn debounce_loop(
+    app: AppHandle,
+    root: PathBuf,
+    mut rules: IgnoreRules,
+    watcher: Weak<Mutex<RecommendedWatcher>>,
+    events: Receiver<notify::Result<Event>>,
+) {
+    let root_text = root.to_string_lossy().to_string();
+    let mut pending = PendingChanges::default();
+    let mut first_pending: Option<Instant> = None;
+
+    loop {
+        let timeout = match first_pending {
+            Some(first) => DEBOUNCE.min(MAX_DELAY.saturating_sub(first.elapsed())),
+            None => Duration::from_secs(3600),
+        };
+        match events.recv_timeout(timeout) {
+            Ok(Ok(event)) => {
+                let Some(event) = filter_event(&root, &rules, event) else {
+                    continue;
+                };
+                // Новые каталоги нужно добавить в наблюдение (на Linux - вручную)
+                if cfg!(target_os = "linux") && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))) {
+                    if let Some(watcher) = watcher.upgrade() {
+                        // Игнорируемые папки в дереве видны, но внутрь наблюдение не заходит
+                        let visible = |dir: &&PathBuf| rules.status_with_parents(dir, true) == IgnoreStatus::Visible;
+                        for dir in event.paths.iter().filter(|path| path.is_dir()).filter(visible) {
+                            watch_tree(&watcher, &rules, dir);
+                        }
+                    }
+                }
+                pending.add(event);
+                first_pending.get_or_insert_with(Instant::now);
+                if first_pending.is_some_and(|first| first.elapsed() < MAX_DELAY) {
+                    continue;
+                }
+            }
+            Ok(Err(e)) => {
+                eprintln!("[watcher] {}", e);
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        first_pending = None;
+        let (changes, rescan) = pending.take();
+        if changes.is_empty() && !rescan {
+            continue;
+        }
+        let rules_changed = rescan || rules_changed(&changes);
+        if rules_changed {
+            rules = IgnoreRules::for_path(&root, Some(&root_text));
+            // Папки, которые перестали игнорироваться, тоже нужно наблюдать
+            if let Some(watcher) = watcher.upgrade().filter(|_| cfg!(target_os = "linux")) {
+                watch_tree(&watcher, &rules, &root);
+            }
+        }
+        invalidate_listings(&app, &changes, rules_changed);
+        let batch = FsChangeBatch {
+            root: root_text.clone(),
+            changes,
+            rescan,
+        };
+        if let Err(e) = app.emit("fs-changes", batch) {
+            eprintln!("[watcher] Не удалось отправить изменения: {}", e);
+        }
+    }
+}
+
+/// Начинает следить за рабочей областью; изменения приходят событием "fs-changes"
+#[tauri::command]
+pub fn watch_workspace(app: AppHandle, state: State<'_, WatcherState>, root: String) -> Result<(), String> {
+    let root_path = PathBuf::from(&root);
+    if !root_path.is_dir() {
+        return Err(format!("Папка '{}' не существует", root));
+    }
+    let mut watchers = state.watchers.lock().map_err(|e| e.to_string())?;
+    if watchers.contains_key(&root) {
+        return Ok(());
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let watcher = notify::recommended_watcher(move |event| {
+        let _ = sender.send(event);
+    })
+    .map_err(|e| format!("Не удалось запустить наблюдение за файлами: {}", e))?;
+    let watcher = Arc::new(Mutex::new(watcher));
+    let rules = IgnoreRules::for_path(&root_path, Some(&root));
+    watch_tree(&watcher, &rules, &root_path);
+
+    let thread_watcher = Arc::downgrade(&watcher);
+    std::thread::spawn(move || debounce_loop(app, root_path, rules, thread_watcher, receiver));
+    watchers.insert(root, WorkspaceWatcher { _watcher: watcher });
+    Ok(())
+}
+
+/// Прекращает наблюдение за рабочей областью (при закрытии проекта)
+#[tauri::command]
+pub fn unwatch_workspace(state: State<'_, WatcherState>, root: String) -> Result<(), String> {
+    state.watchers.lock().map_err(|e| e.to_string())?.remove(&root);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, RemoveKind};
+
+    fn event(kind: EventKind, paths: &[&str], tracker: Option<usize>) -> Event {
+        let mut event = Event::new(kind);
+        for path in paths {
+            event = event.add_path(PathBuf::from(path));
+        }
+        match tracker {
+            Some(tracker) => event.set_tracker(tracker),
+            None => event,
+        }
+    }
+
+    fn created(path: &str) -> Event {
+        event(EventKind::Create(CreateKind::File), &[path], None)
+    }
+
+    fn modified(path: &str) -> Event {
+        event(EventKind::Modify(ModifyKind::Data(DataChange::Any)), &[path], None)
+    }
+
+    fn removed(path: &str) -> Event {
+        event(EventKind::Remove(RemoveKind::File), &[path], None)
+    }
+
+    fn renamed(mode: RenameMode, paths: &[&str], tracker: Option<usize>) -> Event {
+        event(EventKind::Modify(ModifyKind::Name(mode)), paths, tracker)
+    }
+
+    fn collect(events: Vec<Event>) -> Vec<FsChange> {
+        let mut pending = PendingChanges::default();
+        events.into_iter().for_each(|event| pending.add(event));
+        pending.take().0
+    }
+
+    fn rename_change(from: &str, to: &str) -> FsChange {
+        FsChange::Renamed {
+            from: from.to_string(),
+            to: to.to_string(),
+            is_directory: false,
+        }
+    }
+
+    #[test]
+    fn created_then_modified_is_created() {
+        assert_eq!(
+            collect(vec![created("/w/a"), modified("/w/a")]),
+            vec![FsChange::Created {
+                path: "/w/a".to_string(),
+                is_directory: false
+            }]
+        );
+    }
+
+    #[test]
+    fn created_then_deleted_is_dropped() {
+        assert_eq!(collect(vec![created("/w/a"), modified("/w/a"), removed("/w/a")]), vec![]);
+    }
+
+    #[test]
+    fn deleted_then_created_is_modified() {
+        assert_eq!(
+            collect(vec![removed("/w/a"), created("/w/a")]),
+            vec![FsChange::Modified { path: "/w/a".to_string() }]
+        );
+    }
+
+    #[test]
+    fn inotify_rename_is_reported_once() {
+        let events = vec![
+            renamed(RenameMode::From, &["/w/a"], Some(7)),
+            renamed(RenameMode::To, &["/w/b"], Some(7)),
+            renamed(RenameMode::Both, &["/w/a", "/w/b"], Some(7)),
+        ];
+        assert_eq!(collect(events), vec![rename_change("/w/a", "/w/b")]);
+    }
+
+    #[test]
+    fn moved_directory_self_event_is_ignored() {
+        let events = vec![
+            renamed(RenameMode::From, &["/w/dir"], Some(3)),
+            renamed(RenameMode::To, &["/w/moved"], Some(3)),
+            renamed(RenameMode::Both, &["/w/dir", "/w/moved"], Some(3)),
+            renamed(RenameMode::From, &["/w/dir"], None),
+        ];
+        assert_eq!(collect(events), vec![rename_change("/w/dir", "/w/moved")]);
+    }
+
+    #[test]
+    fn windows_rename_halves_are_paired() {
+        let events = vec![
+            renamed(RenameMode::From, &["/w/a"], None),
+            renamed(RenameMode::To, &["/w/b"], None),
+        ];
+        assert_eq!(collect(events), vec![rename_change("/w/a", "/w/b")]);
+    }
+
+    #[test]
+    fn single_rename_event_is_reported() {
+        let events = vec![renamed(RenameMode::Both, &["/w/a", "/w/b"], None)];
+        assert_eq!(collect(events), vec![rename_change("/w/a", "/w/b")]);
+    }
+
+    #[test]
+    fn unrelated_halves_are_move_out_and_move_in() {
+        let events = vec![
+            renamed(RenameMode::From, &["/w/a"], Some(1)),
+            renamed(RenameMode::To, &["/w/b"], Some(2)),
+        ];
+        assert_eq!(
+            collect(events),
+            vec![
+                FsChange::Deleted { path: "/w/a".to_string() },
+                FsChange::Created {
+                    path: "/w/b".to_string(),
+                    is_directory: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unpaired_halves() {
+        assert_eq!(
+            collect(vec![renamed(RenameMode::From, &["/w/a"], Some(1))]),
+            vec![FsChange::Deleted { path: "/w/a".to_string() }]
+        );
+        assert_eq!(
+            collect(vec![renamed(RenameMode::To, &["/w/b"], Some(1))]),
+            vec![FsChange::Created {
+                path: "/w/b".to_string(),
+                is_directory: false
+            }]
+        );
+    }
+
+    #[test]
+    fn created_then_renamed_is_created_under_new_name() {
+        let events = vec![
+            created("/w/tmp"),
+            renamed(RenameMode::From, &["/w/tmp"], Some(5)),
+            renamed(RenameMode::To, &["/w/file"], Some(5)),
+            renamed(RenameMode::Both, &["/w/tmp", "/w/file"], Some(5)),
+        ];
+        assert_eq!(
+            collect(events),
+            vec![FsChange::Created {
+                path: "/w/file".to_string(),
+                is_directory: false
+            }]
+        );
+    }
+
+    #[test]
+    fn rename_chain_keeps_order() {
+        let events = vec![
+            renamed(RenameMode::From, &["/w/a"], Some(1)),
+            renamed(RenameMode::To, &["/w/b"], Some(1)),
+            renamed(RenameMode::Both, &["/w/a", "/w/b"], Some(1)),
+            renamed(RenameMode::From, &["/w/b"], Some(2)),
+            renamed(RenameMode::To, &["/w/c"], Some(2)),
+            renamed(RenameMode::Both, &["/w/b", "/w/c"], Some(2)),
+        ];
+        assert_eq!(
+            collect(events),
+            vec![rename_change("/w/a", "/w/b"), rename_change("/w/b", "/w/c")]
+        );
+    }
+}
@@ -5,6 +5,8 @@ use walkdir::WalkDir;
 use std::collections::HashSet;
 use std::process::Command;
 
+use crate::commands::ignore_rules::{IgnoreRules, IgnoreStatus};
+
 /// Структура для результата списка файлов
 #[derive(serde::Serialize)]
 pub struct DirEntry {
@@ -12,6 +14,9 @@ pub struct DirEntry {
     path: String,
     #[serde(rename = "isDir")]
     is_dir: bool,
+    // Игнорируется по .gitignore; такие элементы дерево показывает приглушенными
+    #[serde(rename = "ignored", skip_serializing_if = "std::ops::Not::not")]
+    ignored: bool,
 }
 
 /// Получает корень проекта на основе текущего пути
@@ -289,6 +294,7 @@ pub fn list_dir(path: &str) -> Vec<DirEntry> {
             entries.push(DirEntry {
                 path: path_str,
                 is_dir,
+                ignored: false,
             });
         }
     }
@@ -296,34 +302,79 @@ pub fn list_dir(path: &str) -> Vec<DirEntry> {
     entries
 }
 
-/// Рекурсивно сканирует директорию и возвращает все файлы
+/// Рекурсивно сканирует директорию и возвращает все файлы.
+/// Исключенное настройками пропускается; игнорируемое по .gitignore - тоже,
+/// если не передан `show_ignored`: тогда оно возвращается с пометкой `ignored`
 #[command]
-pub fn scan_directory(path: &str, recursive: bool) -> Vec<DirEntry> {
+pub fn scan_directory(path: &str, recursive: bool, workspace_root: Option<String>, show_ignored: Option<bool>) -> Vec<DirEntry> {
     let mut entries = Vec::new();
     let path_obj = Path::new(path);
     
     if !path_obj.exists() || !path_obj.is_dir() {
         return entries;
     }
+
+    let ignore_rules = IgnoreRules::for_path(path_obj, workspace_root.as_deref());
+    let show_ignored = show_ignored.unwrap_or(false);
+    // Внутри игнорируемой папки игнорируется все
+    let parent_status = ignore_rules.status_with_parents(path_obj, true);
+    if parent_status == IgnoreStatus::Excluded {
+        return entries;
+    }
     
     // Если нужно рекурсивное сканирование, используем WalkDir
     if recursive {
-        for entry in WalkDir::new(path)
+        let mut walker = WalkDir::new(path)
             .follow_links(true)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path_str = entry.path().to_string_lossy().to_string();
-            let is_dir = entry.path().is_dir();
+            .into_iter();
+        // Пометки папок на пути от корня обхода: содержимое игнорируемой папки тоже игнорируется
+        let mut ignored_parents: Vec<bool> = Vec::new();
+        while let Some(entry) = walker.next() {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            let is_dir = entry.file_type().is_dir();
+            ignored_parents.truncate(entry.depth());
+            let inherited = ignored_parents
+                .last()
+                .copied()
+                .unwrap_or(parent_status == IgnoreStatus::Ignored);
+            let status = if entry.depth() == 0 {
+                IgnoreStatus::Visible
+            } else {
+                ignore_rules.status(entry.path(), is_dir)
+            };
+            let ignored = match status {
+                IgnoreStatus::Ignored if show_ignored => true,
+                IgnoreStatus::Visible => inherited,
+                _ => {
+                    if is_dir {
+                        walker.skip_current_dir();
+                    }
+                    continue;
+                }
+            };
+            if is_dir {
+                ignored_parents.push(ignored);
+            }
             
             entries.push(DirEntry {
-                path: path_str,
+                path: entry.path().to_string_lossy().to_string(),
                 is_dir,
+                ignored,
             });
         }
     } else {
         // Иначе просто читаем директорию
-        entries = list_dir(path);
+        for mut entry in list_dir(path) {
+            match ignore_rules.status(Path::new(&entry.path), entry.is_dir) {
+                IgnoreStatus::Excluded => continue,
+                IgnoreStatus::Ignored if !show_ignored => continue,
+                IgnoreStatus::Ignored => entry.ignored = true,
+                IgnoreStatus::Visible => entry.ignored = parent_status == IgnoreStatus::Ignored,
+            }
+            entries.push(entry);
+        }
     }
     
     entries
@@ -373,10 +424,6 @@ pub fn get_importable_files(root_path: &str) -> Vec<DirEntry> {
 
     println!("Сканирование директории для импортов: {}", root_path);
     
-    // Игнорируем директории, которые обычно содержат много файлов, но не нужны для импортов
-    let ignore_dirs = ["node_modules", ".git", "dist", "build", ".next", "out", 
-                      ".cache", ".idea", ".vscode", ".github", "coverage", ".DS_Store"];
-    
     // Расширения файлов, которые мы хотим включить
     let valid_extensions = ["js", "jsx", "ts", "tsx", "vue", "svelte", "json", "css", "scss", 
                            "sass", "less", "md", "mdx", "svg", "png", "jpg", "jpeg", "gif", 
@@ -396,25 +443,14 @@ pub fn get_importable_files(root_path: &str) -> Vec<DirEntry> {
     
     let root_path_str = absolute_root_path.to_string_lossy().to_string();
     println!("Абсолютный путь корня проекта: {}", root_path_str);
+
+    // Пропускаем файлы из .gitignore и исключения из настроек рабочей области
+    let ignore_rules = IgnoreRules::for_path(&absolute_root_path, None);
     
     for entry in WalkDir::new(&absolute_root_path)
         .follow_links(true)
         .into_iter()
-        .filter_entry(|e| {
-            let path = e.path();
-            
-            // Проверяем, является ли путь директорией
-            if path.is_dir() {
-                // Игнорируем определенные директории
-                let dir_name = path.file_name()
-                    .and_then(|name| name.to_str())
-                    .unwrap_or("");
-                
-                !ignore_dirs.iter().any(|&ignore| dir_name == ignore)
-            } else {
-                true
-            }
-        })
+        .filter_entry(|e| ignore_rules.allows(e))
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
@@ -467,6 +503,7 @@ pub fn get_importable_files(root_path: &str) -> Vec<DirEntry> {
         entries.push(DirEntry {
             path: normalized_path,
             is_dir,
+            ignored: false,
         });
     }
     
@@ -555,28 +592,13 @@ pub fn get_all_files_in_directory(directory: &str) -> Vec<String> {
         return files;
     }
     
-    // Игнорируем определенные директории
-    let ignore_dirs = ["node_modules", ".git", "dist", "build", ".next", "out", 
-                      ".cache", ".idea", ".vscode", ".github", "coverage", ".DS_Store"];
+    // Игнорируем файлы из .gitignore и исключения из настроек рабочей области
+    let ignore_rules = IgnoreRules::for_path(path_obj, None);
     
     for entry in WalkDir::new(directory)
         .follow_links(true)
         .into_iter()
-        .filter_entry(|e| {
-            let path = e.path();
-            
-            // Проверяем, является ли путь директорией
-            if path.is_dir() {
-                // Игнорируем определенные директории
-                let dir_name = path.file_name()
-                    .and_then(|name| name.to_str())
-                    .unwrap_or("");
-                
-                !ignore_dirs.iter().any(|&ignore| dir_name == ignore)
-            } else {
-                true
-            }
-        })
+        .filter_entry(|e| ignore_rules.allows(e))
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
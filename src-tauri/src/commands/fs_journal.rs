@@ -0,0 +1,295 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use tauri::State;
+
+use crate::commands::file_transfer;
+use crate::commands::trash::{self, TrashedItem};
+
+// Сколько операций помнить в каждой рабочей области
+const JOURNAL_LIMIT: usize = 100;
+
+/// Что было на диске сразу после операции: отмена проверяет, что с тех пор ничего не изменилось
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Fingerprint {
+    pub is_directory: bool,
+    pub size: u64,
+    // мс с начала эпохи Unix; у папки меняется при добавлении и удалении элементов
+    pub modified: u64,
+}
+
+impl Fingerprint {
+    pub fn of(path: &Path) -> Option<Fingerprint> {
+        let metadata = path.symlink_metadata().ok()?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_millis() as u64);
+        Some(Fingerprint {
+            is_directory: metadata.is_dir(),
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+            modified,
+        })
+    }
+}
+
+/// Операция с файлами, которую можно отменить
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum FsOperation {
+    // Создание файла, папки или копии. После отмены созданное лежит в корзине (trashed)
+    Created {
+        path: String,
+        fingerprint: Option<Fingerprint>,
+        trashed: Option<TrashedItem>,
+    },
+    // Переименование или перемещение
+    Renamed {
+        from: String,
+        to: String,
+        fingerprint: Option<Fingerprint>,
+    },
+    // Удаление в корзину; fingerprint - восстановленного элемента (после отмены)
+    Trashed {
+        item: TrashedItem,
+        fingerprint: Option<Fingerprint>,
+    },
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map_or_else(|| path.to_string(), |name| name.to_string_lossy().to_string())
+}
+
+impl FsOperation {
+    pub fn created(path: &Path) -> FsOperation {
+        FsOperation::Created {
+            path: path.to_string_lossy().to_string(),
+            fingerprint: Fingerprint::of(path),
+            trashed: None,
+        }
+    }
+
+    pub fn renamed(from: &Path, to: &Path) -> FsOperation {
+        FsOperation::Renamed {
+            from: from.to_string_lossy().to_string(),
+            to: to.to_string_lossy().to_string(),
+            fingerprint: Fingerprint::of(to),
+        }
+    }
+
+    pub fn trashed(item: TrashedItem) -> FsOperation {
+        FsOperation::Trashed { item, fingerprint: None }
+    }
+
+    /// Описание для пунктов "Отменить ..." / "Повторить ..."
+    pub fn label(&self) -> String {
+        match self {
+            FsOperation::Created { path, .. } => format!("Создание {}", file_name(path)),
+            FsOperation::Renamed { from, to, .. } if Path::new(from).parent() == Path::new(to).parent() => {
+                format!("Переименование {} → {}", file_name(from), file_name(to))
+            }
+            FsOperation::Renamed { from, .. } => format!("Перемещение {}", file_name(from)),
+            FsOperation::Trashed { item, .. } => format!("Удаление {}", file_name(&item.original_path)),
+        }
+    }
+
+    fn path(&self) -> &str {
+        match self {
+            FsOperation::Created { path, .. } => path,
+            FsOperation::Renamed { to, .. } => to,
+            FsOperation::Trashed { item, .. } => &item.original_path,
+        }
+    }
+
+    /// Отменяет операцию; возвращает операцию для стека повтора
+    fn revert(self) -> Result<FsOperation, String> {
+        match self {
+            FsOperation::Created { path, fingerprint, .. } => {
+                ensure_unchanged(&path, &fingerprint)?;
+                let item = trash::move_to_trash(Path::new(&path))?;
+                Ok(FsOperation::Created {
+                    path,
+                    fingerprint,
+                    trashed: Some(item),
+                })
+            }
+            FsOperation::Renamed { from, to, fingerprint } => {
+                ensure_unchanged(&to, &fingerprint)?;
+                ensure_free(&from)?;
+                file_transfer::move_path(Path::new(&to), Path::new(&from))?;
+                Ok(FsOperation::Renamed {
+                    fingerprint: Fingerprint::of(Path::new(&from)),
+                    from,
+                    to,
+                })
+            }
+            FsOperation::Trashed { item, .. } => {
+                let restored = trash::restore(&item.id)?;
+                Ok(FsOperation::Trashed {
+                    fingerprint: Fingerprint::of(Path::new(&restored.original_path)),
+                    item: restored,
+                })
+            }
+        }
+    }
+
+    /// Повторяет отмененную операцию; возвращает операцию для стека отмены
+    fn reapply(self) -> Result<FsOperation, String> {
+        match self {
+            FsOperation::Created { path, trashed, .. } => {
+                let item = trashed.ok_or("Нет сведений о созданном элементе")?;
+                trash::restore(&item.id)?;
+                Ok(FsOperation::created(Path::new(&path)))
+            }
+            FsOperation::Renamed { from, to, fingerprint } => {
+                ensure_unchanged(&from, &fingerprint)?;
+                ensure_free(&to)?;
+                file_transfer::move_path(Path::new(&from), Path::new(&to))?;
+                Ok(FsOperation::renamed(Path::new(&from), Path::new(&to)))
+            }
+            FsOperation::Trashed { item, fingerprint } => {
+                ensure_unchanged(&item.original_path, &fingerprint)?;
+                let item = trash::move_to_trash(Path::new(&item.original_path))?;
+                Ok(FsOperation::trashed(item))
+            }
+        }
+    }
+}
+
+fn ensure_unchanged(path: &str, expected: &Option<Fingerprint>) -> Result<(), String> {
+    match Fingerprint::of(Path::new(path)) {
+        None => Err(format!("'{}' больше не существует", path)),
+        Some(current) if expected.as_ref().is_some_and(|expected| *expected != current) => {
+            Err(format!("'{}' изменен после операции", path))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+fn ensure_free(path: &str) -> Result<(), String> {
+    if Path::new(path).symlink_metadata().is_ok() {
+        return Err(format!("На месте '{}' уже есть другой файл", path));
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct Journal {
+    undo: Vec<FsOperation>,
+    redo: Vec<FsOperation>,
+}
+
+/// Журналы операций с файлами, ключ - корень рабочей области
+pub struct FsJournalState {
+    journals: Mutex<HashMap<String, Journal>>,
+}
+
+impl Default for FsJournalState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FsJournalState {
+    pub fn new() -> Self {
+        FsJournalState {
+            journals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Записывает выполненную операцию. Рабочая область - `workspace_root` или открытая
+    /// ранее область, в которой лежит файл; вне рабочих областей операции не записываются
+    pub fn record(&self, workspace_root: Option<&str>, operation: FsOperation) {
+        let Ok(mut journals) = self.journals.lock() else {
+            return;
+        };
+        let root = match workspace_root {
+            Some(root) => root.to_string(),
+            None => {
+                let path = Path::new(operation.path());
+                let Some(root) = journals
+                    .keys()
+                    .filter(|root| path.starts_with(root))
+                    .max_by_key(|root| root.len())
+                else {
+                    return;
+                };
+                root.clone()
+            }
+        };
+        let journal = journals.entry(root).or_default();
+        journal.undo.push(operation);
+        if journal.undo.len() > JOURNAL_LIMIT {
+            journal.undo.remove(0);
+        }
+        journal.redo.clear();
+    }
+}
+
+/// Состояние журнала для пунктов меню
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FsHistory {
+    // Последняя операция - первой
+    pub undo: Vec<String>,
+    pub redo: Vec<String>,
+}
+
+/// Результат отмены или повтора: что изменилось, чтобы обновить дерево и вкладки
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FsUndoResult {
+    pub label: String,
+    pub operation: FsOperation,
+}
+
+fn history_of(journal: &Journal) -> FsHistory {
+    FsHistory {
+        undo: journal.undo.iter().rev().map(FsOperation::label).collect(),
+        redo: journal.redo.iter().rev().map(FsOperation::label).collect(),
+    }
+}
+
+#[tauri::command]
+pub fn get_file_operation_history(state: State<'_, FsJournalState>, workspace_root: String) -> Result<FsHistory, String> {
+    let mut journals = state.journals.lock().map_err(|e| e.to_string())?;
+    Ok(history_of(journals.entry(workspace_root).or_default()))
+}
+
+/// Отменяет последнюю операцию. Если файлы с тех пор изменились, операция
+/// не отменяется и убирается из журнала - вернуть прежнее состояние уже нельзя
+#[tauri::command]
+pub fn undo_file_operation(state: State<'_, FsJournalState>, workspace_root: String) -> Result<Option<FsUndoResult>, String> {
+    let mut journals = state.journals.lock().map_err(|e| e.to_string())?;
+    let journal = journals.entry(workspace_root).or_default();
+    let Some(operation) = journal.undo.pop() else {
+        return Ok(None);
+    };
+    let label = operation.label();
+    let reverted = operation
+        .revert()
+        .map_err(|e| format!("Не удалось отменить \"{}\": {}", label, e))?;
+    journal.redo.push(reverted.clone());
+    Ok(Some(FsUndoResult { label, operation: reverted }))
+}
+
+#[tauri::command]
+pub fn redo_file_operation(state: State<'_, FsJournalState>, workspace_root: String) -> Result<Option<FsUndoResult>, String> {
+    let mut journals = state.journals.lock().map_err(|e| e.to_string())?;
+    let journal = journals.entry(workspace_root).or_default();
+    let Some(operation) = journal.redo.pop() else {
+        return Ok(None);
+    };
+    let label = operation.label();
+    let reapplied = operation
+        .reapply()
+        .map_err(|e| format!("Не удалось повторить \"{}\": {}", label, e))?;
+    journal.undo.push(reapplied.clone());
+    Ok(Some(FsUndoResult { label, operation: reapplied }))
+}
@@ -0,0 +1,428 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::commands::workspace_settings;
+
+const SETTINGS_SECTION: &str = "files";
+
+// Файлы правил в каждой папке; правила .ignore важнее правил .gitignore
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+// Служебные данные VCS, зависимости и результаты сборки: обходы папок (поиск файлов,
+// конвертация, наблюдение) не заходят в них, даже если .gitignore их не перечисляет
+const ALWAYS_SKIPPED_DIRS: [&str; 10] =
+    [".git", "node_modules", "target", "dist", "build", ".next", "out", ".cache", "coverage", "__pycache__"];
+
+/// Раздел "files" настроек рабочей области
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FilesSettings {
+    // Глобы относительно корня рабочей области, как files.exclude в VS Code: "**/.git", "dist/**", "**/*.{log,tmp}"
+    pub exclude: Vec<String>,
+    // Учитывать .gitignore, .ignore, .git/info/exclude и глобальный gitignore
+    pub use_ignore_files: bool,
+}
+
+impl Default for FilesSettings {
+    fn default() -> Self {
+        FilesSettings {
+            exclude: ["**/.git", "**/.svn", "**/.hg", "**/CVS", "**/.DS_Store", "**/Thumbs.db"]
+                .iter()
+                .map(|glob| glob.to_string())
+                .collect(),
+            use_ignore_files: true,
+        }
+    }
+}
+
+/// Видимость файла. Исключенные настройками скрываются всегда,
+/// игнорируемые по .gitignore дерево может показать приглушенными
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IgnoreStatus {
+    Visible,
+    Ignored,
+    Excluded,
+}
+
+/// Правило из .gitignore
+struct Rule {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+fn build_regex(pattern: &str) -> Option<Regex> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(cfg!(any(windows, target_os = "macos")))
+        .build()
+        .ok()
+}
+
+/// Переводит глоб в регулярное выражение без якорей; `braces` включает {a,b}
+fn glob_to_regex(glob: &str, braces: bool) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::new();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                while chars.get(i + 1) == Some(&'*') {
+                    i += 1;
+                }
+                let segment_start = i < 2 || chars[i - 2] == '/';
+                match chars.get(i + 1) {
+                    Some('/') if segment_start => {
+                        regex.push_str("(?:.*/)?");
+                        i += 1;
+                    }
+                    None if segment_start => regex.push_str(".*"),
+                    _ => regex.push_str("[^/]*"),
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => match class_end(&chars, i) {
+                Some(end) => {
+                    regex.push('[');
+                    let mut j = i + 1;
+                    if matches!(chars[j], '!' | '^') {
+                        regex.push('^');
+                        j += 1;
+                    }
+                    for &inner in &chars[j..end] {
+                        if matches!(inner, '\\' | '[' | '&' | '~' | '^') {
+                            regex.push('\\');
+                        }
+                        regex.push(inner);
+                    }
+                    regex.push(']');
+                    i = end;
+                }
+                None => regex.push_str("\\["),
+            },
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                regex.push_str(&regex::escape(&chars[i].to_string()));
+            }
+            '{' if braces => {
+                depth += 1;
+                regex.push_str("(?:");
+            }
+            ',' if braces && depth > 0 => regex.push('|'),
+            '}' if braces && depth > 0 => {
+                depth -= 1;
+                regex.push(')');
+            }
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    for _ in 0..depth {
+        regex.push(')');
+    }
+    regex
+}
+
+/// Индекс закрывающей скобки класса символов; "]" сразу после "[" или "[!" - обычный символ
+fn class_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut j = start + 1;
+    if matches!(chars.get(j), Some('!' | '^')) {
+        j += 1;
+    }
+    if chars.get(j) == Some(&']') {
+        j += 1;
+    }
+    (j..chars.len()).find(|&k| chars[k] == ']')
+}
+
+/// Разбирает строку .gitignore; пустые строки и комментарии дают None
+fn parse_rule(line: &str) -> Option<Rule> {
+    let mut line = line.trim_end_matches('\r');
+    while line.ends_with(' ') && !line.ends_with("\\ ") {
+        line = &line[..line.len() - 1];
+    }
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    // "\!" и "\#" в начале - обычные символы
+    let negated = line.starts_with('!');
+    if negated || line.starts_with("\\!") || line.starts_with("\\#") {
+        line = &line[1..];
+    }
+    let dir_only = line.ends_with('/');
+    let line = line.trim_end_matches('/');
+    // Шаблон со слешем в начале или середине привязан к папке файла правил
+    let anchored = line.contains('/');
+    let line = line.trim_start_matches('/');
+    if line.is_empty() {
+        return None;
+    }
+    let body = glob_to_regex(line, false);
+    let pattern = if anchored {
+        format!("^{}$", body)
+    } else {
+        format!("^(?:.*/)?{}$", body)
+    };
+    Some(Rule {
+        regex: build_regex(&pattern)?,
+        negated,
+        dir_only,
+    })
+}
+
+fn read_rules(path: &Path) -> Vec<Rule> {
+    fs::read_to_string(path)
+        .map(|content| content.lines().filter_map(parse_rule).collect())
+        .unwrap_or_default()
+}
+
+/// Путь относительно `base` через "/"; None, если путь вне `base`
+fn relative(path: &Path, base: &Path) -> Option<String> {
+    let rel = path.strip_prefix(base).ok()?;
+    let parts: Vec<_> = rel.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+    Some(parts.join("/"))
+}
+
+/// Последнее подходящее правило решает: игнорировать (Some(true)) или вернуть (Some(false))
+fn match_rules(rules: &[Rule], rel: &str, is_dir: bool, verdict: &mut Option<bool>) {
+    for rule in rules {
+        if (!rule.dir_only || is_dir) && rule.regex.is_match(rel) {
+            *verdict = Some(!rule.negated);
+        }
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Глобальный gitignore: core.excludesFile из настроек git или ~/.config/git/ignore
+fn global_excludes_file() -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".config"));
+    for config in [home.join(".gitconfig"), config_dir.join("git").join("config")] {
+        let Ok(content) = fs::read_to_string(&config) else {
+            continue;
+        };
+        let mut in_core = false;
+        for line in content.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_core = line.eq_ignore_ascii_case("[core]");
+            } else if let Some((key, value)) = line.split_once('=') {
+                if in_core && key.trim().eq_ignore_ascii_case("excludesfile") {
+                    return Some(expand_home(value.trim().trim_matches('"')));
+                }
+            }
+        }
+    }
+    Some(config_dir.join("git").join("ignore"))
+}
+
+/// Папка git-репозитория; у рабочего дерева и подмодуля .git - файл со ссылкой "gitdir: ..."
+fn git_dir(repo_root: &Path) -> Option<PathBuf> {
+    let dot_git = repo_root.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let content = fs::read_to_string(&dot_git).ok()?;
+    let target = content.trim().strip_prefix("gitdir:")?.trim();
+    Some(repo_root.join(target))
+}
+
+/// Правила игнорирования рабочей области: files.exclude из настроек и файлы правил git.
+/// Файлы .gitignore и .ignore вложенных папок читаются при первом обращении
+pub struct IgnoreRules {
+    workspace_root: PathBuf,
+    // Корень git-репозитория или рабочая область, если репозитория нет
+    rules_root: PathBuf,
+    exclude: Vec<Regex>,
+    use_ignore_files: bool,
+    // Глобальный gitignore и .git/info/exclude, относительно корня репозитория
+    repository_rules: Vec<Rule>,
+    directory_rules: Mutex<HashMap<PathBuf, Arc<Vec<Rule>>>>,
+}
+
+impl IgnoreRules {
+    pub fn new(workspace_root: &Path, settings: &FilesSettings) -> IgnoreRules {
+        let workspace_root = std::path::absolute(workspace_root).unwrap_or_else(|_| workspace_root.to_path_buf());
+        let repo_root = workspace_root
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .map(Path::to_path_buf);
+
+        let mut repository_rules = Vec::new();
+        if let (true, Some(repo_root)) = (settings.use_ignore_files, &repo_root) {
+            if let Some(global) = global_excludes_file() {
+                repository_rules.extend(read_rules(&global));
+            }
+            if let Some(git_dir) = git_dir(repo_root) {
+                repository_rules.extend(read_rules(&git_dir.join("info").join("exclude")));
+            }
+        }
+
+        IgnoreRules {
+            rules_root: repo_root.unwrap_or_else(|| workspace_root.clone()),
+            workspace_root,
+            exclude: settings
+                .exclude
+                .iter()
+                .filter_map(|glob| build_regex(&format!("^{}$", glob_to_regex(glob.trim_end_matches('/'), true))))
+                .collect(),
+            use_ignore_files: settings.use_ignore_files,
+            repository_rules,
+            directory_rules: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Правила для `path`: рабочая область - `workspace_root` или ближайшая папка
+    /// с настройками .xeditor; без нее - сама папка `path`
+    pub fn for_path(path: &Path, workspace_root: Option<&str>) -> IgnoreRules {
+        let root = match workspace_root {
+            Some(root) => PathBuf::from(root),
+            None => path
+                .ancestors()
+                .find(|dir| dir.join(workspace_settings::SETTINGS_DIR).join(workspace_settings::SETTINGS_FILE).is_file())
+                .unwrap_or(path)
+                .to_path_buf(),
+        };
+        let settings = load_settings(&root);
+        IgnoreRules::new(&root, &settings)
+    }
+
+    fn rules_in(&self, dir: &Path) -> Arc<Vec<Rule>> {
+        let Ok(mut cache) = self.directory_rules.lock() else {
+            return Arc::new(Vec::new());
+        };
+        if let Some(rules) = cache.get(dir) {
+            return rules.clone();
+        }
+        cache
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| Arc::new(IGNORE_FILES.iter().flat_map(|name| read_rules(&dir.join(name))).collect()))
+            .clone()
+    }
+
+    /// Статус самого элемента; родительские папки считаются видимыми.
+    /// Подходит для обхода, который не заходит в скрытые папки
+    pub fn status(&self, path: &Path, is_dir: bool) -> IgnoreStatus {
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        if let Some(rel) = relative(&path, &self.workspace_root).filter(|rel| !rel.is_empty()) {
+            if self.exclude.iter().any(|regex| regex.is_match(&rel)) {
+                return IgnoreStatus::Excluded;
+            }
+        }
+        if !self.use_ignore_files {
+            return IgnoreStatus::Visible;
+        }
+        let Some(rel) = relative(&path, &self.rules_root).filter(|rel| !rel.is_empty()) else {
+            return IgnoreStatus::Visible;
+        };
+
+        let mut verdict = None;
+        match_rules(&self.repository_rules, &rel, is_dir, &mut verdict);
+        let Some(parent) = path.parent() else {
+            return IgnoreStatus::Visible;
+        };
+        let mut dirs: Vec<&Path> = parent.ancestors().take_while(|dir| dir.starts_with(&self.rules_root)).collect();
+        dirs.reverse();
+        for dir in dirs {
+            if let Some(rel) = relative(&path, dir) {
+                match_rules(&self.rules_in(dir), &rel, is_dir, &mut verdict);
+            }
+        }
+        if verdict == Some(true) {
+            IgnoreStatus::Ignored
+        } else {
+            IgnoreStatus::Visible
+        }
+    }
+
+    /// Статус с учетом родительских папок: в скрытой папке скрыто все
+    pub fn status_with_parents(&self, path: &Path, is_dir: bool) -> IgnoreStatus {
+        let path = &std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        let mut status = IgnoreStatus::Visible;
+        if let Some(parent) = path.parent() {
+            let mut parents: Vec<&Path> = parent
+                .ancestors()
+                .take_while(|dir| dir.starts_with(&self.workspace_root) && *dir != self.workspace_root)
+                .collect();
+            parents.reverse();
+            for dir in parents {
+                match self.status(dir, true) {
+                    IgnoreStatus::Excluded => return IgnoreStatus::Excluded,
+                    IgnoreStatus::Ignored => status = IgnoreStatus::Ignored,
+                    IgnoreStatus::Visible => {}
+                }
+            }
+        }
+        match self.status(path, is_dir) {
+            IgnoreStatus::Visible => status,
+            own => own,
+        }
+    }
+
+    /// Фильтр для WalkDir::filter_entry: не заходит в игнорируемые, исключенные и всегда пропускаемые папки
+    pub fn allows(&self, entry: &walkdir::DirEntry) -> bool {
+        let is_dir = entry.file_type().is_dir();
+        entry.depth() == 0
+            || (!(is_dir && is_always_skipped(entry.file_name())) && self.status(entry.path(), is_dir) == IgnoreStatus::Visible)
+    }
+}
+
+/// Папка из тех, в которые обходы не заходят никогда (node_modules, target, .git, ...)
+pub fn is_always_skipped(dir_name: &OsStr) -> bool {
+    ALWAYS_SKIPPED_DIRS.iter().any(|name| dir_name == *name)
+}
+
+/// Файл, от которого зависят правила: .gitignore, .ignore или настройки рабочей области
+pub fn is_rules_file(path: &Path) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    IGNORE_FILES.iter().any(|rules| name == *rules)
+        || (name == workspace_settings::SETTINGS_FILE
+            && path.parent().and_then(Path::file_name).is_some_and(|dir| dir == workspace_settings::SETTINGS_DIR))
+}
+
+pub fn load_settings(workspace_root: &Path) -> FilesSettings {
+    workspace_settings::load_section(&workspace_root.to_string_lossy(), SETTINGS_SECTION).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        FilesSettings::default()
+    })
+}
+
+#[tauri::command]
+pub fn get_files_settings(project_root: String) -> Result<FilesSettings, String> {
+    workspace_settings::load_section(&project_root, SETTINGS_SECTION)
+}
+
+#[tauri::command]
+pub fn save_files_settings(project_root: String, settings: FilesSettings) -> Result<(), String> {
+    workspace_settings::save_section(&project_root, SETTINGS_SECTION, &settings)
+}
+
+/// Статусы путей, например для новых файлов из событий наблюдения
+#[tauri::command]
+pub fn get_ignore_status(workspace_root: String, paths: Vec<String>) -> Vec<IgnoreStatus> {
+    let rules = IgnoreRules::for_path(Path::new(&workspace_root), Some(&workspace_root));
+    paths
+        .iter()
+        .map(|path| {
+            let path = Path::new(path);
+            rules.status_with_parents(path, path.is_dir())
+        })
+        .collect()
+}
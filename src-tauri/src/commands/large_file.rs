@@ -0,0 +1,680 @@
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::commands::text_encoding::{self, TextEncoding};
+
+// Смещение запоминается для каждой такой по счету строки: индекс файла в 100 млн строк - около 800 КБ
+const CHECKPOINT_LINES: u64 = 1000;
+const READ_CHUNK: usize = 1 << 20;
+// Ограничения одного запроса, чтобы по IPC не уходили гигабайты
+const MAX_LINES_PER_READ: usize = 10_000;
+const MAX_BYTES_PER_READ: u64 = 16 << 20;
+// Более длинные строки обрезаются (минифицированные файлы, дампы без переводов строк)
+const MAX_LINE_BYTES: usize = 64 * 1024;
+// Поиск смотрит только начало очень длинных строк
+const MAX_SEARCH_LINE_BYTES: usize = 1 << 20;
+// Текст найденной строки в результатах поиска
+const MAX_PREVIEW_CHARS: usize = 500;
+const DEFAULT_MAX_MATCHES: usize = 1000;
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Смещения строк. Строка считается законченной, когда встретился перевод строки (\n);
+/// \r в конце строки отбрасывается при выдаче
+struct LineIndex {
+    // checkpoints[k] - начало строки k * CHECKPOINT_LINES
+    checkpoints: Vec<u64>,
+    // Законченных строк
+    lines: u64,
+    // Сколько байт просмотрено
+    indexed: u64,
+    // Начало последней, еще не законченной строки
+    tail_start: u64,
+    // Первый проход дошел до конца файла
+    complete: bool,
+}
+
+impl LineIndex {
+    fn new(start: u64) -> LineIndex {
+        LineIndex {
+            checkpoints: vec![start],
+            lines: 0,
+            indexed: start,
+            tail_start: start,
+            complete: false,
+        }
+    }
+
+    /// Число строк с учетом последней строки без перевода строки
+    fn line_count(&self) -> u64 {
+        self.lines + u64::from(self.tail_start < self.indexed)
+    }
+}
+
+/// Открытый в режиме больших файлов файл
+struct LargeFile {
+    path: PathBuf,
+    encoding: TextEncoding,
+    // Перевод строки в этой кодировке: 1 байт, в UTF-16 - 2, в UTF-32 - 4
+    newline: Vec<u8>,
+    // Начало текста после BOM
+    start: u64,
+    index: Mutex<LineIndex>,
+    // Индекс дописывает только один поток: первичная индексация или слежение
+    writer: Mutex<()>,
+    closed: AtomicBool,
+    // Нечетное - слежение включено. Каждое включение и выключение увеличивает счетчик,
+    // и поток слежения предыдущего включения завершается, даже если его включили снова
+    follow_generation: AtomicU64,
+}
+
+/// Читает строки с заданного смещения, не держа в памяти больше `limit` байт строки
+struct LineReader<'a> {
+    reader: BufReader<File>,
+    newline: &'a [u8],
+}
+
+impl LineReader<'_> {
+    fn open(file: &LargeFile, offset: u64) -> io::Result<LineReader<'_>> {
+        let mut reader = BufReader::with_capacity(64 * 1024, File::open(&file.path)?);
+        reader.seek(SeekFrom::Start(offset))?;
+        Ok(LineReader {
+            reader,
+            newline: &file.newline,
+        })
+    }
+
+    /// Следующая строка без перевода строки и флаг "обрезана"; None - конец файла
+    fn next_line(&mut self, limit: usize) -> io::Result<Option<(Vec<u8>, bool)>> {
+        let unit = self.newline.len();
+        let mut line = Vec::new();
+        let mut truncated = false;
+        let mut started = false;
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.len() < unit {
+                return Ok(started.then_some((line, truncated)));
+            }
+            started = true;
+            let usable = available.len() - available.len() % unit;
+            let found = if unit == 1 {
+                available.iter().position(|&byte| byte == self.newline[0])
+            } else {
+                available[..usable]
+                    .chunks_exact(unit)
+                    .position(|chunk| chunk == self.newline)
+                    .map(|position| position * unit)
+            };
+            let end = found.unwrap_or(usable);
+            let room = limit.saturating_sub(line.len());
+            line.extend_from_slice(&available[..end.min(room)]);
+            truncated |= end > room;
+            match found {
+                Some(position) => {
+                    self.reader.consume(position + unit);
+                    return Ok(Some((line, truncated)));
+                }
+                None => self.reader.consume(usable),
+            }
+        }
+    }
+}
+
+impl LargeFile {
+    fn decode(&self, bytes: &[u8]) -> String {
+        let (mut text, _) = self.encoding.decode(bytes);
+        if text.ends_with('\r') {
+            text.pop();
+        }
+        text
+    }
+
+    /// Дочитывает индекс до текущего конца файла. false - файл закрыли во время индексации
+    fn extend_index(&self, mut progress: impl FnMut(&LineIndex)) -> io::Result<bool> {
+        let _writer = self.writer.lock().map_err(|e| io::Error::other(e.to_string()))?;
+        let unit = self.newline.len();
+        let mut position = self.index.lock().map_err(|e| io::Error::other(e.to_string()))?.indexed;
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(position))?;
+        let mut buffer = vec![0u8; READ_CHUNK];
+        loop {
+            if self.closed.load(Ordering::Relaxed) {
+                return Ok(false);
+            }
+            let read = file.read(&mut buffer)?;
+            // Половинка символа UTF-16/32 в конце дописываемого файла - дочитаем в следующий раз
+            let usable = read - read % unit;
+            if usable == 0 {
+                return Ok(true);
+            }
+            if usable != read {
+                file.seek(SeekFrom::Start(position + usable as u64))?;
+            }
+            let newlines: Vec<usize> = if unit == 1 {
+                buffer[..usable]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, byte)| **byte == self.newline[0])
+                    .map(|(offset, _)| offset)
+                    .collect()
+            } else {
+                buffer[..usable]
+                    .chunks_exact(unit)
+                    .enumerate()
+                    .filter(|(_, chunk)| *chunk == self.newline.as_slice())
+                    .map(|(index, _)| index * unit)
+                    .collect()
+            };
+
+            let mut index = self.index.lock().map_err(|e| io::Error::other(e.to_string()))?;
+            for offset in newlines {
+                index.lines += 1;
+                index.tail_start = position + (offset + unit) as u64;
+                if index.lines % CHECKPOINT_LINES == 0 {
+                    let line_start = index.tail_start;
+                    index.checkpoints.push(line_start);
+                }
+            }
+            position += usable as u64;
+            index.indexed = position;
+            progress(&index);
+        }
+    }
+
+    /// Останавливает поток слежения, если он запущен
+    fn unfollow(&self) {
+        let _ = self.follow_generation.fetch_update(Ordering::AcqRel, Ordering::Acquire, |generation| {
+            (!generation.is_multiple_of(2)).then_some(generation + 1)
+        });
+    }
+
+    fn reset_index(&self) {
+        if let Ok(mut index) = self.index.lock() {
+            *index = LineIndex {
+                complete: true,
+                ..LineIndex::new(self.start)
+            };
+        }
+    }
+
+    /// Строки с `first` (с нуля), не больше `count`; true - часть строк обрезана
+    fn read_lines(&self, first: u64, count: usize) -> io::Result<(Vec<String>, bool)> {
+        let (mut line, offset) = {
+            let index = self.index.lock().map_err(|e| io::Error::other(e.to_string()))?;
+            let checkpoint = ((first / CHECKPOINT_LINES) as usize).min(index.checkpoints.len() - 1);
+            (checkpoint as u64 * CHECKPOINT_LINES, index.checkpoints[checkpoint])
+        };
+        let mut reader = LineReader::open(self, offset)?;
+        // До нужной строки от ближайшей запомненной - не больше CHECKPOINT_LINES строк,
+        // если индексация уже прошла это место
+        while line < first {
+            if reader.next_line(0)?.is_none() {
+                return Ok((Vec::new(), false));
+            }
+            line += 1;
+        }
+        let mut lines = Vec::new();
+        let mut any_truncated = false;
+        while lines.len() < count {
+            let Some((bytes, truncated)) = reader.next_line(MAX_LINE_BYTES)? else {
+                break;
+            };
+            any_truncated |= truncated;
+            lines.push(self.decode(&bytes));
+        }
+        Ok((lines, any_truncated))
+    }
+
+    fn status(&self) -> LargeFileStatus {
+        let size = fs::metadata(&self.path).map_or(0, |metadata| metadata.len());
+        match self.index.lock() {
+            Ok(index) => LargeFileStatus {
+                size,
+                indexed_bytes: index.indexed,
+                line_count: index.line_count(),
+                complete: index.complete,
+            },
+            Err(_) => LargeFileStatus {
+                size,
+                indexed_bytes: 0,
+                line_count: 0,
+                complete: false,
+            },
+        }
+    }
+}
+
+/// Открытые большие файлы и флаги отмены поиска
+pub struct LargeFileState {
+    files: Mutex<HashMap<String, Arc<LargeFile>>>,
+    searches: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    next_id: AtomicU64,
+}
+
+impl Default for LargeFileState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LargeFileState {
+    pub fn new() -> Self {
+        LargeFileState {
+            files: Mutex::new(HashMap::new()),
+            searches: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    fn get(&self, id: &str) -> Result<Arc<LargeFile>, String> {
+        self.files
+            .lock()
+            .map_err(|e| e.to_string())?
+            .get(id)
+            .cloned()
+            .ok_or_else(|| format!("Файл {} не открыт", id))
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LargeFileInfo {
+    pub id: String,
+    pub path: String,
+    pub size: u64,
+    pub encoding: String,
+    pub bom: bool,
+}
+
+/// Ход индексации; событие "large-file-index-progress" и ответ get_large_file_status
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LargeFileStatus {
+    pub size: u64,
+    pub indexed_bytes: u64,
+    // Строк найдено на данный момент
+    pub line_count: u64,
+    pub complete: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct IndexProgress {
+    id: String,
+    #[serde(flatten)]
+    status: LargeFileStatus,
+}
+
+/// Строки файла; номера строк - с единицы
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LineRange {
+    pub start_line: u64,
+    pub lines: Vec<String>,
+    // Часть строк обрезана до MAX_LINE_BYTES
+    pub truncated: bool,
+    pub line_count: u64,
+    pub complete: bool,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LargeSearchOptions {
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub max_results: Option<usize>,
+    // По нему работает cancel_large_file_search и приходит "large-file-search-progress"
+    pub search_id: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LargeFileMatch {
+    pub line: u64,
+    pub column: usize,
+    // Длина совпадения в символах
+    pub length: usize,
+    pub text: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LargeSearchResult {
+    pub matches: Vec<LargeFileMatch>,
+    // Совпадений больше, чем max_results
+    pub limited: bool,
+    pub cancelled: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SearchProgress {
+    search_id: String,
+    scanned_bytes: u64,
+    size: u64,
+    matches: usize,
+}
+
+/// Дописанные строки, событие "large-file-appended"
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct AppendedLines {
+    id: String,
+    // Номер первой строки в lines, с единицы
+    first_line: u64,
+    lines: Vec<String>,
+    // Строк дописано больше, чем передано: пропущены первые из них
+    skipped: u64,
+    line_count: u64,
+    size: u64,
+    // Файл стал короче (перезаписан или ротирован) - номера строк начались заново
+    truncated: bool,
+}
+
+fn index_in_background(app: AppHandle, id: String, file: Arc<LargeFile>) {
+    let mut last_report = Instant::now();
+    let size = fs::metadata(&file.path).map_or(0, |metadata| metadata.len());
+    let report = |index: &LineIndex, done: bool| {
+        let status = LargeFileStatus {
+            size: size.max(index.indexed),
+            indexed_bytes: index.indexed,
+            line_count: index.line_count(),
+            complete: done,
+        };
+        let _ = app.emit("large-file-index-progress", IndexProgress { id: id.clone(), status });
+    };
+    let result = file.extend_index(|index| {
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            last_report = Instant::now();
+            report(index, false);
+        }
+    });
+    match result {
+        Ok(true) => {
+            if let Ok(mut index) = file.index.lock() {
+                index.complete = true;
+                report(&index, true);
+            }
+        }
+        Ok(false) => {}
+        Err(e) => eprintln!("[large-file] Не удалось проиндексировать {}: {}", file.path.display(), e),
+    }
+}
+
+fn follow_loop(app: AppHandle, id: String, file: Arc<LargeFile>, generation: u64) {
+    while file.follow_generation.load(Ordering::Acquire) == generation && !file.closed.load(Ordering::Relaxed) {
+        std::thread::sleep(FOLLOW_INTERVAL);
+        let Ok((indexed, before, complete)) = file.index.lock().map(|index| (index.indexed, index.lines, index.complete)) else {
+            return;
+        };
+        // Пока идет первичная индексация, все строки - не новые
+        if !complete {
+            continue;
+        }
+        let size = fs::metadata(&file.path).map_or(0, |metadata| metadata.len());
+        let truncated = size < indexed;
+        if size == indexed {
+            continue;
+        }
+        let before = if truncated {
+            file.reset_index();
+            0
+        } else {
+            before
+        };
+        if !matches!(file.extend_index(|_| {}), Ok(true)) {
+            continue;
+        }
+        let Ok(after) = file.index.lock().map(|index| index.lines) else {
+            return;
+        };
+        // Отдаются только законченные строки; незаконченная придет, когда ее допишут
+        let appended = after - before;
+        if appended == 0 && !truncated {
+            continue;
+        }
+        let count = appended.min(MAX_LINES_PER_READ as u64);
+        let lines = match file.read_lines(after - count, count as usize) {
+            Ok((lines, _)) => lines,
+            Err(e) => {
+                eprintln!("[large-file] {}", e);
+                continue;
+            }
+        };
+        let update = AppendedLines {
+            id: id.clone(),
+            first_line: after - count + 1,
+            lines,
+            skipped: appended - count,
+            line_count: after,
+            size,
+            truncated,
+        };
+        if let Err(e) = app.emit("large-file-appended", update) {
+            eprintln!("[large-file] Не удалось отправить новые строки: {}", e);
+        }
+    }
+}
+
+fn search(app: &AppHandle, file: &LargeFile, query: &str, options: &LargeSearchOptions, cancel: &AtomicBool) -> Result<LargeSearchResult, String> {
+    let pattern = if options.regex { query.to_string() } else { regex::escape(query) };
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| format!("Некорректное регулярное выражение: {}", e))?;
+    let max_results = options.max_results.unwrap_or(DEFAULT_MAX_MATCHES);
+    let size = fs::metadata(&file.path).map_or(0, |metadata| metadata.len());
+
+    let mut reader = LineReader::open(file, file.start).map_err(|e| e.to_string())?;
+    let mut result = LargeSearchResult {
+        matches: Vec::new(),
+        limited: false,
+        cancelled: false,
+    };
+    let mut line_number = 0u64;
+    let mut last_report = Instant::now();
+    while let Some((bytes, _)) = reader.next_line(MAX_SEARCH_LINE_BYTES).map_err(|e| e.to_string())? {
+        line_number += 1;
+        if line_number.is_multiple_of(1024) && cancel.load(Ordering::Relaxed) {
+            result.cancelled = true;
+            break;
+        }
+        if let (Some(search_id), true) = (&options.search_id, last_report.elapsed() >= PROGRESS_INTERVAL) {
+            last_report = Instant::now();
+            let progress = SearchProgress {
+                search_id: search_id.clone(),
+                scanned_bytes: reader.reader.stream_position().unwrap_or(0),
+                size,
+                matches: result.matches.len(),
+            };
+            let _ = app.emit("large-file-search-progress", progress);
+        }
+
+        let text = file.decode(&bytes);
+        for found in regex.find_iter(&text) {
+            if result.matches.len() == max_results {
+                result.limited = true;
+                return Ok(result);
+            }
+            result.matches.push(LargeFileMatch {
+                line: line_number,
+                column: text[..found.start()].chars().count() + 1,
+                length: found.as_str().chars().count(),
+                text: text.chars().take(MAX_PREVIEW_CHARS).collect(),
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// Открывает файл без загрузки в память: строки индексируются в фоне
+/// (событие "large-file-index-progress"), читаются по запросу диапазонами
+#[tauri::command]
+pub fn open_large_file(app: AppHandle, state: State<'_, LargeFileState>, path: String, encoding: Option<String>) -> Result<LargeFileInfo, String> {
+    let file_path = PathBuf::from(&path);
+    let size = fs::metadata(&file_path)
+        .map_err(|_| format!("Файл '{}' не существует", path))?
+        .len();
+    let mut head = Vec::new();
+    File::open(&file_path)
+        .and_then(|file| file.take(8192).read_to_end(&mut head))
+        .map_err(|e| format!("Не удалось открыть файл: {}", e))?;
+    let encoding = match encoding {
+        Some(name) => TextEncoding::from_name(&name)?,
+        None => text_encoding::detect_sample(&head)?,
+    };
+    let bom = encoding.has_bom(&head);
+    let start = if bom { encoding.bom_bytes().len() as u64 } else { 0 };
+
+    let id = format!("large-{}", state.next_id.fetch_add(1, Ordering::Relaxed));
+    let file = Arc::new(LargeFile {
+        path: file_path,
+        encoding,
+        newline: encoding.with_bom(false).encode("\n").bytes,
+        start,
+        index: Mutex::new(LineIndex::new(start)),
+        writer: Mutex::new(()),
+        closed: AtomicBool::new(false),
+        follow_generation: AtomicU64::new(0),
+    });
+    state
+        .files
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(id.clone(), file.clone());
+
+    let thread_id = id.clone();
+    std::thread::spawn(move || index_in_background(app, thread_id, file));
+    Ok(LargeFileInfo {
+        id,
+        path,
+        size,
+        encoding: encoding.label.to_string(),
+        bom,
+    })
+}
+
+#[tauri::command]
+pub fn get_large_file_status(state: State<'_, LargeFileState>, id: String) -> Result<LargeFileStatus, String> {
+    Ok(state.get(&id)?.status())
+}
+
+/// Строки с `start_line` (с единицы), не больше `count`. Строки дальше проиндексированного
+/// места тоже читаются, но медленнее: от последней запомненной позиции
+#[tauri::command]
+pub async fn read_large_file_lines(state: State<'_, LargeFileState>, id: String, start_line: u64, count: usize) -> Result<LineRange, String> {
+    let file = state.get(&id)?;
+    let start_line = start_line.max(1);
+    tokio::task::spawn_blocking(move || {
+        let (lines, truncated) = file
+            .read_lines(start_line - 1, count.min(MAX_LINES_PER_READ))
+            .map_err(|e| format!("Не удалось прочитать файл: {}", e))?;
+        let status = file.status();
+        Ok(LineRange {
+            start_line,
+            lines,
+            truncated,
+            line_count: status.line_count,
+            complete: status.complete,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Байты файла с `offset`, не больше `length` (до 16 МБ) - для просмотра двоичных файлов
+#[tauri::command]
+pub async fn read_file_range(path: String, offset: u64, length: u64) -> Result<Vec<u8>, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut file = File::open(&path).map_err(|e| format!("Не удалось открыть файл: {}", e))?;
+        file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+        let mut bytes = Vec::new();
+        file.take(length.min(MAX_BYTES_PER_READ))
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("Не удалось прочитать файл: {}", e))?;
+        Ok(bytes)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Поиск по файлу строка за строкой, без загрузки файла в память
+#[tauri::command]
+pub async fn search_large_file(
+    app: AppHandle,
+    state: State<'_, LargeFileState>,
+    id: String,
+    query: String,
+    options: Option<LargeSearchOptions>,
+) -> Result<LargeSearchResult, String> {
+    let file = state.get(&id)?;
+    let options = options.unwrap_or_default();
+    let cancel = Arc::new(AtomicBool::new(false));
+    if let Some(search_id) = &options.search_id {
+        state
+            .searches
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(search_id.clone(), cancel.clone());
+    }
+    let search_id = options.search_id.clone();
+    let result = tokio::task::spawn_blocking(move || search(&app, &file, &query, &options, &cancel))
+        .await
+        .map_err(|e| e.to_string());
+    if let (Some(search_id), Ok(mut searches)) = (search_id, state.searches.lock()) {
+        searches.remove(&search_id);
+    }
+    result?
+}
+
+#[tauri::command]
+pub fn cancel_large_file_search(state: State<'_, LargeFileState>, search_id: String) -> Result<bool, String> {
+    let searches = state.searches.lock().map_err(|e| e.to_string())?;
+    Ok(match searches.get(&search_id) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    })
+}
+
+/// Как tail -f: дописанные в файл строки приходят событием "large-file-appended"
+#[tauri::command]
+pub fn follow_large_file(app: AppHandle, state: State<'_, LargeFileState>, id: String) -> Result<(), String> {
+    let file = state.get(&id)?;
+    let Ok(previous) = file
+        .follow_generation
+        .fetch_update(Ordering::AcqRel, Ordering::Acquire, |generation| {
+            generation.is_multiple_of(2).then_some(generation + 1)
+        })
+    else {
+        return Ok(());
+    };
+    std::thread::spawn(move || follow_loop(app, id, file, previous + 1));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unfollow_large_file(state: State<'_, LargeFileState>, id: String) -> Result<(), String> {
+    state.get(&id)?.unfollow();
+    Ok(())
+}
+
+/// Закрывает файл: останавливает индексацию и слежение
+#[tauri::command]
+pub fn close_large_file(state: State<'_, LargeFileState>, id: String) -> Result<(), String> {
+    if let Some(file) = state.files.lock().map_err(|e| e.to_string())?.remove(&id) {
+        file.closed.store(true, Ordering::Relaxed);
+        file.unfollow();
+    }
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::atomic_write::write_atomic;
+use crate::commands::file_info;
+use crate::commands::ignore_rules::IgnoreRules;
+use crate::commands::text_encoding;
+
+/// Перевод строки в файле
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    #[serde(rename = "LF")]
+    Lf,
+    #[serde(rename = "CRLF")]
+    Crlf,
+    #[serde(rename = "CR")]
+    Cr,
+    // В файле встречаются разные переводы строк
+    #[serde(rename = "mixed")]
+    Mixed,
+}
+
+impl LineEnding {
+    /// Перевод строки для новых файлов
+    pub fn native() -> LineEnding {
+        if cfg!(windows) {
+            LineEnding::Crlf
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    /// Целевой перевод строки по названию из нижней панели ("LF", "CRLF", "CR")
+    pub fn from_name(name: &str) -> Result<LineEnding, String> {
+        match name.trim().to_ascii_uppercase().as_str() {
+            "LF" => Ok(LineEnding::Lf),
+            "CRLF" => Ok(LineEnding::Crlf),
+            "CR" => Ok(LineEnding::Cr),
+            _ => Err(format!("Неизвестный перевод строки: {}", name)),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+            LineEnding::Mixed => "\n",
+        }
+    }
+}
+
+/// Переводы строк в тексте; None - в тексте одна строка
+pub fn detect(text: &str) -> Option<LineEnding> {
+    let (mut lf, mut crlf, mut cr) = (0usize, 0usize, 0usize);
+    let mut bytes = text.bytes().peekable();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'\r' if bytes.peek() == Some(&b'\n') => {
+                bytes.next();
+                crlf += 1;
+            }
+            b'\r' => cr += 1,
+            b'\n' => lf += 1,
+            _ => {}
+        }
+    }
+    match (lf > 0, crlf > 0, cr > 0) {
+        (false, false, false) => None,
+        (true, false, false) => Some(LineEnding::Lf),
+        (false, true, false) => Some(LineEnding::Crlf),
+        (false, false, true) => Some(LineEnding::Cr),
+        _ => Some(LineEnding::Mixed),
+    }
+}
+
+/// Приводит все переводы строк текста к `target`
+pub fn convert(text: &str, target: LineEnding) -> String {
+    let separator = target.as_str();
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                result.push_str(separator);
+            }
+            '\n' => result.push_str(separator),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Результат конвертации одного файла
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionResult {
+    pub path: String,
+    pub line_ending: Option<LineEnding>,
+    pub bom: bool,
+    // false - файл уже был в нужном виде и не перезаписывался
+    pub changed: bool,
+}
+
+/// Переписывает файл с другими переводами строк и/или BOM; кодировка сохраняется
+fn convert_file_impl(path: &Path, line_ending: Option<LineEnding>, bom: Option<bool>) -> Result<ConversionResult, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Не удалось открыть файл: {}", e))?;
+    let encoding = text_encoding::detect(&bytes)?;
+    let (content, malformed) = encoding.decode(&bytes);
+    if malformed {
+        return Err(format!("Файл '{}' содержит некорректные символы в кодировке {}", path.display(), encoding.label));
+    }
+
+    let encoding = encoding.with_bom(bom.unwrap_or(encoding.bom));
+    let content = match line_ending {
+        Some(target) => convert(&content, target),
+        None => content,
+    };
+    let converted = encoding.encode(&content).bytes;
+    let changed = converted != bytes;
+    if changed {
+        write_atomic(path, &converted).map_err(|e| e.message().to_string())?;
+    }
+    Ok(ConversionResult {
+        path: path.to_string_lossy().to_string(),
+        line_ending: detect(&content),
+        bom: encoding.bom,
+        changed,
+    })
+}
+
+fn parse_target(line_ending: Option<String>) -> Result<Option<LineEnding>, String> {
+    line_ending.as_deref().map(LineEnding::from_name).transpose()
+}
+
+/// Меняет переводы строк (`line_ending`: "LF", "CRLF", "CR") и/или наличие BOM в файле
+#[tauri::command]
+pub fn convert_line_endings(path: String, line_ending: Option<String>, bom: Option<bool>) -> Result<ConversionResult, String> {
+    convert_file_impl(Path::new(&path), parse_target(line_ending)?, bom)
+}
+
+/// Итог конвертации папки
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderConversionResult {
+    pub converted: Vec<String>,
+    pub unchanged: usize,
+    // Двоичные файлы и файлы, кодировка которых угадана, а не определена надежно
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Конвертирует все текстовые файлы папки; `extensions` ограничивает набор файлов
+#[tauri::command]
+pub async fn convert_folder_line_endings(
+    path: String,
+    line_ending: Option<String>,
+    bom: Option<bool>,
+    extensions: Option<Vec<String>>,
+) -> Result<FolderConversionResult, String> {
+    let target = parse_target(line_ending)?;
+    if !Path::new(&path).is_dir() {
+        return Err(format!("Папка '{}' не существует", path));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let mut result = FolderConversionResult::default();
+        // Игнорируемые по .gitignore, исключенные настройками и зависимости (node_modules, target, ...) не трогаем
+        let ignore_rules = IgnoreRules::for_path(Path::new(&path), None);
+        let walker = WalkDir::new(&path).into_iter().filter_entry(|entry| ignore_rules.allows(entry));
+        for entry in walker.filter_map(|entry| entry.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let file = entry.path();
+            if let Some(extensions) = &extensions {
+                let extension = file.extension().and_then(|e| e.to_str()).unwrap_or_default();
+                if !extensions.iter().any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(extension)) {
+                    continue;
+                }
+            }
+            let display = file.to_string_lossy().to_string();
+            match std::fs::read(file) {
+                Ok(bytes) if is_certainly_text(&bytes) => {}
+                Ok(_) => {
+                    result.skipped.push(display);
+                    continue;
+                }
+                Err(e) => {
+                    result.errors.push(format!("{}: {}", display, e));
+                    continue;
+                }
+            }
+            match convert_file_impl(file, target, bom) {
+                Ok(converted) if converted.changed => result.converted.push(display),
+                Ok(_) => result.unchanged += 1,
+                Err(e) => result.errors.push(format!("{}: {}", display, e)),
+            }
+        }
+        Ok(result)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Массовая конвертация трогает только явный текст: UTF-8 или файл с BOM. Двоичный файл
+/// detect может принять за UTF-16 без BOM или за однобайтовую кодировку, и запись его испортит
+fn is_certainly_text(bytes: &[u8]) -> bool {
+    if file_info::sniff_binary(bytes).is_some() {
+        return false;
+    }
+    match text_encoding::detect(bytes) {
+        Ok(encoding) if encoding.has_bom(bytes) => true,
+        Ok(_) => !bytes.contains(&0) && std::str::from_utf8(bytes).is_ok(),
+        Err(_) => false,
+    }
+}
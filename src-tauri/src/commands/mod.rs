@@ -2,5 +2,19 @@ pub mod window_commands;
 pub mod file_operations;
 pub mod terminal; // Добавленная строка
 pub mod language_server;
+pub mod directory_listing; // Сортированное постраничное чтение папок с кэшем
 pub mod fs_commands; // Модуль для автодополнения импортов
-pub mod fonts; // Add new fonts module
\ No newline at end of file
+pub mod fs_journal; // Журнал операций с файлами для отмены и повтора
+pub mod fonts; // Add new fonts module
+pub mod file_info; // Сведения о файле: права, владелец, тип по содержимому
+pub mod file_transfer; // Копирование и перемещение файлов и папок
+pub mod file_watcher; // Наблюдение за файлами рабочей области
+pub mod ignore_rules; // .gitignore, .ignore и исключения из настроек для дерева и поиска файлов
+pub mod large_file; // Большие файлы: индекс строк, чтение диапазонов, поиск и слежение
+pub mod line_endings; // Переводы строк: определение, сохранение и конвертация
+pub mod problem_matchers; // Разбор вывода компиляторов в диагностики
+pub mod project_env; // Окружение проекта: .env, venv, node_modules/.bin
+pub mod tasks; // Задачи проекта (npm, make, cargo, pyproject)
+pub mod text_encoding; // Определение кодировки и перекодирование текстовых файлов
+pub mod trash; // Корзина ОС: удаление, список и восстановление
+pub mod workspace_settings; // Настройки рабочей области (.xeditor/settings.json)
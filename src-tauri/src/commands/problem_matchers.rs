@@ -0,0 +1,496 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::workspace_settings;
+
+// Раздел .xeditor/settings.json с настройками разбора вывода
+const SETTINGS_SECTION: &str = "problemMatchers";
+// Строка без перевода строки длиннее этого - не вывод компилятора, а, например, прогресс-бар
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+lazy_static! {
+    static ref ANSI_ESCAPE: Regex =
+        Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]").unwrap();
+    // main.c:3:5: error: expected ';' before '}' token [-Werror]
+    static ref GCC: Regex =
+        Regex::new(r"^(.+?):(\d+):(\d+): (?:fatal )?(error|warning|note): (.+?)(?: \[(-W[^\]]+)\])?$").unwrap();
+    // src/app.ts(12,5): error TS2322: ...
+    static ref TSC: Regex = Regex::new(r"^(.+?)\((\d+),(\d+)\): (error|warning) (TS\d+): (.+)$").unwrap();
+    // src/app.ts:12:5 - error TS2322: ... (tsc --pretty)
+    static ref TSC_PRETTY: Regex = Regex::new(r"^(.+?):(\d+):(\d+) - (error|warning) (TS\d+): (.+)$").unwrap();
+    // error[E0308]: mismatched types
+    static ref RUSTC_HEADER: Regex = Regex::new(r"^(error|warning)(?:\[(\w+)\])?: (.+)$").unwrap();
+    //   --> src/main.rs:4:5
+    static ref RUSTC_LOCATION: Regex = Regex::new(r"^\s*--> (.+?):(\d+):(\d+)$").unwrap();
+    //   12:5  error  'x' is defined but never used  no-unused-vars
+    static ref ESLINT_STYLISH: Regex =
+        Regex::new(r"^\s+(\d+):(\d+)\s+(error|warning)\s+(.+?)(?:\s{2,}(\S+))?$").unwrap();
+    // /src/app.js: line 12, col 5, Error - 'x' is defined but never used. (no-unused-vars)
+    static ref ESLINT_COMPACT: Regex =
+        Regex::new(r"^(.+?): line (\d+), col (\d+), (Error|Warning) - (.+?)(?: \(([^()]+)\))?$").unwrap();
+    //   File "app.py", line 3, in main
+    static ref PYTHON_FRAME: Regex = Regex::new(r#"^\s*File "(.+)", line (\d+)"#).unwrap();
+    // ValueError: invalid literal for int()
+    static ref PYTHON_EXCEPTION: Regex =
+        Regex::new(r"^([A-Za-z_][\w.]*(?:Error|Exception|Warning|Interrupt|Exit))(?:: (.*))?$").unwrap();
+}
+
+/// Встроенные matcher-ы
+pub const BUILTIN_MATCHERS: [&str; 6] = ["tsc", "cargo", "cargo-json", "eslint", "gcc", "python"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    fn parse(text: &str) -> Severity {
+        let text = text.to_lowercase();
+        if text.contains("warn") {
+            Severity::Warning
+        } else if text.contains("note") || text.contains("info") || text.contains("help") {
+            Severity::Info
+        } else {
+            Severity::Error
+        }
+    }
+}
+
+/// Проблема, найденная в выводе процесса
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: u32,
+    pub column: Option<u32>,
+    pub severity: Severity,
+    pub message: String,
+    pub code: Option<String>,
+    // Имя matcher-а, который нашел проблему
+    pub source: String,
+}
+
+/// Пользовательский matcher: регулярное выражение и номера его групп
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternConfig {
+    pub name: String,
+    pub regexp: String,
+    pub file: usize,
+    pub line: usize,
+    #[serde(default)]
+    pub column: Option<usize>,
+    #[serde(default)]
+    pub severity: Option<usize>,
+    pub message: usize,
+    #[serde(default)]
+    pub code: Option<usize>,
+    // Если в строке нет группы с уровнем
+    #[serde(default)]
+    pub default_severity: Severity,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemMatcherSettings {
+    // Встроенные matcher-ы, которые не нужно применять
+    #[serde(default)]
+    pub disabled: Vec<String>,
+    #[serde(default)]
+    pub custom: Vec<PatternConfig>,
+}
+
+/// Payload события `problems-updated` для панели Problems
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemsUpdate {
+    // Чьи это проблемы: "task:npm:build", "terminal:term-1"
+    pub owner: String,
+    pub diagnostics: Vec<Diagnostic>,
+    // true - прежние проблемы этого владельца нужно удалить
+    pub reset: bool,
+}
+
+pub fn publish(app: &AppHandle, owner: &str, diagnostics: Vec<Diagnostic>, reset: bool) {
+    if diagnostics.is_empty() && !reset {
+        return;
+    }
+    let _ = app.emit(
+        "problems-updated",
+        ProblemsUpdate {
+            owner: owner.to_string(),
+            diagnostics,
+            reset,
+        },
+    );
+}
+
+fn capture_u32(captures: &regex::Captures, group: usize) -> Option<u32> {
+    captures.get(group)?.as_str().parse().ok()
+}
+
+fn capture_string(captures: &regex::Captures, group: usize) -> Option<String> {
+    captures.get(group).map(|m| m.as_str().trim().to_string())
+}
+
+/// Разбор вывода построчно; matcher может копить состояние между строками
+trait LineMatcher: Send {
+    fn feed(&mut self, line: &str) -> Option<Diagnostic>;
+
+    /// Начало нового вывода (новая команда в терминале)
+    fn reset(&mut self) {}
+}
+
+/// Однострочный шаблон: встроенный (gcc, tsc) или из настроек
+struct PatternMatcher {
+    name: String,
+    regex: Regex,
+    config: PatternConfig,
+}
+
+impl PatternMatcher {
+    #[allow(clippy::too_many_arguments)]
+    fn builtin(
+        name: &str,
+        regex: &Regex,
+        file: usize,
+        line: usize,
+        column: usize,
+        severity: usize,
+        message: usize,
+        code: usize,
+    ) -> PatternMatcher {
+        PatternMatcher {
+            name: name.to_string(),
+            regex: regex.clone(),
+            config: PatternConfig {
+                name: name.to_string(),
+                regexp: regex.as_str().to_string(),
+                file,
+                line,
+                column: Some(column),
+                severity: Some(severity),
+                message,
+                code: Some(code),
+                default_severity: Severity::Error,
+            },
+        }
+    }
+}
+
+impl LineMatcher for PatternMatcher {
+    fn feed(&mut self, line: &str) -> Option<Diagnostic> {
+        let captures = self.regex.captures(line)?;
+        let config = &self.config;
+        Some(Diagnostic {
+            file: capture_string(&captures, config.file)?,
+            line: capture_u32(&captures, config.line)?,
+            column: config.column.and_then(|group| capture_u32(&captures, group)),
+            severity: config
+                .severity
+                .and_then(|group| captures.get(group))
+                .map(|m| Severity::parse(m.as_str()))
+                .unwrap_or(config.default_severity),
+            message: capture_string(&captures, config.message)?,
+            code: config.code.and_then(|group| capture_string(&captures, group)),
+            source: self.name.clone(),
+        })
+    }
+}
+
+/// Человекочитаемый вывод rustc: заголовок, затем строка `--> файл:строка:колонка`
+#[derive(Default)]
+struct RustcMatcher {
+    pending: Option<(Severity, Option<String>, String)>,
+}
+
+impl LineMatcher for RustcMatcher {
+    fn feed(&mut self, line: &str) -> Option<Diagnostic> {
+        if let Some(captures) = RUSTC_HEADER.captures(line) {
+            self.pending = Some((
+                Severity::parse(&captures[1]),
+                capture_string(&captures, 2),
+                captures[3].trim().to_string(),
+            ));
+            return None;
+        }
+        let captures = RUSTC_LOCATION.captures(line)?;
+        let (severity, code, message) = self.pending.take()?;
+        Some(Diagnostic {
+            file: captures[1].to_string(),
+            line: capture_u32(&captures, 2)?,
+            column: capture_u32(&captures, 3),
+            severity,
+            message,
+            code,
+            source: "cargo".to_string(),
+        })
+    }
+
+    fn reset(&mut self) {
+        self.pending = None;
+    }
+}
+
+/// cargo --message-format=json: по JSON-объекту на строку
+struct CargoJsonMatcher;
+
+impl LineMatcher for CargoJsonMatcher {
+    fn feed(&mut self, line: &str) -> Option<Diagnostic> {
+        if !line.starts_with('{') {
+            return None;
+        }
+        let json: serde_json::Value = serde_json::from_str(line).ok()?;
+        if json["reason"] != "compiler-message" {
+            return None;
+        }
+        let message = &json["message"];
+        let span = message["spans"]
+            .as_array()?
+            .iter()
+            .find(|span| span["is_primary"].as_bool() == Some(true))?;
+        Some(Diagnostic {
+            file: span["file_name"].as_str()?.to_string(),
+            line: span["line_start"].as_u64()? as u32,
+            column: span["column_start"].as_u64().map(|c| c as u32),
+            severity: Severity::parse(message["level"].as_str().unwrap_or("error")),
+            message: message["message"].as_str()?.to_string(),
+            code: message["code"]["code"].as_str().map(|c| c.to_string()),
+            source: "cargo-json".to_string(),
+        })
+    }
+}
+
+/// eslint: stylish (файл, затем строки с позициями) и compact
+#[derive(Default)]
+struct EslintMatcher {
+    current_file: Option<String>,
+}
+
+impl LineMatcher for EslintMatcher {
+    fn feed(&mut self, line: &str) -> Option<Diagnostic> {
+        if let Some(captures) = ESLINT_COMPACT.captures(line) {
+            return Some(Diagnostic {
+                file: captures[1].to_string(),
+                line: capture_u32(&captures, 2)?,
+                column: capture_u32(&captures, 3),
+                severity: Severity::parse(&captures[4]),
+                message: captures[5].trim().to_string(),
+                code: capture_string(&captures, 6),
+                source: "eslint".to_string(),
+            });
+        }
+
+        if let Some(captures) = ESLINT_STYLISH.captures(line) {
+            return Some(Diagnostic {
+                file: self.current_file.clone()?,
+                line: capture_u32(&captures, 1)?,
+                column: capture_u32(&captures, 2),
+                severity: Severity::parse(&captures[3]),
+                message: captures[4].trim().to_string(),
+                code: capture_string(&captures, 5),
+                source: "eslint".to_string(),
+            });
+        }
+
+        // В stylish имя файла печатается отдельной строкой без отступа
+        let trimmed = line.trim_end();
+        if !trimmed.is_empty() && !line.starts_with(char::is_whitespace) {
+            let looks_like_path = (trimmed.contains('/') || trimmed.contains('\\'))
+                && !trimmed.contains(' ')
+                && Path::new(trimmed).extension().is_some();
+            self.current_file = looks_like_path.then(|| trimmed.to_string());
+        }
+        None
+    }
+
+    fn reset(&mut self) {
+        self.current_file = None;
+    }
+}
+
+/// Трассировка Python: проблема - в последнем кадре, сообщение - из строки исключения
+#[derive(Default)]
+struct PythonMatcher {
+    last_frame: Option<(String, u32)>,
+}
+
+impl LineMatcher for PythonMatcher {
+    fn feed(&mut self, line: &str) -> Option<Diagnostic> {
+        if line.starts_with("Traceback (most recent call last)") {
+            self.last_frame = None;
+            return None;
+        }
+        if let Some(captures) = PYTHON_FRAME.captures(line) {
+            // <stdin>, <string> и т.п. - не файлы
+            if !captures[1].starts_with('<') {
+                self.last_frame = Some((captures[1].to_string(), capture_u32(&captures, 2)?));
+            }
+            return None;
+        }
+        let captures = PYTHON_EXCEPTION.captures(line)?;
+        let (file, line_number) = self.last_frame.take()?;
+        let exception = captures[1].to_string();
+        let message = match captures.get(2) {
+            Some(text) if !text.as_str().is_empty() => format!("{}: {}", exception, text.as_str()),
+            _ => exception.clone(),
+        };
+        Some(Diagnostic {
+            file,
+            line: line_number,
+            column: None,
+            severity: if exception.ends_with("Warning") {
+                Severity::Warning
+            } else {
+                Severity::Error
+            },
+            message,
+            code: Some(exception),
+            source: "python".to_string(),
+        })
+    }
+
+    fn reset(&mut self) {
+        self.last_frame = None;
+    }
+}
+
+fn builtin_matchers(name: &str) -> Vec<Box<dyn LineMatcher>> {
+    match name {
+        "gcc" => vec![Box::new(PatternMatcher::builtin("gcc", &GCC, 1, 2, 3, 4, 5, 6))],
+        "tsc" => vec![
+            Box::new(PatternMatcher::builtin("tsc", &TSC, 1, 2, 3, 4, 6, 5)),
+            Box::new(PatternMatcher::builtin("tsc", &TSC_PRETTY, 1, 2, 3, 4, 6, 5)),
+        ],
+        "cargo" => vec![Box::new(RustcMatcher::default())],
+        "cargo-json" => vec![Box::new(CargoJsonMatcher)],
+        "eslint" => vec![Box::new(EslintMatcher::default())],
+        "python" => vec![Box::new(PythonMatcher::default())],
+        _ => Vec::new(),
+    }
+}
+
+pub fn load_settings(project_root: Option<&str>) -> ProblemMatcherSettings {
+    match project_root {
+        Some(root) => workspace_settings::load_section(root, SETTINGS_SECTION).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            ProblemMatcherSettings::default()
+        }),
+        None => ProblemMatcherSettings::default(),
+    }
+}
+
+/// Превращает поток вывода процесса в диагностики
+pub struct ProblemCollector {
+    matchers: Vec<Box<dyn LineMatcher>>,
+    // Относительно этой директории разрешаются пути из вывода
+    base_dir: PathBuf,
+    partial: String,
+}
+
+impl ProblemCollector {
+    /// `only` ограничивает набор matcher-ов по имени (встроенные и пользовательские)
+    pub fn new(settings: &ProblemMatcherSettings, base_dir: &Path, only: Option<&[String]>) -> ProblemCollector {
+        let enabled = |name: &str| match only {
+            Some(names) => names.iter().any(|n| n == name),
+            None => !settings.disabled.iter().any(|n| n == name),
+        };
+
+        let mut matchers: Vec<Box<dyn LineMatcher>> = Vec::new();
+        for name in BUILTIN_MATCHERS.iter().filter(|name| enabled(name)) {
+            matchers.extend(builtin_matchers(name));
+        }
+        for config in settings.custom.iter().filter(|config| enabled(&config.name)) {
+            match Regex::new(&config.regexp) {
+                Ok(regex) => matchers.push(Box::new(PatternMatcher {
+                    name: config.name.clone(),
+                    regex,
+                    config: config.clone(),
+                })),
+                Err(e) => eprintln!("Некорректное выражение matcher-а '{}': {}", config.name, e),
+            }
+        }
+
+        ProblemCollector {
+            matchers,
+            base_dir: base_dir.to_path_buf(),
+            partial: String::new(),
+        }
+    }
+
+    pub fn set_base_dir(&mut self, base_dir: &Path) {
+        self.base_dir = base_dir.to_path_buf();
+    }
+
+    pub fn reset(&mut self) {
+        self.partial.clear();
+        for matcher in &mut self.matchers {
+            matcher.reset();
+        }
+    }
+
+    /// Принимает очередной фрагмент вывода; неполная последняя строка ждет продолжения
+    pub fn feed(&mut self, text: &str) -> Vec<Diagnostic> {
+        self.partial.push_str(text);
+        let mut diagnostics = Vec::new();
+        while let Some(end) = self.partial.find('\n') {
+            let line: String = self.partial.drain(..=end).collect();
+            diagnostics.extend(self.feed_line(&line));
+        }
+        if self.partial.len() > MAX_LINE_BYTES {
+            self.partial.clear();
+        }
+        diagnostics
+    }
+
+    /// Разбирает остаток вывода без перевода строки в конце
+    pub fn finish(&mut self) -> Vec<Diagnostic> {
+        let rest = std::mem::take(&mut self.partial);
+        self.feed_line(&rest)
+    }
+
+    fn feed_line(&mut self, raw: &str) -> Vec<Diagnostic> {
+        let line = ANSI_ESCAPE.replace_all(raw.trim_end_matches(['\n', '\r']), "");
+        // После \r терминал перерисовывает строку - значим только последний вариант
+        let line = line.rsplit('\r').next().unwrap_or_default();
+        if line.trim().is_empty() {
+            return Vec::new();
+        }
+
+        let mut diagnostics = Vec::new();
+        for matcher in &mut self.matchers {
+            if let Some(mut diagnostic) = matcher.feed(line) {
+                let path = Path::new(&diagnostic.file);
+                if path.is_relative() {
+                    diagnostic.file = self.base_dir.join(path).to_string_lossy().to_string();
+                }
+                if !diagnostics.contains(&diagnostic) {
+                    diagnostics.push(diagnostic);
+                }
+            }
+        }
+        diagnostics
+    }
+}
+
+#[tauri::command]
+pub fn get_problem_matcher_settings(project_root: String) -> Result<ProblemMatcherSettings, String> {
+    workspace_settings::load_section(&project_root, SETTINGS_SECTION)
+}
+
+#[tauri::command]
+pub fn save_problem_matcher_settings(project_root: String, settings: ProblemMatcherSettings) -> Result<(), String> {
+    // Проверяем выражения сразу, а не при первом запуске задачи
+    for config in &settings.custom {
+        Regex::new(&config.regexp)
+            .map_err(|e| format!("Некорректное выражение matcher-а '{}': {}", config.name, e))?;
+    }
+    workspace_settings::save_section(&project_root, SETTINGS_SECTION, &settings)
+}
@@ -0,0 +1,361 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::commands::terminal::runner;
+use crate::commands::workspace_settings;
+
+// Раздел .xeditor/settings.json с окружением проекта
+const SETTINGS_SECTION: &str = "environment";
+
+fn default_env_files() -> Vec<String> {
+    vec![".env".to_string(), ".env.local".to_string()]
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Настройки окружения, в котором запускаются терминалы, задачи и файлы проекта
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentSettings {
+    // Загружаются по порядку, относительно корня проекта; более поздние переопределяют ранние
+    #[serde(default = "default_env_files")]
+    pub env_files: Vec<String>,
+    #[serde(default = "default_true")]
+    pub node_modules_bin: bool,
+    #[serde(default = "default_true")]
+    pub activate_venv: bool,
+    // Путь к venv, если он не в .venv/venv/env корня проекта
+    #[serde(default)]
+    pub venv: Option<String>,
+    // Применяются последними; null удаляет переменную
+    #[serde(default)]
+    pub variables: BTreeMap<String, Option<String>>,
+}
+
+impl Default for EnvironmentSettings {
+    fn default() -> Self {
+        EnvironmentSettings {
+            env_files: default_env_files(),
+            node_modules_bin: true,
+            activate_venv: true,
+            venv: None,
+            variables: BTreeMap::new(),
+        }
+    }
+}
+
+/// Переменная, которую окружение проекта меняет относительно окружения редактора
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvVariable {
+    pub name: String,
+    // None - переменная удаляется
+    pub value: Option<String>,
+    // ".env", "node_modules", "venv", "settings"
+    pub source: String,
+    // Значение в окружении редактора
+    pub inherited: Option<String>,
+}
+
+/// Окружение проекта: только отличия от окружения редактора
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectEnvironment {
+    pub variables: Vec<EnvVariable>,
+    // Загруженные .env-файлы
+    pub env_files: Vec<String>,
+    pub venv: Option<String>,
+    // Каталоги, добавленные в начало PATH
+    pub path_entries: Vec<String>,
+    // Ошибки разбора .env и т.п. - окружение все равно строится
+    pub warnings: Vec<String>,
+}
+
+#[cfg(windows)]
+const PATH_VAR: &str = "Path";
+#[cfg(not(windows))]
+const PATH_VAR: &str = "PATH";
+
+/// Накопитель изменений: значение и источник каждой переменной
+#[derive(Default)]
+struct Builder {
+    changes: BTreeMap<String, (Option<String>, String)>,
+}
+
+impl Builder {
+    fn get(&self, name: &str) -> Option<String> {
+        match self.changes.get(name) {
+            Some((value, _)) => value.clone(),
+            None => std::env::var(name).ok(),
+        }
+    }
+
+    fn set(&mut self, name: &str, value: Option<String>, source: &str) {
+        self.changes.insert(name.to_string(), (value, source.to_string()));
+    }
+
+    fn prepend_path(&mut self, dir: &Path, source: &str) {
+        let current = self.get(PATH_VAR).unwrap_or_default();
+        let paths = std::iter::once(dir.to_path_buf()).chain(std::env::split_paths(&current));
+        if let Ok(joined) = std::env::join_paths(paths) {
+            self.set(PATH_VAR, Some(joined.to_string_lossy().to_string()), source);
+        }
+    }
+}
+
+/// Подставляет $NAME, ${NAME}, ${NAME:-default} и ${NAME-default}; \$ - буквальный доллар
+fn expand(value: &str, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let mut result = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if chars.get(i + 1) == Some(&'$') => {
+                result.push('$');
+                i += 2;
+            }
+            '$' if chars.get(i + 1) == Some(&'{') => {
+                let Some(close) = chars[i + 2..].iter().position(|&c| c == '}') else {
+                    result.extend(&chars[i..]);
+                    break;
+                };
+                let inner: String = chars[i + 2..i + 2 + close].iter().collect();
+                let replacement = if let Some((name, default)) = inner.split_once(":-") {
+                    lookup(name).filter(|v| !v.is_empty()).unwrap_or_else(|| expand(default, lookup))
+                } else if let Some((name, default)) = inner.split_once('-') {
+                    lookup(name).unwrap_or_else(|| expand(default, lookup))
+                } else {
+                    lookup(&inner).unwrap_or_default()
+                };
+                result.push_str(&replacement);
+                i += close + 3;
+            }
+            '$' if chars.get(i + 1).is_some_and(|c| c.is_ascii_alphabetic() || *c == '_') => {
+                let length = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+                    .count();
+                let name: String = chars[i + 1..i + 1 + length].iter().collect();
+                result.push_str(&lookup(&name).unwrap_or_default());
+                i += length + 1;
+            }
+            c => {
+                result.push(c);
+                i += 1;
+            }
+        }
+    }
+    result
+}
+
+/// Разбирает .env: KEY=VALUE, `export`, комментарии, кавычки и многострочные значения в "..."
+fn load_env_file(path: &Path, name: &str, builder: &mut Builder, warnings: &mut Vec<String>) {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            warnings.push(format!("Не удалось прочитать {}: {}", name, e));
+            return;
+        }
+    };
+
+    let mut lines = content.lines().enumerate();
+    while let Some((index, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, raw)) = line.split_once('=') else {
+            warnings.push(format!("{}:{}: ожидалось KEY=VALUE", name, index + 1));
+            continue;
+        };
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+            warnings.push(format!("{}:{}: некорректное имя переменной '{}'", name, index + 1, key));
+            continue;
+        }
+
+        let raw = raw.trim();
+        let value = if let Some(rest) = raw.strip_prefix('\'') {
+            // В одинарных кавычках - как есть, без подстановок
+            match rest.find('\'') {
+                Some(end) => rest[..end].to_string(),
+                None => {
+                    warnings.push(format!("{}:{}: нет закрывающей кавычки", name, index + 1));
+                    continue;
+                }
+            }
+        } else if let Some(rest) = raw.strip_prefix('"') {
+            let mut text = rest.to_string();
+            // Значение может продолжаться на следующих строках до закрывающей кавычки
+            let end = loop {
+                if let Some(end) = closing_quote(&text) {
+                    break Some(end);
+                }
+                match lines.next() {
+                    Some((_, next)) => {
+                        text.push('\n');
+                        text.push_str(next);
+                    }
+                    None => break None,
+                }
+            };
+            let Some(end) = end else {
+                warnings.push(format!("{}:{}: нет закрывающей кавычки", name, index + 1));
+                continue;
+            };
+            let unescaped = text[..end]
+                .replace("\\n", "\n")
+                .replace("\\t", "\t")
+                .replace("\\\"", "\"");
+            expand(&unescaped, &|var| builder.get(var))
+        } else {
+            // Без кавычек комментарий начинается с " #"
+            let value = raw.split(" #").next().unwrap_or_default().trim_end();
+            expand(value, &|var| builder.get(var))
+        };
+        builder.set(key, Some(value), name);
+    }
+}
+
+/// Позиция закрывающей двойной кавычки, не экранированной обратной косой чертой
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(index),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+fn load_settings(project_root: &str, warnings: &mut Vec<String>) -> EnvironmentSettings {
+    workspace_settings::load_section(project_root, SETTINGS_SECTION).unwrap_or_else(|e| {
+        warnings.push(e);
+        EnvironmentSettings::default()
+    })
+}
+
+impl ProjectEnvironment {
+    /// Строит окружение проекта: .env-файлы, node_modules/.bin, venv, затем переопределения из настроек
+    pub fn load(project_root: &str) -> ProjectEnvironment {
+        let root = Path::new(project_root);
+        let mut environment = ProjectEnvironment::default();
+        let settings = load_settings(project_root, &mut environment.warnings);
+        let mut builder = Builder::default();
+
+        for name in &settings.env_files {
+            let path = root.join(name);
+            if path.is_file() {
+                load_env_file(&path, name, &mut builder, &mut environment.warnings);
+                environment.env_files.push(path.to_string_lossy().to_string());
+            }
+        }
+
+        let mut path_entries: Vec<PathBuf> = Vec::new();
+        if settings.activate_venv {
+            let venv = match &settings.venv {
+                Some(venv) => Some(root.join(venv)).filter(|venv| venv.is_dir()),
+                None => runner::venv_in(root),
+            };
+            match venv {
+                Some(venv) => {
+                    builder.set("VIRTUAL_ENV", Some(venv.to_string_lossy().to_string()), "venv");
+                    // Как activate: PYTHONHOME сломал бы стандартную библиотеку venv
+                    if std::env::var_os("PYTHONHOME").is_some() {
+                        builder.set("PYTHONHOME", None, "venv");
+                    }
+                    builder.prepend_path(&runner::venv_bin(&venv), "venv");
+                    path_entries.push(runner::venv_bin(&venv));
+                    environment.venv = Some(venv.to_string_lossy().to_string());
+                }
+                None if settings.venv.is_some() => {
+                    environment
+                        .warnings
+                        .push(format!("Виртуальное окружение не найдено: {}", settings.venv.as_deref().unwrap_or_default()));
+                }
+                None => {}
+            }
+        }
+
+        if settings.node_modules_bin {
+            let bin = root.join("node_modules").join(".bin");
+            if bin.is_dir() {
+                builder.prepend_path(&bin, "node_modules");
+                path_entries.insert(0, bin);
+            }
+        }
+
+        for (name, value) in &settings.variables {
+            let value = value.as_ref().map(|value| expand(value, &|var| builder.get(var)));
+            builder.set(name, value, "settings");
+        }
+
+        environment.path_entries = path_entries
+            .iter()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .collect();
+        environment.variables = builder
+            .changes
+            .into_iter()
+            .map(|(name, (value, source))| EnvVariable {
+                inherited: std::env::var(&name).ok(),
+                name,
+                value,
+                source,
+            })
+            .collect();
+        for warning in &environment.warnings {
+            eprintln!("[environment] {}", warning);
+        }
+        environment
+    }
+
+    /// Окружение проекта или пустое, если проект не открыт
+    pub fn for_project(project_root: Option<&str>) -> ProjectEnvironment {
+        project_root.map(Self::load).unwrap_or_default()
+    }
+
+    /// Переменные, которые нужно задать процессу
+    pub fn vars(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.variables
+            .iter()
+            .filter_map(|var| Some((var.name.as_str(), var.value.as_deref()?)))
+    }
+
+    /// Переменные, которые нужно удалить из окружения процесса
+    pub fn removed(&self) -> impl Iterator<Item = &str> {
+        self.variables
+            .iter()
+            .filter(|var| var.value.is_none())
+            .map(|var| var.name.as_str())
+    }
+
+    pub fn apply(&self, command: &mut std::process::Command) {
+        command.envs(self.vars());
+        for name in self.removed() {
+            command.env_remove(name);
+        }
+    }
+}
+
+/// Окружение проекта для просмотра в интерфейсе
+#[tauri::command]
+pub fn get_project_environment(project_root: String) -> ProjectEnvironment {
+    ProjectEnvironment::load(&project_root)
+}
+
+#[tauri::command]
+pub fn get_environment_settings(project_root: String) -> Result<EnvironmentSettings, String> {
+    workspace_settings::load_section(&project_root, SETTINGS_SECTION)
+}
+
+#[tauri::command]
+pub fn save_environment_settings(project_root: String, settings: EnvironmentSettings) -> Result<(), String> {
+    workspace_settings::save_section(&project_root, SETTINGS_SECTION, &settings)
+}
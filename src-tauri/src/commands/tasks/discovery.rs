@@ -0,0 +1,391 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::commands::terminal::runner;
+
+/// Откуда взялась задача
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskSource {
+    Npm,
+    Make,
+    Cargo,
+    Python,
+    User,
+}
+
+/// Задача проекта: программа, которая запускается без оболочки и без PTY
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDefinition {
+    // Уникален в пределах проекта: "npm:build", "make:test", "user:Deploy"
+    pub id: String,
+    pub label: String,
+    pub source: TaskSource,
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: String,
+    pub env: HashMap<String, String>,
+    // Что именно выполнит задача (текст npm-скрипта и т.п.)
+    pub detail: Option<String>,
+    // Какими matcher-ами разбирать вывод; None - всеми включенными
+    pub problem_matchers: Option<Vec<String>>,
+}
+
+impl TaskDefinition {
+    fn new(source: TaskSource, prefix: &str, name: &str, program: &str, args: Vec<String>, cwd: &Path) -> TaskDefinition {
+        TaskDefinition {
+            id: format!("{}:{}", prefix, name),
+            label: name.to_string(),
+            source,
+            program: program.to_string(),
+            args,
+            cwd: cwd.to_string_lossy().to_string(),
+            env: HashMap::new(),
+            detail: None,
+            problem_matchers: None,
+        }
+    }
+}
+
+/// Как выполнять пользовательскую задачу
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UserTaskKind {
+    // command - строка для системной оболочки (sh -c / cmd /C)
+    #[default]
+    Shell,
+    // command - программа, args - ее аргументы
+    Process,
+}
+
+/// Задача из раздела "tasks" настроек рабочей области
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UserTask {
+    pub label: String,
+    #[serde(default)]
+    pub kind: UserTaskKind,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    // Относительно корня проекта
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub problem_matchers: Option<Vec<String>>,
+}
+
+/// Имя исполняемого файла с учетом .cmd-оберток npm, yarn и т.п. в Windows
+fn node_tool(name: &str) -> String {
+    if cfg!(windows) {
+        format!("{}.cmd", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn package_manager(root: &Path) -> &'static str {
+    if root.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else if root.join("yarn.lock").exists() {
+        "yarn"
+    } else if root.join("bun.lockb").exists() || root.join("bun.lock").exists() {
+        "bun"
+    } else {
+        "npm"
+    }
+}
+
+fn npm_tasks(root: &Path) -> Vec<TaskDefinition> {
+    let Ok(content) = fs::read_to_string(root.join("package.json")) else {
+        return Vec::new();
+    };
+    let json: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("Ошибка парсинга package.json: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let manager = package_manager(root);
+    let program = if manager == "bun" {
+        manager.to_string()
+    } else {
+        node_tool(manager)
+    };
+    let mut tasks = Vec::new();
+    if let Some(scripts) = json["scripts"].as_object() {
+        for (name, script) in scripts {
+            let args = vec!["run".to_string(), name.clone()];
+            let mut task = TaskDefinition::new(TaskSource::Npm, "npm", name, &program, args, root);
+            task.detail = script.as_str().map(|s| s.to_string());
+            tasks.push(task);
+        }
+    }
+    tasks
+}
+
+/// Цели Makefile: строки вида `target: deps` без шаблонов и служебных целей
+fn make_tasks(root: &Path) -> Vec<TaskDefinition> {
+    let Some(makefile) = ["GNUmakefile", "makefile", "Makefile"]
+        .iter()
+        .map(|name| root.join(name))
+        .find(|path| path.is_file())
+    else {
+        return Vec::new();
+    };
+    let Ok(content) = fs::read_to_string(&makefile) else {
+        return Vec::new();
+    };
+
+    let mut tasks: Vec<TaskDefinition> = Vec::new();
+    let mut in_define = false;
+    for line in content.lines() {
+        // Рецепты начинаются с табуляции
+        if line.starts_with('\t') || line.starts_with('#') {
+            continue;
+        }
+        // Тело многострочной переменной define ... endef
+        let directive = line.split_whitespace().next().unwrap_or("");
+        if in_define {
+            in_define = directive != "endef";
+            continue;
+        }
+        match directive {
+            "define" => {
+                in_define = true;
+                continue;
+            }
+            "export" | "unexport" | "override" => continue,
+            _ => {}
+        }
+        let Some((targets, rest)) = line.split_once(':') else {
+            continue;
+        };
+        // := и ::= - это присваивания, а не правила; `URL = http://...` и `X ?= a:b` тоже
+        if targets.contains('=') || rest.starts_with('=') || rest.starts_with(":=") {
+            continue;
+        }
+        for target in targets.split_whitespace() {
+            let is_name = target
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'));
+            if !is_name || target.starts_with('.') || tasks.iter().any(|t| t.label == target) {
+                continue;
+            }
+            tasks.push(TaskDefinition::new(
+                TaskSource::Make,
+                "make",
+                target,
+                "make",
+                vec![target.to_string()],
+                root,
+            ));
+        }
+    }
+    tasks
+}
+
+fn cargo_tasks(root: &Path) -> Vec<TaskDefinition> {
+    let Ok(content) = fs::read_to_string(root.join("Cargo.toml")) else {
+        return Vec::new();
+    };
+    let manifest: toml::Value = match toml::from_str(&content) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("Ошибка парсинга Cargo.toml: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let cargo = |name: &str, args: &[&str]| {
+        let args = args.iter().map(|arg| arg.to_string()).collect();
+        TaskDefinition::new(TaskSource::Cargo, "cargo", name, "cargo", args, root)
+    };
+    let mut tasks = vec![
+        cargo("build", &["build"]),
+        cargo("check", &["check"]),
+        cargo("test", &["test"]),
+        cargo("clippy", &["clippy"]),
+    ];
+
+    // Для виртуального манифеста (только [workspace]) запускать нечего
+    let Some(package) = manifest.get("package") else {
+        return tasks;
+    };
+
+    let mut binaries: Vec<String> = Vec::new();
+    if let Some(bins) = manifest.get("bin").and_then(|b| b.as_array()) {
+        binaries.extend(bins.iter().filter_map(|bin| bin.get("name")?.as_str().map(|s| s.to_string())));
+    }
+    if root.join("src").join("main.rs").is_file() {
+        if let Some(name) = package.get("name").and_then(|n| n.as_str()) {
+            binaries.push(name.to_string());
+        }
+    }
+    if let Ok(entries) = fs::read_dir(root.join("src").join("bin")) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = if path.is_dir() && path.join("main.rs").is_file() {
+                path.file_name()
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                path.file_stem()
+            } else {
+                None
+            };
+            if let Some(name) = name {
+                binaries.push(name.to_string_lossy().to_string());
+            }
+        }
+    }
+    binaries.sort();
+    binaries.dedup();
+
+    for bin in binaries {
+        tasks.push(cargo(&format!("run {}", bin), &["run", "--bin", &bin]));
+    }
+    tasks
+}
+
+fn python_tasks(root: &Path) -> Vec<TaskDefinition> {
+    let Ok(content) = fs::read_to_string(root.join("pyproject.toml")) else {
+        return Vec::new();
+    };
+    let pyproject: toml::Value = match toml::from_str(&content) {
+        Ok(pyproject) => pyproject,
+        Err(e) => {
+            eprintln!("Ошибка парсинга pyproject.toml: {}", e);
+            return Vec::new();
+        }
+    };
+    let table = |path: &[&str]| {
+        let mut value = &pyproject;
+        for key in path {
+            value = value.get(key)?;
+        }
+        value.as_table()
+    };
+
+    let mut tasks = Vec::new();
+
+    // Точки входа пакета: module:function
+    if let Some(scripts) = table(&["project", "scripts"]) {
+        let python = runner::find_python(&root.join("pyproject.toml"), Some(root));
+        for (name, entry) in scripts {
+            let Some((module, function)) = entry.as_str().and_then(|e| e.split_once(':')) else {
+                continue;
+            };
+            let code = format!(
+                "import sys; from {} import {}; sys.exit({}())",
+                module.trim(),
+                function.trim(),
+                function.trim()
+            );
+            let mut task = TaskDefinition::new(
+                TaskSource::Python,
+                "python",
+                name,
+                &python,
+                vec!["-c".to_string(), code],
+                root,
+            );
+            task.detail = entry.as_str().map(|s| s.to_string());
+            tasks.push(task);
+        }
+    }
+
+    // Скрипты менеджеров проектов запускаются через сам менеджер
+    for (tool, path) in [("poetry", ["tool", "poetry", "scripts"]), ("pdm", ["tool", "pdm", "scripts"])] {
+        if let Some(scripts) = table(&path) {
+            for name in scripts.keys() {
+                let args = vec!["run".to_string(), name.clone()];
+                tasks.push(TaskDefinition::new(TaskSource::Python, tool, name, tool, args, root));
+            }
+        }
+    }
+    tasks
+}
+
+fn user_task(root: &Path, task: &UserTask) -> TaskDefinition {
+    let (program, args) = match task.kind {
+        UserTaskKind::Process => (task.command.clone(), task.args.clone()),
+        UserTaskKind::Shell => {
+            let mut command = task.command.clone();
+            for arg in &task.args {
+                command.push(' ');
+                command.push_str(arg);
+            }
+            if cfg!(windows) {
+                ("cmd".to_string(), vec!["/C".to_string(), command])
+            } else {
+                ("sh".to_string(), vec!["-c".to_string(), command])
+            }
+        }
+    };
+    let cwd = match &task.cwd {
+        Some(cwd) => root.join(cwd),
+        None => root.to_path_buf(),
+    };
+
+    let mut definition = TaskDefinition::new(TaskSource::User, "user", &task.label, &program, args, &cwd);
+    definition.env = task.env.clone();
+    definition.detail = Some(task.command.clone());
+    definition.problem_matchers = task.problem_matchers.clone();
+    definition
+}
+
+/// Все задачи проекта: пользовательские идут первыми
+pub fn discover(root: &Path, user_tasks: &[UserTask]) -> Vec<TaskDefinition> {
+    let mut tasks: Vec<TaskDefinition> = user_tasks.iter().map(|task| user_task(root, task)).collect();
+    tasks.extend(npm_tasks(root));
+    tasks.extend(make_tasks(root));
+    tasks.extend(cargo_tasks(root));
+    tasks.extend(python_tasks(root));
+    tasks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    // Makefile во временной папке, отдельной для каждого теста
+    fn make_labels(name: &str, makefile: &str) -> Vec<String> {
+        let root: PathBuf = std::env::temp_dir().join(format!("make-tasks-{}-{}", std::process::id(), name));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("Makefile"), makefile).unwrap();
+        let labels = make_tasks(&root).into_iter().map(|task| task.label).collect();
+        fs::remove_dir_all(&root).unwrap();
+        labels
+    }
+
+    #[test]
+    fn lists_rule_targets_once() {
+        let labels = make_labels("rules", "all: build test\n\tdone\nbuild test: deps\n\tcc\nbuild:\n");
+        assert_eq!(labels, ["all", "build", "test"]);
+    }
+
+    #[test]
+    fn skips_assignments_with_colons() {
+        let makefile = "X := a:b\nY ::= c\nURL = http://example.com\nZ ?= a:b\nall:\n";
+        assert_eq!(make_labels("assignments", makefile), ["all"]);
+    }
+
+    #[test]
+    fn skips_define_bodies() {
+        let makefile = "define RECIPE\nfoo: bar\n\techo\nendef\nall:\n";
+        assert_eq!(make_labels("define", makefile), ["all"]);
+    }
+
+    #[test]
+    fn skips_special_and_pattern_targets() {
+        let makefile = ".PHONY: all clean\n%.o: %.c\n\tcc\nall:\nclean:\n";
+        assert_eq!(make_labels("special", makefile), ["all", "clean"]);
+    }
+}
@@ -0,0 +1,399 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io::Read,
+    path::Path,
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tauri::{async_runtime::Mutex, AppHandle, Emitter, State};
+
+use crate::commands::problem_matchers::{self, ProblemCollector};
+use crate::commands::project_env::ProjectEnvironment;
+use crate::commands::terminal::output::Utf8StreamDecoder;
+use crate::commands::terminal::process;
+#[cfg(unix)]
+use crate::commands::terminal::process::TerminalSignal;
+use crate::commands::workspace_settings;
+
+pub mod discovery;
+
+use discovery::{TaskDefinition, UserTask};
+
+// Раздел .xeditor/settings.json с пользовательскими задачами
+const SETTINGS_SECTION: &str = "tasks";
+// Сколько завершенных запусков хранить для панели задач
+const FINISHED_RUNS_LIMIT: usize = 50;
+// Сколько ждать завершения после SIGTERM, прежде чем убить задачу
+const CANCEL_GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksSettings {
+    #[serde(default)]
+    pub tasks: Vec<UserTask>,
+}
+
+/// Состояние запуска задачи
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "state", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum TaskStatus {
+    Running,
+    Finished {
+        exit_code: Option<i32>,
+        success: bool,
+        cancelled: bool,
+        duration_ms: u64,
+    },
+}
+
+/// Один запуск задачи
+pub struct TaskRun {
+    pub id: String,
+    pub task: TaskDefinition,
+    pub started_at: i64,
+    pub pid: u32,
+    status: std::sync::Mutex<TaskStatus>,
+    cancelled: AtomicBool,
+    #[cfg(windows)]
+    job: Option<process::JobObject>,
+}
+
+impl TaskRun {
+    fn status(&self) -> TaskStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    fn is_running(&self) -> bool {
+        matches!(self.status(), TaskStatus::Running)
+    }
+
+    fn info(&self) -> TaskRunInfo {
+        TaskRunInfo {
+            run_id: self.id.clone(),
+            task_id: self.task.id.clone(),
+            label: self.task.label.clone(),
+            started_at: self.started_at,
+            pid: self.pid,
+            status: self.status(),
+        }
+    }
+
+    /// Завершает задачу вместе с порожденными ею процессами
+    fn cancel(self: &Arc<Self>) {
+        self.cancelled.store(true, Ordering::Release);
+
+        // Задача запущена в собственной группе процессов: npm, make и cargo
+        // порождают дочерние процессы, и сигнал должен дойти до всех
+        #[cfg(unix)]
+        {
+            let _ = process::signal_group(self.pid, TerminalSignal::Terminate);
+            let run = self.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(CANCEL_GRACE_PERIOD);
+                if run.is_running() {
+                    let _ = process::signal_group(run.pid, TerminalSignal::Kill);
+                }
+            });
+        }
+        #[cfg(windows)]
+        match &self.job {
+            Some(job) => job.terminate(),
+            None => {
+                let _ = process::terminate_pid(self.pid);
+            }
+        }
+    }
+}
+
+/// Информация о запуске для панели задач
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRunInfo {
+    pub run_id: String,
+    pub task_id: String,
+    pub label: String,
+    pub started_at: i64,
+    pub pid: u32,
+    pub status: TaskStatus,
+}
+
+/// Payload события `task-output`
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskOutput {
+    pub run_id: String,
+    // "stdout" или "stderr"
+    pub stream: &'static str,
+    pub data: String,
+}
+
+/// Payload события `task-finished`
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskFinished {
+    pub run_id: String,
+    pub task_id: String,
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub cancelled: bool,
+    pub duration_ms: u64,
+}
+
+/// Запуски задач, ключ - идентификатор запуска
+pub struct TaskState {
+    runs: Arc<Mutex<HashMap<String, Arc<TaskRun>>>>,
+    next_id: AtomicU64,
+}
+
+impl Default for TaskState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskState {
+    pub fn new() -> Self {
+        TaskState {
+            runs: Arc::new(Mutex::new(HashMap::new())),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    async fn get(&self, run_id: &str) -> Result<Arc<TaskRun>, String> {
+        self.runs
+            .lock()
+            .await
+            .get(run_id)
+            .cloned()
+            .ok_or_else(|| format!("Запуск задачи '{}' не найден", run_id))
+    }
+}
+
+fn load_settings(project_root: &str) -> TasksSettings {
+    workspace_settings::load_section(project_root, SETTINGS_SECTION).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        TasksSettings::default()
+    })
+}
+
+/// Читает поток процесса и пересылает его событиями `task-output`,
+/// найденные в нем проблемы - событиями `problems-updated`
+fn forward_output<R: Read + Send + 'static>(
+    app: AppHandle,
+    run_id: String,
+    stream: &'static str,
+    mut reader: R,
+    mut problems: ProblemCollector,
+    problems_owner: String,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut emit = |data: String| {
+            let diagnostics = problems.feed(&data);
+            problem_matchers::publish(&app, &problems_owner, diagnostics, false);
+            if !data.is_empty() {
+                let _ = app.emit(
+                    "task-output",
+                    TaskOutput {
+                        run_id: run_id.clone(),
+                        stream,
+                        data,
+                    },
+                );
+            }
+        };
+
+        let mut decoder = Utf8StreamDecoder::default();
+        let mut buffer = [0u8; 8192];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(n) if n > 0 => emit(decoder.decode(&buffer[..n])),
+                _ => {
+                    emit(decoder.finish());
+                    break;
+                }
+            }
+        }
+        let diagnostics = problems.finish();
+        problem_matchers::publish(&app, &problems_owner, diagnostics, false);
+    })
+}
+
+/// Убирает из списка самые старые завершенные запуски
+fn prune_finished(runs: &mut HashMap<String, Arc<TaskRun>>) {
+    let mut finished: Vec<(i64, String)> = runs
+        .values()
+        .filter(|run| !run.is_running())
+        .map(|run| (run.started_at, run.id.clone()))
+        .collect();
+    if finished.len() <= FINISHED_RUNS_LIMIT {
+        return;
+    }
+    finished.sort();
+    for (_, id) in finished.iter().take(finished.len() - FINISHED_RUNS_LIMIT) {
+        runs.remove(id);
+    }
+}
+
+async fn start_task(
+    state: &TaskState,
+    app: AppHandle,
+    project_root: &str,
+    task: TaskDefinition,
+) -> Result<TaskRunInfo, String> {
+    let mut command = Command::new(&task.program);
+    ProjectEnvironment::load(project_root).apply(&mut command);
+    command
+        .args(&task.args)
+        .current_dir(&task.cwd)
+        .envs(&task.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW: без этого флага у консольной программы появится окно
+        command.creation_flags(0x0800_0000);
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Не удалось запустить задачу '{}': {}", task.label, e))?;
+    let pid = child.id();
+    let started = Instant::now();
+
+    let run = Arc::new(TaskRun {
+        id: format!("task-{}", state.next_id.fetch_add(1, Ordering::Relaxed)),
+        task,
+        started_at: chrono::Local::now().timestamp_millis(),
+        pid,
+        status: std::sync::Mutex::new(TaskStatus::Running),
+        cancelled: AtomicBool::new(false),
+        #[cfg(windows)]
+        job: process::JobObject::for_process(pid).ok(),
+    });
+    let info = run.info();
+    {
+        let mut runs = state.runs.lock().await;
+        runs.insert(run.id.clone(), run.clone());
+        prune_finished(&mut runs);
+    }
+
+    // Проблемы привязаны к задаче, а не к запуску: повторный запуск заменяет их
+    let problems_owner = format!("task:{}", run.task.id);
+    problem_matchers::publish(&app, &problems_owner, Vec::new(), true);
+    let matcher_settings = problem_matchers::load_settings(Some(project_root));
+    // У stdout и stderr свои коллекторы, чтобы не склеивать строки из разных потоков
+    let collector = || {
+        ProblemCollector::new(
+            &matcher_settings,
+            Path::new(&run.task.cwd),
+            run.task.problem_matchers.as_deref(),
+        )
+    };
+    let readers = [
+        child.stdout.take().map(|stdout| {
+            forward_output(app.clone(), run.id.clone(), "stdout", stdout, collector(), problems_owner.clone())
+        }),
+        child.stderr.take().map(|stderr| {
+            forward_output(app.clone(), run.id.clone(), "stderr", stderr, collector(), problems_owner.clone())
+        }),
+    ];
+
+    std::thread::spawn(move || {
+        let exit_code = match child.wait() {
+            Ok(status) => status.code(),
+            Err(e) => {
+                eprintln!("[{}] Failed to wait for task: {}", run.id, e);
+                None
+            }
+        };
+        // task-finished должен прийти после всего вывода
+        for reader in readers.into_iter().flatten() {
+            let _ = reader.join();
+        }
+
+        let cancelled = run.cancelled.load(Ordering::Acquire);
+        let duration_ms = started.elapsed().as_millis() as u64;
+        let success = exit_code == Some(0) && !cancelled;
+        *run.status.lock().unwrap() = TaskStatus::Finished {
+            exit_code,
+            success,
+            cancelled,
+            duration_ms,
+        };
+        let _ = app.emit(
+            "task-finished",
+            TaskFinished {
+                run_id: run.id.clone(),
+                task_id: run.task.id.clone(),
+                exit_code,
+                success,
+                cancelled,
+                duration_ms,
+            },
+        );
+    });
+
+    Ok(info)
+}
+
+/// Задачи проекта: пользовательские, npm-скрипты, цели Makefile, Cargo и pyproject
+#[tauri::command]
+pub fn list_tasks(project_root: String) -> Vec<TaskDefinition> {
+    let settings = load_settings(&project_root);
+    discovery::discover(Path::new(&project_root), &settings.tasks)
+}
+
+/// Запускает задачу; вывод приходит событиями `task-output`, результат - `task-finished`
+#[tauri::command]
+pub async fn run_task(
+    state: State<'_, TaskState>,
+    app: AppHandle,
+    project_root: String,
+    task_id: String,
+) -> Result<TaskRunInfo, String> {
+    let settings = load_settings(&project_root);
+    let task = discovery::discover(Path::new(&project_root), &settings.tasks)
+        .into_iter()
+        .find(|task| task.id == task_id)
+        .ok_or_else(|| format!("Задача '{}' не найдена", task_id))?;
+    start_task(&state, app, &project_root, task).await
+}
+
+#[tauri::command]
+pub async fn cancel_task(state: State<'_, TaskState>, run_id: String) -> Result<(), String> {
+    let run = state.get(&run_id).await?;
+    if !run.is_running() {
+        return Err(format!("Задача '{}' уже завершена", run.task.label));
+    }
+    run.cancel();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_task_runs(state: State<'_, TaskState>) -> Result<Vec<TaskRunInfo>, String> {
+    let mut runs: Vec<TaskRunInfo> = state.runs.lock().await.values().map(|run| run.info()).collect();
+    runs.sort_by_key(|info| info.started_at);
+    Ok(runs)
+}
+
+#[tauri::command]
+pub fn get_user_tasks(project_root: String) -> Result<Vec<UserTask>, String> {
+    workspace_settings::load_section::<TasksSettings>(&project_root, SETTINGS_SECTION).map(|s| s.tasks)
+}
+
+#[tauri::command]
+pub fn save_user_tasks(project_root: String, tasks: Vec<UserTask>) -> Result<(), String> {
+    workspace_settings::save_section(&project_root, SETTINGS_SECTION, &TasksSettings { tasks })
+}
@@ -0,0 +1,1042 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use vte::{Params, Parser, Perform};
+
+use super::integration::{CommandRecord, ShellEvent, ShellTracker};
+
+// Совпадает с настройкой scrollback в xterm.js на фронтенде
+pub const SCROLLBACK_LINES: usize = 5000;
+
+const BOLD: u8 = 1;
+const DIM: u8 = 1 << 1;
+const ITALIC: u8 = 1 << 2;
+const UNDERLINE: u8 = 1 << 3;
+const BLINK: u8 = 1 << 4;
+const INVERSE: u8 = 1 << 5;
+const HIDDEN: u8 = 1 << 6;
+const STRIKE: u8 = 1 << 7;
+
+// Цвета по умолчанию и первые 16 цветов палитры для HTML-расшифровки
+const DEFAULT_FG: (u8, u8, u8) = (0xd4, 0xd4, 0xd4);
+const DEFAULT_BG: (u8, u8, u8) = (0x1e, 0x1e, 0x1e);
+const ANSI_PALETTE: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x31, 0x31),
+    (0x0d, 0xbc, 0x79),
+    (0xe5, 0xe5, 0x10),
+    (0x24, 0x72, 0xc8),
+    (0xbc, 0x3f, 0xbc),
+    (0x11, 0xa8, 0xcd),
+    (0xe5, 0xe5, 0xe5),
+    (0x66, 0x66, 0x66),
+    (0xf1, 0x4c, 0x4c),
+    (0x23, 0xd1, 0x8b),
+    (0xf5, 0xf5, 0x43),
+    (0x3b, 0x8e, 0xea),
+    (0xd6, 0x70, 0xd6),
+    (0x29, 0xb8, 0xdb),
+    (0xff, 0xff, 0xff),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+enum Color {
+    #[default]
+    Default,
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    fn rgb(self, default: (u8, u8, u8)) -> (u8, u8, u8) {
+        match self {
+            Color::Default => default,
+            Color::Indexed(n) if n < 16 => ANSI_PALETTE[n as usize],
+            // Куб 6x6x6
+            Color::Indexed(n) if n < 232 => {
+                let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+                let n = n - 16;
+                (level(n / 36), level(n / 6 % 6), level(n % 6))
+            }
+            // Оттенки серого
+            Color::Indexed(n) => {
+                let v = 8 + (n - 232) * 10;
+                (v, v, v)
+            }
+            Color::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+struct Style {
+    fg: Color,
+    bg: Color,
+    flags: u8,
+}
+
+impl Style {
+    /// SGR-последовательность, полностью задающая этот стиль
+    fn to_sgr(self) -> String {
+        let mut codes = vec!["0".to_string()];
+        let flags = [
+            (BOLD, "1"),
+            (DIM, "2"),
+            (ITALIC, "3"),
+            (UNDERLINE, "4"),
+            (BLINK, "5"),
+            (INVERSE, "7"),
+            (HIDDEN, "8"),
+            (STRIKE, "9"),
+        ];
+        for (flag, code) in flags {
+            if self.flags & flag != 0 {
+                codes.push(code.to_string());
+            }
+        }
+        for (color, base) in [(self.fg, 30u16), (self.bg, 40u16)] {
+            match color {
+                Color::Default => {}
+                Color::Indexed(n) if n < 8 => codes.push((base + n as u16).to_string()),
+                Color::Indexed(n) if n < 16 => codes.push((base + 60 + n as u16 - 8).to_string()),
+                Color::Indexed(n) => codes.push(format!("{};5;{}", base + 8, n)),
+                Color::Rgb(r, g, b) => codes.push(format!("{};2;{};{};{}", base + 8, r, g, b)),
+            }
+        }
+        format!("\x1b[{}m", codes.join(";"))
+    }
+
+    /// CSS для HTML-расшифровки; пустая строка - стиль по умолчанию
+    fn to_css(self) -> String {
+        let (mut fg, mut bg) = (self.fg, self.bg);
+        if self.flags & INVERSE != 0 {
+            (fg, bg) = (bg, fg);
+        }
+        let mut css = Vec::new();
+        if fg != Color::Default || self.flags & INVERSE != 0 {
+            let (r, g, b) = fg.rgb(if self.flags & INVERSE != 0 { DEFAULT_BG } else { DEFAULT_FG });
+            css.push(format!("color:#{:02x}{:02x}{:02x}", r, g, b));
+        }
+        if bg != Color::Default || self.flags & INVERSE != 0 {
+            let (r, g, b) = bg.rgb(if self.flags & INVERSE != 0 { DEFAULT_FG } else { DEFAULT_BG });
+            css.push(format!("background:#{:02x}{:02x}{:02x}", r, g, b));
+        }
+        if self.flags & BOLD != 0 {
+            css.push("font-weight:bold".to_string());
+        }
+        if self.flags & DIM != 0 {
+            css.push("opacity:0.6".to_string());
+        }
+        if self.flags & ITALIC != 0 {
+            css.push("font-style:italic".to_string());
+        }
+        match (self.flags & UNDERLINE != 0, self.flags & STRIKE != 0) {
+            (true, true) => css.push("text-decoration:underline line-through".to_string()),
+            (true, false) => css.push("text-decoration:underline".to_string()),
+            (false, true) => css.push("text-decoration:line-through".to_string()),
+            (false, false) => {}
+        }
+        if self.flags & HIDDEN != 0 {
+            css.push("visibility:hidden".to_string());
+        }
+        css.join(";")
+    }
+}
+
+fn escape_html(ch: char, out: &mut String) {
+    match ch {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        _ => out.push(ch),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Cell {
+    ch: char,
+    style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            ch: ' ',
+            style: Style::default(),
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+struct Row {
+    cells: Vec<Cell>,
+    // Строка продолжается на следующей (автоперенос, а не перевод строки)
+    wrapped: bool,
+}
+
+impl Row {
+    fn blank(cols: usize) -> Row {
+        Row {
+            cells: vec![Cell::default(); cols],
+            wrapped: false,
+        }
+    }
+
+    fn text(&self) -> String {
+        let text: String = self.cells.iter().map(|c| c.ch).collect();
+        if self.wrapped {
+            text
+        } else {
+            text.trim_end().to_string()
+        }
+    }
+
+    /// Для scrollback храним строку без хвостовых пустых ячеек
+    fn trimmed(mut self) -> Row {
+        if !self.wrapped {
+            while self.cells.last() == Some(&Cell::default()) {
+                self.cells.pop();
+            }
+        }
+        self
+    }
+
+    fn resize(&mut self, cols: usize) {
+        self.cells.resize(cols, Cell::default());
+    }
+
+    /// Ячейки без хвостовых пустых (у перенесенной строки хвост значим)
+    fn visible_cells(&self) -> &[Cell] {
+        let mut cells = self.cells.as_slice();
+        if !self.wrapped {
+            while let Some((last, rest)) = cells.split_last() {
+                if *last != Cell::default() {
+                    break;
+                }
+                cells = rest;
+            }
+        }
+        cells
+    }
+
+    fn write_html(&self, out: &mut String) {
+        let cells = self.visible_cells();
+        let mut index = 0;
+        while index < cells.len() {
+            let style = cells[index].style;
+            let end = cells[index..]
+                .iter()
+                .position(|cell| cell.style != style)
+                .map_or(cells.len(), |offset| index + offset);
+            let css = style.to_css();
+            if !css.is_empty() {
+                out.push_str(&format!("<span style=\"{}\">", css));
+            }
+            for cell in &cells[index..end] {
+                escape_html(cell.ch, out);
+            }
+            if !css.is_empty() {
+                out.push_str("</span>");
+            }
+            index = end;
+        }
+    }
+
+    fn write_ansi(&self, out: &mut String, current: &mut Style) {
+        for cell in self.visible_cells() {
+            if cell.style != *current {
+                out.push_str(&cell.style.to_sgr());
+                *current = cell.style;
+            }
+            out.push(cell.ch);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct SavedCursor {
+    row: usize,
+    col: usize,
+    style: Style,
+}
+
+/// Снимок состояния терминала для восстановления фронтенда
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalSnapshot {
+    pub rows: usize,
+    pub cols: usize,
+    pub cursor_row: usize,
+    pub cursor_col: usize,
+    pub cursor_visible: bool,
+    pub alternate_screen: bool,
+    pub title: String,
+    // Абсолютный номер первой строки lines (растет, когда старые строки вытесняются)
+    pub first_line: u64,
+    // Количество строк scrollback в начале lines
+    pub scrollback_lines: usize,
+    // Текст: scrollback, затем основной экран
+    pub lines: Vec<String>,
+    // Тот же контент с цветами и позицией курсора - достаточно записать в пустой xterm
+    pub ansi: String,
+}
+
+/// Найденное в выводе терминала вхождение; line - индекс в TerminalSnapshot::lines
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrollbackMatch {
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+    pub text: String,
+}
+
+struct Screen {
+    rows: usize,
+    cols: usize,
+    grid: Vec<Row>,
+    // Основной экран, пока активен альтернативный (vim, less, htop)
+    primary: Option<Vec<Row>>,
+    scrollback: VecDeque<Row>,
+    scrollback_limit: usize,
+    cursor_row: usize,
+    cursor_col: usize,
+    wrap_pending: bool,
+    style: Style,
+    saved: SavedCursor,
+    saved_primary: SavedCursor,
+    scroll_top: usize,
+    scroll_bottom: usize,
+    cursor_visible: bool,
+    autowrap: bool,
+    title: String,
+    // Сколько строк вытеснено из scrollback за все время
+    lines_dropped: u64,
+    shell: ShellTracker,
+}
+
+impl Screen {
+    fn new(rows: usize, cols: usize, scrollback_limit: usize) -> Screen {
+        let rows = rows.max(1);
+        let cols = cols.max(1);
+        Screen {
+            rows,
+            cols,
+            grid: vec![Row::blank(cols); rows],
+            primary: None,
+            scrollback: VecDeque::new(),
+            scrollback_limit,
+            cursor_row: 0,
+            cursor_col: 0,
+            wrap_pending: false,
+            style: Style::default(),
+            saved: SavedCursor::default(),
+            saved_primary: SavedCursor::default(),
+            scroll_top: 0,
+            scroll_bottom: rows - 1,
+            cursor_visible: true,
+            autowrap: true,
+            title: String::new(),
+            lines_dropped: 0,
+            shell: ShellTracker::default(),
+        }
+    }
+
+    fn blank_row(&self) -> Row {
+        // Очистка заполняет строку текущим цветом фона, как в xterm
+        Row {
+            cells: vec![self.blank_cell(); self.cols],
+            wrapped: false,
+        }
+    }
+
+    fn blank_cell(&self) -> Cell {
+        Cell {
+            ch: ' ',
+            style: Style {
+                bg: self.style.bg,
+                ..Style::default()
+            },
+        }
+    }
+
+    fn push_scrollback(&mut self, row: Row) {
+        self.scrollback.push_back(row.trimmed());
+        while self.scrollback.len() > self.scrollback_limit {
+            self.scrollback.pop_front();
+            self.lines_dropped += 1;
+        }
+    }
+
+    /// Абсолютный номер строки курсора на основном экране
+    fn cursor_line(&self) -> u64 {
+        let row = if self.primary.is_some() {
+            self.saved_primary.row
+        } else {
+            self.cursor_row
+        };
+        self.lines_dropped + self.scrollback.len() as u64 + row as u64
+    }
+
+    fn row_at(&self, line: u64) -> Option<&Row> {
+        let index = usize::try_from(line.checked_sub(self.lines_dropped)?).ok()?;
+        let main_screen = self.primary.as_ref().unwrap_or(&self.grid);
+        if index < self.scrollback.len() {
+            self.scrollback.get(index)
+        } else {
+            main_screen.get(index - self.scrollback.len())
+        }
+    }
+
+    /// Текст между двумя позициями; перенесенные строки склеиваются
+    fn text_between(&self, start: (u64, usize), end: (u64, usize)) -> String {
+        let mut text = String::new();
+        for line in start.0..=end.0 {
+            let Some(row) = self.row_at(line) else {
+                continue;
+            };
+            let from = if line == start.0 { start.1 } else { 0 };
+            let to = if line == end.0 { end.1 } else { row.cells.len() };
+            let part: String = row.cells.iter().take(to).skip(from).map(|c| c.ch).collect();
+            if row.wrapped && line != end.0 {
+                text.push_str(&part);
+            } else {
+                text.push_str(part.trim_end());
+                if line != end.0 {
+                    text.push('\n');
+                }
+            }
+        }
+        text.trim().to_string()
+    }
+
+    fn shell_integration(&mut self, marker: &[u8], args: &[&[u8]]) {
+        // Маркер в середине строки относится к следующей строке вывода
+        let line = self.cursor_line();
+        let next_line = if self.cursor_col > 0 { line + 1 } else { line };
+        match marker {
+            b"B" => self.shell.prompt_end((line, self.cursor_col)),
+            b"C" => {
+                let command = self
+                    .shell
+                    .input_start()
+                    .map(|start| self.text_between(start, (line, self.cursor_col)))
+                    .unwrap_or_default();
+                self.shell.command_start(command, next_line);
+            }
+            b"D" => {
+                let exit_code = args
+                    .first()
+                    .and_then(|code| std::str::from_utf8(code).ok())
+                    .and_then(|code| code.trim().parse().ok());
+                self.shell.command_finish(exit_code, next_line);
+            }
+            _ => {}
+        }
+    }
+
+    fn scroll_up(&mut self, count: usize) {
+        for _ in 0..count.min(self.scroll_bottom + 1 - self.scroll_top) {
+            let row = self.grid.remove(self.scroll_top);
+            if self.scroll_top == 0 && self.primary.is_none() {
+                self.push_scrollback(row);
+            }
+            let blank = self.blank_row();
+            self.grid.insert(self.scroll_bottom, blank);
+        }
+    }
+
+    fn scroll_down(&mut self, count: usize) {
+        for _ in 0..count.min(self.scroll_bottom + 1 - self.scroll_top) {
+            self.grid.remove(self.scroll_bottom);
+            let blank = self.blank_row();
+            self.grid.insert(self.scroll_top, blank);
+        }
+    }
+
+    fn linefeed(&mut self) {
+        if self.cursor_row == self.scroll_bottom {
+            self.scroll_up(1);
+        } else if self.cursor_row + 1 < self.rows {
+            self.cursor_row += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        if self.cursor_row == self.scroll_top {
+            self.scroll_down(1);
+        } else if self.cursor_row > 0 {
+            self.cursor_row -= 1;
+        }
+    }
+
+    fn move_to(&mut self, row: usize, col: usize) {
+        self.cursor_row = row.min(self.rows - 1);
+        self.cursor_col = col.min(self.cols - 1);
+        self.wrap_pending = false;
+    }
+
+    fn erase_cells(&mut self, row: usize, from: usize, to: usize) {
+        let blank = self.blank_cell();
+        let cells = &mut self.grid[row].cells;
+        let to = to.min(cells.len());
+        for cell in cells.iter_mut().take(to).skip(from) {
+            *cell = blank;
+        }
+    }
+
+    fn erase_rows(&mut self, from: usize, to: usize) {
+        for row in from..to.min(self.rows) {
+            self.grid[row] = self.blank_row();
+        }
+    }
+
+    fn save_cursor(&self) -> SavedCursor {
+        SavedCursor {
+            row: self.cursor_row,
+            col: self.cursor_col,
+            style: self.style,
+        }
+    }
+
+    fn restore_cursor(&mut self, saved: SavedCursor) {
+        self.move_to(saved.row, saved.col);
+        self.style = saved.style;
+    }
+
+    fn enter_alternate(&mut self, clear: bool) {
+        if self.primary.is_some() {
+            return;
+        }
+        self.saved_primary = self.save_cursor();
+        let alternate = vec![Row::blank(self.cols); self.rows];
+        self.primary = Some(std::mem::replace(&mut self.grid, alternate));
+        if clear {
+            self.move_to(0, 0);
+        }
+    }
+
+    fn exit_alternate(&mut self) {
+        if let Some(primary) = self.primary.take() {
+            self.grid = primary;
+            let saved = self.saved_primary;
+            self.restore_cursor(saved);
+        }
+    }
+
+    fn reset(&mut self) {
+        let mut fresh = Screen::new(self.rows, self.cols, self.scrollback_limit);
+        // Сброс экрана не затрагивает историю и состояние оболочки
+        std::mem::swap(&mut fresh.scrollback, &mut self.scrollback);
+        std::mem::swap(&mut fresh.title, &mut self.title);
+        std::mem::swap(&mut fresh.shell, &mut self.shell);
+        fresh.lines_dropped = self.lines_dropped;
+        *self = fresh;
+    }
+
+    fn resize(&mut self, rows: usize, cols: usize) {
+        let rows = rows.max(1);
+        let cols = cols.max(1);
+
+        if cols != self.cols {
+            for row in self.grid.iter_mut().chain(self.primary.iter_mut().flatten()) {
+                row.resize(cols);
+            }
+        }
+
+        if rows < self.rows {
+            // Сначала уводим в scrollback строки над курсором, затем отрезаем пустой низ
+            let overflow = (self.cursor_row + 1).saturating_sub(rows);
+            for _ in 0..overflow {
+                let row = self.grid.remove(0);
+                if self.primary.is_none() {
+                    self.push_scrollback(row);
+                }
+            }
+            self.grid.truncate(rows);
+            self.cursor_row -= overflow;
+            if let Some(primary) = self.primary.as_mut() {
+                let overflow = primary.len().saturating_sub(rows);
+                primary.drain(..overflow);
+            }
+        } else if rows > self.rows {
+            self.grid.resize(rows, Row::blank(cols));
+            if let Some(primary) = self.primary.as_mut() {
+                primary.resize(rows, Row::blank(cols));
+            }
+        }
+
+        self.rows = rows;
+        self.cols = cols;
+        self.scroll_top = 0;
+        self.scroll_bottom = rows - 1;
+        self.move_to(self.cursor_row, self.cursor_col);
+    }
+
+    fn set_private_mode(&mut self, mode: u16, enabled: bool) {
+        match mode {
+            7 => self.autowrap = enabled,
+            25 => self.cursor_visible = enabled,
+            47 | 1047 => {
+                if enabled {
+                    self.enter_alternate(false);
+                } else {
+                    self.exit_alternate();
+                }
+            }
+            1049 => {
+                if enabled {
+                    self.enter_alternate(true);
+                } else {
+                    self.exit_alternate();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, params: &Params) {
+        let params: Vec<&[u16]> = params.iter().collect();
+        if params.is_empty() {
+            self.style = Style::default();
+            return;
+        }
+
+        let mut i = 0;
+        while i < params.len() {
+            let param = params[i];
+            match param[0] {
+                0 => self.style = Style::default(),
+                1 => self.style.flags |= BOLD,
+                2 => self.style.flags |= DIM,
+                3 => self.style.flags |= ITALIC,
+                4 => self.style.flags |= UNDERLINE,
+                5 | 6 => self.style.flags |= BLINK,
+                7 => self.style.flags |= INVERSE,
+                8 => self.style.flags |= HIDDEN,
+                9 => self.style.flags |= STRIKE,
+                21 | 22 => self.style.flags &= !(BOLD | DIM),
+                23 => self.style.flags &= !ITALIC,
+                24 => self.style.flags &= !UNDERLINE,
+                25 => self.style.flags &= !BLINK,
+                27 => self.style.flags &= !INVERSE,
+                28 => self.style.flags &= !HIDDEN,
+                29 => self.style.flags &= !STRIKE,
+                n @ 30..=37 => self.style.fg = Color::Indexed((n - 30) as u8),
+                39 => self.style.fg = Color::Default,
+                n @ 40..=47 => self.style.bg = Color::Indexed((n - 40) as u8),
+                49 => self.style.bg = Color::Default,
+                n @ 90..=97 => self.style.fg = Color::Indexed((n - 90 + 8) as u8),
+                n @ 100..=107 => self.style.bg = Color::Indexed((n - 100 + 8) as u8),
+                n @ (38 | 48) => {
+                    let (color, consumed) = parse_extended_color(&params[i..]);
+                    if let Some(color) = color {
+                        if n == 38 {
+                            self.style.fg = color;
+                        } else {
+                            self.style.bg = color;
+                        }
+                    }
+                    i += consumed;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+/// Разбирает 38/48 в обеих формах: 38;5;n / 38;2;r;g;b и 38:5:n / 38:2::r:g:b.
+/// Возвращает цвет и количество дополнительно прочитанных параметров.
+fn parse_extended_color(params: &[&[u16]]) -> (Option<Color>, usize) {
+    let first = params[0];
+    if first.len() > 1 {
+        let color = match first[1] {
+            5 if first.len() > 2 => Some(Color::Indexed(first[2] as u8)),
+            2 if first.len() >= 6 => Some(Color::Rgb(first[3] as u8, first[4] as u8, first[5] as u8)),
+            2 if first.len() == 5 => Some(Color::Rgb(first[2] as u8, first[3] as u8, first[4] as u8)),
+            _ => None,
+        };
+        return (color, 0);
+    }
+
+    let value = |index: usize| params.get(index).map(|p| p[0]);
+    match value(1) {
+        Some(5) => (value(2).map(|n| Color::Indexed(n as u8)), 2),
+        Some(2) => match (value(2), value(3), value(4)) {
+            (Some(r), Some(g), Some(b)) => (Some(Color::Rgb(r as u8, g as u8, b as u8)), 4),
+            _ => (None, params.len() - 1),
+        },
+        _ => (None, 0),
+    }
+}
+
+impl Perform for Screen {
+    fn print(&mut self, c: char) {
+        if self.wrap_pending {
+            if self.autowrap {
+                self.grid[self.cursor_row].wrapped = true;
+                self.cursor_col = 0;
+                self.linefeed();
+            }
+            self.wrap_pending = false;
+        }
+
+        self.grid[self.cursor_row].cells[self.cursor_col] = Cell { ch: c, style: self.style };
+        if self.cursor_col + 1 >= self.cols {
+            self.wrap_pending = true;
+        } else {
+            self.cursor_col += 1;
+        }
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            0x08 => {
+                self.cursor_col = self.cursor_col.saturating_sub(1);
+                self.wrap_pending = false;
+            }
+            0x09 => {
+                let next_stop = (self.cursor_col / 8 + 1) * 8;
+                self.cursor_col = next_stop.min(self.cols - 1);
+            }
+            0x0A..=0x0C => {
+                self.linefeed();
+                self.wrap_pending = false;
+            }
+            0x0D => {
+                self.cursor_col = 0;
+                self.wrap_pending = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], _ignore: bool, action: char) {
+        let values: Vec<u16> = params.iter().map(|p| p[0]).collect();
+        let arg = |index: usize, default: usize| match values.get(index) {
+            Some(&v) if v > 0 => v as usize,
+            _ => default,
+        };
+        let private = intermediates.first() == Some(&b'?');
+
+        match action {
+            'A' => self.move_to(self.cursor_row.saturating_sub(arg(0, 1)), self.cursor_col),
+            'B' | 'e' => self.move_to(self.cursor_row + arg(0, 1), self.cursor_col),
+            'C' | 'a' => self.move_to(self.cursor_row, self.cursor_col + arg(0, 1)),
+            'D' => self.move_to(self.cursor_row, self.cursor_col.saturating_sub(arg(0, 1))),
+            'E' => self.move_to(self.cursor_row + arg(0, 1), 0),
+            'F' => self.move_to(self.cursor_row.saturating_sub(arg(0, 1)), 0),
+            'G' | '`' => self.move_to(self.cursor_row, arg(0, 1) - 1),
+            'H' | 'f' => self.move_to(arg(0, 1) - 1, arg(1, 1) - 1),
+            'd' => self.move_to(arg(0, 1) - 1, self.cursor_col),
+            'J' => match values.first().copied().unwrap_or(0) {
+                0 => {
+                    self.erase_cells(self.cursor_row, self.cursor_col, self.cols);
+                    self.erase_rows(self.cursor_row + 1, self.rows);
+                }
+                1 => {
+                    self.erase_rows(0, self.cursor_row);
+                    self.erase_cells(self.cursor_row, 0, self.cursor_col + 1);
+                }
+                2 => self.erase_rows(0, self.rows),
+                3 => {
+                    self.lines_dropped += self.scrollback.len() as u64;
+                    self.scrollback.clear();
+                }
+                _ => {}
+            },
+            'K' => match values.first().copied().unwrap_or(0) {
+                0 => self.erase_cells(self.cursor_row, self.cursor_col, self.cols),
+                1 => self.erase_cells(self.cursor_row, 0, self.cursor_col + 1),
+                2 => self.erase_cells(self.cursor_row, 0, self.cols),
+                _ => {}
+            },
+            'L' | 'M' => {
+                if self.cursor_row < self.scroll_top || self.cursor_row > self.scroll_bottom {
+                    return;
+                }
+                let count = arg(0, 1).min(self.scroll_bottom + 1 - self.cursor_row);
+                for _ in 0..count {
+                    let blank = self.blank_row();
+                    if action == 'L' {
+                        self.grid.remove(self.scroll_bottom);
+                        self.grid.insert(self.cursor_row, blank);
+                    } else {
+                        self.grid.remove(self.cursor_row);
+                        self.grid.insert(self.scroll_bottom, blank);
+                    }
+                }
+                self.cursor_col = 0;
+                self.wrap_pending = false;
+            }
+            'P' => {
+                let blank = self.blank_cell();
+                let cols = self.cols;
+                let cells = &mut self.grid[self.cursor_row].cells;
+                let count = arg(0, 1).min(cols - self.cursor_col);
+                cells.drain(self.cursor_col..self.cursor_col + count);
+                cells.resize(cols, blank);
+            }
+            '@' => {
+                let blank = self.blank_cell();
+                let cols = self.cols;
+                let cells = &mut self.grid[self.cursor_row].cells;
+                let count = arg(0, 1).min(cols - self.cursor_col);
+                for _ in 0..count {
+                    cells.insert(self.cursor_col, blank);
+                }
+                cells.truncate(cols);
+            }
+            'X' => self.erase_cells(self.cursor_row, self.cursor_col, self.cursor_col + arg(0, 1)),
+            'S' if !private => self.scroll_up(arg(0, 1)),
+            'T' if !private => self.scroll_down(arg(0, 1)),
+            'm' if !private && intermediates.is_empty() => self.select_graphic_rendition(params),
+            'r' if !private => {
+                let top = arg(0, 1) - 1;
+                let bottom = arg(1, self.rows).min(self.rows) - 1;
+                if top < bottom {
+                    self.scroll_top = top;
+                    self.scroll_bottom = bottom;
+                    self.move_to(0, 0);
+                }
+            }
+            's' if !private => self.saved = self.save_cursor(),
+            'u' if !private => {
+                let saved = self.saved;
+                self.restore_cursor(saved);
+            }
+            'h' | 'l' if private => {
+                for mode in &values {
+                    self.set_private_mode(*mode, action == 'h');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, byte: u8) {
+        if !intermediates.is_empty() {
+            // Выбор набора символов и прочее нас не интересует
+            return;
+        }
+        match byte {
+            b'7' => self.saved = self.save_cursor(),
+            b'8' => {
+                let saved = self.saved;
+                self.restore_cursor(saved);
+            }
+            b'D' => self.linefeed(),
+            b'E' => {
+                self.cursor_col = 0;
+                self.linefeed();
+            }
+            b'M' => self.reverse_index(),
+            b'c' => self.reset(),
+            _ => {}
+        }
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        match params {
+            [b"0" | b"2", title, ..] => self.title = String::from_utf8_lossy(title).to_string(),
+            // vte делит параметры по ';', а в пути он тоже может встретиться
+            [b"7", url @ ..] if !url.is_empty() => {
+                let url = url.iter().map(|part| String::from_utf8_lossy(part)).collect::<Vec<_>>().join(";");
+                self.shell.report_cwd(&url);
+            }
+            [b"133", marker, args @ ..] => self.shell_integration(marker, args),
+            _ => {}
+        }
+    }
+}
+
+/// Эмулятор терминала на стороне бэкенда: экран и ограниченный scrollback сессии
+pub struct TerminalEmulator {
+    parser: Parser,
+    screen: Screen,
+}
+
+impl TerminalEmulator {
+    pub fn new(rows: u16, cols: u16) -> TerminalEmulator {
+        Self::with_scrollback(rows, cols, SCROLLBACK_LINES)
+    }
+
+    /// Эмулятор с другим пределом scrollback, например для расшифровки записи целиком
+    pub fn with_scrollback(rows: u16, cols: u16, scrollback_lines: usize) -> TerminalEmulator {
+        TerminalEmulator {
+            parser: Parser::new(),
+            screen: Screen::new(rows as usize, cols as usize, scrollback_lines),
+        }
+    }
+
+    pub fn process(&mut self, bytes: &[u8]) {
+        self.parser.advance(&mut self.screen, bytes);
+    }
+
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        self.screen.resize(rows as usize, cols as usize);
+    }
+
+    pub fn take_events(&mut self) -> Vec<ShellEvent> {
+        self.screen.shell.take_events()
+    }
+
+    pub fn shell_integration_active(&self) -> bool {
+        self.screen.shell.is_active()
+    }
+
+    /// Текущая директория по OSC 7
+    pub fn cwd(&self) -> Option<String> {
+        self.screen.shell.cwd().map(|cwd| cwd.to_string())
+    }
+
+    /// Оболочка выполняет команду (а не ждет ввода в приглашении)
+    pub fn is_running_command(&self) -> bool {
+        self.screen.shell.is_running()
+    }
+
+    pub fn commands(&self) -> Vec<CommandRecord> {
+        self.screen.shell.commands()
+    }
+
+    /// Вывод команды; строки, уже вытесненные из scrollback, пропускаются
+    pub fn command_output(&self, id: u64) -> Option<String> {
+        let screen = &self.screen;
+        let record = screen.shell.command(id)?;
+        let end = record
+            .output_end_line
+            .unwrap_or_else(|| screen.cursor_line() + 1);
+        let mut output = String::new();
+        for row in (record.output_start_line.max(screen.lines_dropped)..end).filter_map(|line| screen.row_at(line)) {
+            output.push_str(&row.text());
+            if !row.wrapped {
+                output.push('\n');
+            }
+        }
+        Some(output.trim_end().to_string())
+    }
+
+    /// Строки в том же порядке, что и в TerminalSnapshot::lines
+    fn text_rows(&self) -> impl Iterator<Item = &Row> {
+        let screen = &self.screen;
+        let main_screen = screen.primary.as_ref().unwrap_or(&screen.grid);
+        screen.scrollback.iter().chain(main_screen.iter())
+    }
+
+    /// Строки для расшифровки: без пустого хвоста экрана под последним выводом
+    fn transcript_rows(&self) -> Vec<&Row> {
+        let mut rows: Vec<&Row> = self.text_rows().collect();
+        while rows.last().is_some_and(|row| row.visible_cells().iter().all(|c| c.ch == ' ')) {
+            rows.pop();
+        }
+        rows
+    }
+
+    /// Весь вывод простым текстом
+    pub fn transcript_text(&self) -> String {
+        let mut text = String::new();
+        for row in self.transcript_rows() {
+            text.push_str(&row.text());
+            if !row.wrapped {
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// Весь вывод в HTML с цветами; результат предназначен для <pre>
+    pub fn transcript_html(&self) -> String {
+        let mut html = String::new();
+        for row in self.transcript_rows() {
+            row.write_html(&mut html);
+            if !row.wrapped {
+                html.push('\n');
+            }
+        }
+        html
+    }
+
+    pub fn snapshot(&self) -> TerminalSnapshot {
+        let screen = &self.screen;
+        let lines: Vec<String> = self.text_rows().map(|row| row.text()).collect();
+
+        let mut ansi = String::new();
+        let mut style = Style::default();
+        let main_screen = screen.primary.as_ref().unwrap_or(&screen.grid);
+        let rows: Vec<&Row> = screen.scrollback.iter().chain(main_screen.iter()).collect();
+        for (index, row) in rows.iter().enumerate() {
+            row.write_ansi(&mut ansi, &mut style);
+            // Перенесенная строка продолжится сама, как при исходном выводе
+            if index + 1 < rows.len() && !row.wrapped {
+                ansi.push_str("\r\n");
+            }
+        }
+
+        if screen.primary.is_some() {
+            ansi.push_str("\x1b[0m\x1b[?1049h\x1b[H");
+            style = Style::default();
+            for (index, row) in screen.grid.iter().enumerate() {
+                ansi.push_str(&format!("\x1b[{};1H", index + 1));
+                row.write_ansi(&mut ansi, &mut style);
+            }
+        }
+
+        ansi.push_str(&screen.style.to_sgr());
+        ansi.push_str(&format!("\x1b[{};{}H", screen.cursor_row + 1, screen.cursor_col + 1));
+        if !screen.cursor_visible {
+            ansi.push_str("\x1b[?25l");
+        }
+
+        TerminalSnapshot {
+            rows: screen.rows,
+            cols: screen.cols,
+            cursor_row: screen.cursor_row,
+            cursor_col: screen.cursor_col,
+            cursor_visible: screen.cursor_visible,
+            alternate_screen: screen.primary.is_some(),
+            title: screen.title.clone(),
+            first_line: screen.lines_dropped,
+            scrollback_lines: screen.scrollback.len(),
+            lines,
+            ansi,
+        }
+    }
+
+    /// Поиск подстроки в scrollback и на основном экране
+    pub fn search(&self, query: &str, case_sensitive: bool, max_results: usize) -> Vec<ScrollbackMatch> {
+        let mut matches = Vec::new();
+        if query.is_empty() {
+            return matches;
+        }
+
+        let needle = if case_sensitive {
+            query.to_string()
+        } else {
+            query.to_lowercase()
+        };
+        let needle_chars = needle.chars().count();
+
+        for (line, row) in self.text_rows().enumerate() {
+            let text = row.text();
+            let haystack = if case_sensitive {
+                text.clone()
+            } else {
+                text.to_lowercase()
+            };
+            // Позиция в символах, а не в байтах: фронтенд считает колонки по символам
+            for (byte_index, _) in haystack.match_indices(&needle) {
+                matches.push(ScrollbackMatch {
+                    line,
+                    column: haystack[..byte_index].chars().count(),
+                    length: needle_chars,
+                    text: text.clone(),
+                });
+                if matches.len() >= max_results {
+                    return matches;
+                }
+            }
+        }
+        matches
+    }
+}
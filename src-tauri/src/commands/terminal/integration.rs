@@ -0,0 +1,288 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use super::shell::{ShellKind, ShellProfile};
+
+// Сколько выполненных команд помнит сессия
+const HISTORY_LIMIT: usize = 500;
+
+const BASH_SCRIPT: &str = include_str!("scripts/xeditor.bash");
+const FISH_SCRIPT: &str = include_str!("scripts/xeditor.fish");
+const POWERSHELL_SCRIPT: &str = include_str!("scripts/xeditor.ps1");
+const ZSH_SCRIPTS: [(&str, &str); 4] = [
+    (".zshenv", include_str!("scripts/zsh/zshenv.zsh")),
+    (".zprofile", include_str!("scripts/zsh/zprofile.zsh")),
+    (".zshrc", include_str!("scripts/zsh/zshrc.zsh")),
+    (".zlogin", include_str!("scripts/zsh/zlogin.zsh")),
+];
+
+/// Команда, выполненная в терминале (по маркерам OSC 133).
+/// Строки вывода - абсолютные номера, см. TerminalSnapshot::first_line.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandRecord {
+    pub id: u64,
+    pub command: String,
+    pub cwd: Option<String>,
+    pub started_at: i64,
+    // Пока команда выполняется, finished_at, exit_code и output_end_line пустые
+    pub finished_at: Option<i64>,
+    pub exit_code: Option<i32>,
+    pub output_start_line: u64,
+    // Не включая эту строку
+    pub output_end_line: Option<u64>,
+}
+
+/// Изменения, о которых нужно сообщить фронтенду
+#[derive(Clone, Debug)]
+pub enum ShellEvent {
+    CwdChanged(String),
+    Command(CommandRecord),
+}
+
+/// Состояние оболочки, восстановленное из ее служебных последовательностей
+#[derive(Default)]
+pub struct ShellTracker {
+    // true после первого маркера - значит, интеграция в оболочке работает
+    active: bool,
+    cwd: Option<String>,
+    // Позиция, где закончилось приглашение и начинается ввод команды
+    input_start: Option<(u64, usize)>,
+    running: Option<CommandRecord>,
+    history: VecDeque<CommandRecord>,
+    next_id: u64,
+    events: Vec<ShellEvent>,
+}
+
+impl ShellTracker {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn cwd(&self) -> Option<&str> {
+        self.cwd.as_deref()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    pub fn input_start(&self) -> Option<(u64, usize)> {
+        self.input_start
+    }
+
+    /// История команд, включая выполняющуюся сейчас
+    pub fn commands(&self) -> Vec<CommandRecord> {
+        self.history.iter().chain(self.running.iter()).cloned().collect()
+    }
+
+    pub fn command(&self, id: u64) -> Option<&CommandRecord> {
+        self.history.iter().chain(self.running.iter()).find(|c| c.id == id)
+    }
+
+    pub fn take_events(&mut self) -> Vec<ShellEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// OSC 7: file://host/path
+    pub fn report_cwd(&mut self, url: &str) {
+        let Some(path) = parse_file_url(url) else {
+            return;
+        };
+        self.active = true;
+        if self.cwd.as_deref() != Some(path.as_str()) {
+            self.cwd = Some(path.clone());
+            self.events.push(ShellEvent::CwdChanged(path));
+        }
+    }
+
+    /// OSC 133;B - приглашение выведено, дальше пользователь вводит команду
+    pub fn prompt_end(&mut self, position: (u64, usize)) {
+        self.active = true;
+        self.input_start = Some(position);
+    }
+
+    /// OSC 133;C - команда принята, дальше идет ее вывод
+    pub fn command_start(&mut self, command: String, output_start_line: u64) {
+        self.active = true;
+        self.input_start = None;
+        self.next_id += 1;
+        let record = CommandRecord {
+            id: self.next_id,
+            command,
+            cwd: self.cwd.clone(),
+            started_at: chrono::Local::now().timestamp_millis(),
+            finished_at: None,
+            exit_code: None,
+            output_start_line,
+            output_end_line: None,
+        };
+        self.events.push(ShellEvent::Command(record.clone()));
+        self.running = Some(record);
+    }
+
+    /// OSC 133;D;код - команда завершилась. Без предшествующего C (пустой Enter) игнорируется.
+    pub fn command_finish(&mut self, exit_code: Option<i32>, output_end_line: u64) {
+        self.active = true;
+        let Some(mut record) = self.running.take() else {
+            return;
+        };
+        record.finished_at = Some(chrono::Local::now().timestamp_millis());
+        record.exit_code = exit_code;
+        record.output_end_line = Some(output_end_line.max(record.output_start_line));
+        self.events.push(ShellEvent::Command(record.clone()));
+        self.history.push_back(record);
+        while self.history.len() > HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+    }
+}
+
+/// Разбирает file://host/path из OSC 7 в локальный путь
+pub fn parse_file_url(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("file://")?;
+    // Хост нас не интересует: терминал всегда локальный
+    let path = &rest[rest.find('/')?..];
+    let path = percent_decode(path);
+
+    // На Windows путь приходит как /C:/Users/...
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        return Some(path[1..].replace('/', "\\"));
+    }
+    Some(path)
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let high = (bytes[i + 1] as char).to_digit(16);
+            let low = (bytes[i + 2] as char).to_digit(16);
+            if let (Some(high), Some(low)) = (high, low) {
+                decoded.push((high * 16 + low) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Аргументы и переменные окружения, которые подключают интеграцию к оболочке
+pub struct Injection {
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+}
+
+fn scripts_dir() -> PathBuf {
+    // Каталог пользователя, а не общий /tmp: туда никто другой не подложит свой скрипт
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("xeditor")
+        .join("shell-integration")
+}
+
+/// Записывает скрипт, если его еще нет или он устарел
+fn install_script(dir: &Path, name: &str, content: &str) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Не удалось создать каталог {}: {}", dir.display(), e))?;
+    let path = dir.join(name);
+    if std::fs::read_to_string(&path).ok().as_deref() != Some(content) {
+        std::fs::write(&path, content)
+            .map_err(|e| format!("Не удалось записать {}: {}", path.display(), e))?;
+    }
+    Ok(path)
+}
+
+/// Готовит запуск оболочки с интеграцией. None - оболочка не поддерживается
+/// или интеграция выключена в профиле; тогда оболочка запускается как обычно.
+pub fn inject(profile: &ShellProfile) -> Result<Option<Injection>, String> {
+    if !profile.shell_integration || !profile.interactive {
+        return Ok(None);
+    }
+
+    let dir = scripts_dir();
+    let injection = match profile.kind {
+        ShellKind::Bash => {
+            let script = install_script(&dir, "xeditor.bash", BASH_SCRIPT)?;
+            // С -l bash игнорирует --init-file, поэтому login-файлы загружает сам скрипт
+            let mut args = vec![
+                "--init-file".to_string(),
+                script.to_string_lossy().to_string(),
+                "-i".to_string(),
+            ];
+            args.extend(profile.args.iter().cloned());
+            let mut env = Vec::new();
+            if profile.login {
+                env.push(("XEDITOR_SHELL_LOGIN".to_string(), "1".to_string()));
+            }
+            Injection { args, env }
+        }
+        ShellKind::Zsh => {
+            let zsh_dir = dir.join("zsh");
+            for (name, content) in ZSH_SCRIPTS {
+                install_script(&zsh_dir, name, content)?;
+            }
+            let user_zdotdir = profile
+                .env
+                .get("ZDOTDIR")
+                .cloned()
+                .or_else(|| std::env::var("ZDOTDIR").ok())
+                .or_else(|| dirs::home_dir().map(|home| home.to_string_lossy().to_string()))
+                .unwrap_or_default();
+            Injection {
+                args: profile.command_args(),
+                env: vec![
+                    ("ZDOTDIR".to_string(), zsh_dir.to_string_lossy().to_string()),
+                    ("XEDITOR_USER_ZDOTDIR".to_string(), user_zdotdir),
+                ],
+            }
+        }
+        ShellKind::Fish => {
+            let script = install_script(&dir, "xeditor.fish", FISH_SCRIPT)?;
+            let mut args = profile.command_args();
+            args.push("--init-command".to_string());
+            args.push(format!("source {}", ShellKind::Fish.quote(&script.to_string_lossy())));
+            Injection { args, env: Vec::new() }
+        }
+        ShellKind::PowerShell => {
+            let script = install_script(&dir, "xeditor.ps1", POWERSHELL_SCRIPT)?;
+            let source = format!(". {}", ShellKind::PowerShell.quote(&script.to_string_lossy()));
+            Injection {
+                args: powershell_args(profile.command_args(), &source),
+                env: Vec::new(),
+            }
+        }
+        ShellKind::Sh | ShellKind::Cmd => return Ok(None),
+    };
+    Ok(Some(injection))
+}
+
+/// -Command должен быть последним аргументом PowerShell: если он уже есть
+/// в профиле (chcp 65001), дописываем подключение скрипта к нему
+fn powershell_args(mut args: Vec<String>, source: &str) -> Vec<String> {
+    let command_index = args
+        .iter()
+        .position(|arg| arg.eq_ignore_ascii_case("-Command") || arg.eq_ignore_ascii_case("-c"));
+    match command_index {
+        Some(index) => {
+            let command = args.split_off(index + 1).join(" ");
+            if command.trim().is_empty() {
+                args.push(source.to_string());
+            } else {
+                args.push(format!("{}; {}", command, source));
+            }
+        }
+        None => {
+            args.push("-Command".to_string());
+            args.push(source.to_string());
+        }
+    }
+    args
+}
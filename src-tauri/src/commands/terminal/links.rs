@@ -0,0 +1,146 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+
+use super::integration::parse_file_url;
+
+lazy_static! {
+    //   File "app.py", line 3, in main
+    static ref PYTHON_FRAME: Regex = Regex::new(r#"File "([^"]+)", line (\d+)"#).unwrap();
+    // file:///home/user/app.js:10:5
+    static ref FILE_URL: Regex = Regex::new(r#"file://[^\s"'<>()\[\]]+?(?::(\d+)(?::(\d+))?)?(?:[\s"'<>()\[\]]|$)"#).unwrap();
+    // src/main.rs:12:5, C:\src\main.c(3,7), ~/notes.md, ./build.sh:2
+    static ref PATH: Regex = Regex::new(
+        r"(?:[A-Za-z]:[\\/]|~[\\/]|\.{1,2}[\\/]|[\\/])?(?:[\w.\-+@$]+[\\/])*[\w.\-+@$]+(?::(\d+)(?::(\d+))?|\((\d+)(?:,\s*(\d+))?\))?"
+    )
+    .unwrap();
+}
+
+/// Ссылка на файл в строке вывода терминала
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalLink {
+    // Позиция в строке в символах (как считает фронтенд), end не включается
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    // Абсолютный путь к существующему файлу
+    pub path: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+/// Убирает из пути `.` и `..`, не обращаясь к диску (canonicalize в Windows дает \\?\-пути)
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Находит файл: `~` раскрывается, относительный путь ищется по очереди в `base_dirs`
+fn resolve_path(raw: &str, base_dirs: &[PathBuf]) -> Option<PathBuf> {
+    let expanded = match raw.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            dirs::home_dir()?.join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(raw),
+    };
+    if expanded.is_absolute() {
+        return expanded.is_file().then(|| normalize(&expanded));
+    }
+    base_dirs
+        .iter()
+        .map(|dir| normalize(&dir.join(&expanded)))
+        .find(|path| path.is_file())
+}
+
+/// Ссылка с позицией в символах по диапазону байтов строки
+fn make_link(line: &str, start: usize, end: usize, path: PathBuf, line_number: Option<u32>, column: Option<u32>) -> TerminalLink {
+    TerminalLink {
+        start: line[..start].chars().count(),
+        end: line[..end].chars().count(),
+        text: line[start..end].to_string(),
+        path: path.to_string_lossy().to_string(),
+        line: line_number,
+        column,
+    }
+}
+
+fn number(captures: &regex::Captures, group: usize) -> Option<u32> {
+    captures.get(group)?.as_str().parse().ok()
+}
+
+/// Ссылки на существующие файлы в строке вывода
+pub fn find_links(line: &str, base_dirs: &[PathBuf]) -> Vec<TerminalLink> {
+    // Найденные диапазоны в байтах, чтобы общие шаблоны не перекрывали частные
+    let mut found: Vec<(usize, usize, TerminalLink)> = Vec::new();
+    let overlaps = |found: &[(usize, usize, TerminalLink)], start: usize, end: usize| {
+        found.iter().any(|(s, e, _)| start < *e && *s < end)
+    };
+
+    for captures in PYTHON_FRAME.captures_iter(line) {
+        let whole = captures.get(0).unwrap();
+        if let Some(path) = resolve_path(&captures[1], base_dirs) {
+            let link = make_link(line, whole.start(), whole.end(), path, number(&captures, 2), None);
+            found.push((whole.start(), whole.end(), link));
+        }
+    }
+
+    for captures in FILE_URL.captures_iter(line) {
+        let whole = captures.get(0).unwrap();
+        // Шаблон захватывает завершающий разделитель - он не часть ссылки
+        let text = whole.as_str().trim_end_matches(|c: char| c.is_whitespace() || "\"'<>()[]".contains(c));
+        let end = whole.start() + text.len();
+        if overlaps(&found, whole.start(), end) {
+            continue;
+        }
+        let url = match captures.get(1) {
+            Some(line_group) => &line[whole.start()..line_group.start() - 1],
+            None => text,
+        };
+        if let Some(path) = parse_file_url(url).and_then(|path| resolve_path(&path, base_dirs)) {
+            let link = make_link(line, whole.start(), end, path, number(&captures, 1), number(&captures, 2));
+            found.push((whole.start(), end, link));
+        }
+    }
+
+    for captures in PATH.captures_iter(line) {
+        let whole = captures.get(0).unwrap();
+        let has_position = captures.get(1).is_some() || captures.get(3).is_some();
+        let path_end = [1, 3]
+            .iter()
+            .find_map(|&group| captures.get(group))
+            .map_or(whole.end(), |group| group.start() - 1);
+        // Точка в конце предложения - не часть имени файла
+        let raw = line[whole.start()..path_end].trim_end_matches('.');
+        // Одиночные слова без расширения и пути проверять на диске не нужно
+        if raw.is_empty() || !(has_position || raw.contains(['/', '\\', '.'])) {
+            continue;
+        }
+        let end = if has_position { whole.end() } else { whole.start() + raw.len() };
+        if overlaps(&found, whole.start(), end) {
+            continue;
+        }
+        if let Some(path) = resolve_path(raw, base_dirs) {
+            let (line_number, column) = match captures.get(1) {
+                Some(_) => (number(&captures, 1), number(&captures, 2)),
+                None => (number(&captures, 3), number(&captures, 4)),
+            };
+            found.push((whole.start(), end, make_link(line, whole.start(), end, path, line_number, column)));
+        }
+    }
+
+    found.sort_by_key(|(start, _, _)| *start);
+    found.into_iter().map(|(_, _, link)| link).collect()
+}
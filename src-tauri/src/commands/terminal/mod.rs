@@ -0,0 +1,962 @@
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    time::Duration,
+};
+use tauri::{async_runtime::Mutex, AppHandle, Emitter, State};
+
+use crate::commands::problem_matchers::{self, ProblemCollector};
+use crate::commands::project_env::ProjectEnvironment;
+
+pub mod emulator;
+pub mod integration;
+pub mod links;
+pub mod output;
+pub mod process;
+pub mod recording;
+pub mod runner;
+pub mod shell;
+
+use emulator::{ScrollbackMatch, TerminalEmulator, TerminalSnapshot};
+use integration::{CommandRecord, ShellEvent};
+use links::TerminalLink;
+use output::{FlowControl, Utf8StreamDecoder};
+use process::{TerminalProcess, TerminalSignal};
+use recording::{Recorder, RecordingInfo};
+use shell::{ShellKind, ShellProfile};
+
+// Идентификатор сессии, которую используют команды, вызванные без session_id
+pub const DEFAULT_SESSION_ID: &str = "default";
+
+/// Одна PTY-сессия терминала (вкладка build, server, git и т.д.)
+pub struct PtySession {
+    pub id: String,
+    pub title: String,
+    pub created_at: i64,
+    pub profile: ShellProfile,
+    pub cwd: Option<String>,
+    pub shell: ShellKind,
+    // Команда, которую выполняет сессия вместо интерактивной оболочки
+    pub command: Option<String>,
+    pub project_root: Option<String>,
+    pub pid: Option<u32>,
+    status: std::sync::Mutex<SessionStatus>,
+    flow: Arc<FlowControl>,
+    // Копия экрана и scrollback для восстановления фронтенда и поиска
+    screen: Arc<std::sync::Mutex<TerminalEmulator>>,
+    // Запись сессии в asciicast, если включена
+    recording: Arc<std::sync::Mutex<Option<Recorder>>>,
+    master: Mutex<Box<dyn MasterPty + Send>>,
+    writer: Mutex<Box<dyn Write + Send>>,
+    killer: Mutex<Box<dyn ChildKiller + Send + Sync>>,
+    #[cfg(windows)]
+    job: Option<process::JobObject>,
+}
+
+/// Состояние процесса оболочки в сессии
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "state", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum SessionStatus {
+    Running,
+    Exited {
+        // None, если код завершения получить не удалось
+        exit_code: Option<u32>,
+        signal: Option<String>,
+        ended_at: i64,
+    },
+}
+
+/// Менеджер PTY-сессий, ключ - идентификатор сессии
+pub struct PtyState {
+    pub sessions: Arc<Mutex<HashMap<String, Arc<PtySession>>>>,
+    next_id: AtomicU64,
+}
+
+impl Default for PtyState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PtyState {
+    pub fn new() -> Self {
+        PtyState {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    fn generate_id(&self) -> String {
+        format!("term-{}", self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Возвращает сессию по идентификатору (или сессию по умолчанию)
+    pub async fn get(&self, session_id: Option<&str>) -> Result<Arc<PtySession>, String> {
+        let id = session_id.unwrap_or(DEFAULT_SESSION_ID);
+        self.sessions
+            .lock()
+            .await
+            .get(id)
+            .cloned()
+            .ok_or_else(|| format!("Терминальная сессия '{}' не найдена", id))
+    }
+}
+
+impl PtySession {
+    pub fn status(&self) -> SessionStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self.status(), SessionStatus::Running)
+    }
+
+    /// Текущая директория оболочки: по OSC 7, а без интеграции на Linux - из /proc
+    pub fn current_dir(&self) -> Option<String> {
+        if let Some(cwd) = self.screen.lock().unwrap().cwd() {
+            return Some(cwd);
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(pid) = self.pid {
+            if let Ok(path) = std::fs::read_link(format!("/proc/{}/cwd", pid)) {
+                return Some(path.to_string_lossy().to_string());
+            }
+        }
+        None
+    }
+
+    pub async fn write_input(&self, data: &[u8]) -> Result<(), String> {
+        if !self.is_running() {
+            return Err(format!("Сессия терминала '{}' завершена", self.id));
+        }
+        if let Some(recorder) = self.recording.lock().unwrap().as_mut() {
+            recorder.input(&String::from_utf8_lossy(data));
+        }
+        let mut writer = self.writer.lock().await;
+        writer
+            .write_all(data)
+            .map_err(|e| format!("Failed to write to PTY: {}", e))?;
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to flush PTY: {}", e))
+    }
+
+    /// Печатает команду в оболочку и "нажимает" Enter
+    pub async fn write_command(&self, command: &str) -> Result<(), String> {
+        let line = format!("{}{}", command, self.shell.newline());
+        self.write_input(line.as_bytes()).await
+    }
+
+    /// Группа процессов переднего плана, если в терминале выполняется
+    /// что-то кроме самой оболочки
+    #[cfg(unix)]
+    async fn foreground_group(&self) -> Option<u32> {
+        let leader = self.master.lock().await.process_group_leader()? as u32;
+        if Some(leader) == self.pid {
+            None
+        } else {
+            Some(leader)
+        }
+    }
+
+    /// Процессы, запущенные из оболочки сессии
+    pub async fn processes(&self) -> Result<Vec<TerminalProcess>, String> {
+        let pid = self
+            .pid
+            .ok_or_else(|| "Не удалось определить процесс оболочки".to_string())?;
+        #[cfg(unix)]
+        let foreground = self.foreground_group().await;
+        #[cfg(not(unix))]
+        let foreground = None;
+        process::descendants(pid, foreground)
+    }
+
+    /// Отправляет сигнал процессу переднего плана, не трогая саму оболочку
+    pub async fn send_signal(&self, signal: TerminalSignal) -> Result<(), String> {
+        // Ctrl+C через PTY: драйвер терминала сам доставит SIGINT группе
+        // переднего плана, а в пустой оболочке просто сбросит строку ввода
+        if signal == TerminalSignal::Interrupt {
+            #[cfg(unix)]
+            if let Some(group) = self.foreground_group().await {
+                return process::signal_group(group, signal);
+            }
+            return self.write_input(&[0x03]).await;
+        }
+
+        #[cfg(unix)]
+        {
+            match self.foreground_group().await {
+                Some(group) => process::signal_group(group, signal),
+                None => Err("В терминале нет запущенных процессов".to_string()),
+            }
+        }
+
+        // На Windows нет групп процессов: завершаем всех потомков оболочки
+        #[cfg(windows)]
+        {
+            let children = self.processes().await?;
+            if children.is_empty() {
+                return Err("В терминале нет запущенных процессов".to_string());
+            }
+            for child in children.iter().rev() {
+                let _ = process::terminate_pid(child.pid);
+            }
+            Ok(())
+        }
+    }
+
+    /// Завершает оболочку вместе со всеми запущенными из нее процессами
+    async fn kill_tree(&self) {
+        self.flow.disable();
+
+        #[cfg(unix)]
+        if let Ok(children) = self.processes().await {
+            for child in children.iter().rev() {
+                let _ = process::signal_pid(child.pid, TerminalSignal::Kill);
+            }
+        }
+        #[cfg(windows)]
+        if let Some(job) = &self.job {
+            job.terminate();
+        }
+
+        let _ = self.killer.lock().await.kill();
+    }
+
+    fn info(&self) -> TerminalSessionInfo {
+        TerminalSessionInfo {
+            session_id: self.id.clone(),
+            title: self.title.clone(),
+            created_at: self.created_at,
+            profile_id: self.profile.id.clone(),
+            shell: self.shell,
+            command: self.command.clone(),
+            pid: self.pid,
+            status: self.status(),
+            current_dir: self.current_dir(),
+            shell_integration: self.screen.lock().unwrap().shell_integration_active(),
+            recording: self.recording.lock().unwrap().as_ref().map(|r| r.info().path),
+        }
+    }
+}
+
+/// Информация о сессии для панели терминала
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalSessionInfo {
+    pub session_id: String,
+    pub title: String,
+    pub created_at: i64,
+    pub profile_id: String,
+    pub shell: ShellKind,
+    pub command: Option<String>,
+    pub pid: Option<u32>,
+    pub status: SessionStatus,
+    pub current_dir: Option<String>,
+    // Оболочка присылает маркеры OSC 7/133
+    pub shell_integration: bool,
+    // Путь к файлу, если сессия записывается
+    pub recording: Option<String>,
+}
+
+/// Payload события `pty-output`
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PtyOutput {
+    pub session_id: String,
+    pub data: String,
+    // Размер data в байтах UTF-8 - это значение фронтенд передает в ack_pty_output
+    pub bytes: usize,
+}
+
+/// Payload события `pty-exit`
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PtyExit {
+    pub session_id: String,
+    pub exit_code: Option<u32>,
+    pub signal: Option<String>,
+    pub success: bool,
+}
+
+/// Payload события `terminal-cwd`
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalCwdChanged {
+    pub session_id: String,
+    pub cwd: String,
+}
+
+/// Payload события `terminal-command`: команда началась (finishedAt пустой) или завершилась
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalCommandEvent {
+    pub session_id: String,
+    pub command: CommandRecord,
+}
+
+/// Параметры запуска оболочки в новой сессии
+#[derive(Clone)]
+pub struct SpawnOptions {
+    pub session_id: String,
+    pub title: String,
+    pub profile: ShellProfile,
+    // Явная рабочая директория имеет приоритет над cwd профиля
+    pub cwd: Option<String>,
+    pub rows: u16,
+    pub cols: u16,
+    // Выполнить команду и завершиться вместо интерактивной оболочки
+    pub command: Option<String>,
+    // Для настроек проекта, например problem matcher-ов
+    pub project_root: Option<String>,
+}
+
+fn build_command(
+    profile: &ShellProfile,
+    environment: &ProjectEnvironment,
+    cwd: Option<&str>,
+    command: Option<&str>,
+) -> CommandBuilder {
+    let mut cmd = CommandBuilder::new(&profile.path);
+    // Если скрипты интеграции не удалось подготовить, оболочка просто работает без нее
+    let injection = match command {
+        Some(_) => None,
+        None => integration::inject(profile).unwrap_or_else(|e| {
+            eprintln!("[{}] {}", profile.id, e);
+            None
+        }),
+    };
+    match (&injection, command) {
+        (_, Some(command)) => cmd.args(profile.kind.run_args(profile.login, command)),
+        (Some(injection), None) => cmd.args(&injection.args),
+        (None, None) => cmd.args(profile.command_args()),
+    }
+
+    if !cfg!(windows) {
+        cmd.env("TERM", "xterm-256color");
+        cmd.env("COLORTERM", "truecolor");
+    }
+    // Окружение проекта (.env, venv, node_modules/.bin), поверх него - профиль оболочки
+    for (key, value) in environment.vars() {
+        cmd.env(key, value);
+    }
+    for key in environment.removed() {
+        cmd.env_remove(key);
+    }
+    for (key, value) in &profile.env {
+        cmd.env(key, value);
+    }
+    for (key, value) in injection.iter().flat_map(|injection| &injection.env) {
+        cmd.env(key, value);
+    }
+
+    if let Some(dir) = cwd.or(profile.cwd.as_deref()) {
+        if std::path::Path::new(dir).is_dir() {
+            cmd.cwd(dir);
+        }
+    }
+    cmd
+}
+
+fn emit_shell_event(app: &AppHandle, session_id: &str, event: ShellEvent) {
+    let session_id = session_id.to_string();
+    let _ = match event {
+        ShellEvent::CwdChanged(cwd) => app.emit("terminal-cwd", TerminalCwdChanged { session_id, cwd }),
+        ShellEvent::Command(command) => app.emit("terminal-command", TerminalCommandEvent { session_id, command }),
+    };
+}
+
+/// Запускает оболочку в новой PTY и регистрирует сессию под указанным id.
+/// Если сессия с таким id уже существует, она закрывается.
+async fn spawn_session(
+    state: &PtyState,
+    app: AppHandle,
+    options: SpawnOptions,
+) -> Result<TerminalSessionInfo, String> {
+    let SpawnOptions {
+        session_id,
+        title,
+        profile,
+        cwd,
+        rows,
+        cols,
+        command,
+        project_root,
+    } = options;
+
+    let pty_system = native_pty_system();
+    let pair = pty_system
+        .openpty(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| e.to_string())?;
+
+    let environment = ProjectEnvironment::for_project(project_root.as_deref());
+    let cmd = build_command(&profile, &environment, cwd.as_deref(), command.as_deref());
+    let mut child = pair.slave.spawn_command(cmd).map_err(|e| e.to_string())?;
+    let killer = child.clone_killer();
+    let pid = child.process_id();
+    #[cfg(windows)]
+    let job = pid.and_then(|pid| match process::JobObject::for_process(pid) {
+        Ok(job) => Some(job),
+        Err(e) => {
+            eprintln!("[{}] {}", session_id, e);
+            None
+        }
+    });
+
+    let master = pair.master;
+    let mut reader = master.try_clone_reader().map_err(|e| e.to_string())?;
+    let writer = master.take_writer().map_err(|e| e.to_string())?;
+
+    let session = Arc::new(PtySession {
+        id: session_id.clone(),
+        title,
+        created_at: chrono::Local::now().timestamp_millis(),
+        shell: profile.kind,
+        profile,
+        cwd,
+        command,
+        project_root,
+        pid,
+        status: std::sync::Mutex::new(SessionStatus::Running),
+        flow: Arc::new(FlowControl::default()),
+        screen: Arc::new(std::sync::Mutex::new(TerminalEmulator::new(rows, cols))),
+        recording: Arc::new(std::sync::Mutex::new(None)),
+        master: Mutex::new(master),
+        writer: Mutex::new(writer),
+        killer: Mutex::new(killer),
+        #[cfg(windows)]
+        job,
+    });
+    let info = session.info();
+
+    let previous = state
+        .sessions
+        .lock()
+        .await
+        .insert(session_id.clone(), session.clone());
+    if let Some(previous) = previous {
+        previous.kill_tree().await;
+    }
+
+    // Чтение из PTY блокирующее, поэтому выполняем его в отдельном потоке.
+    // Очередь ограничена: если вывод не успевают отправлять, чтение встает.
+    let (chunk_tx, chunk_rx) = mpsc::sync_channel::<Vec<u8>>(output::READ_QUEUE_CHUNKS);
+    let reader_session_id = session_id.clone();
+    let reader_screen = session.screen.clone();
+    let reader_app = app.clone();
+    let reader_recording = session.recording.clone();
+    let problems_owner = format!("terminal:{}", session_id);
+    let base_dir = session
+        .cwd
+        .clone()
+        .or_else(|| session.profile.cwd.clone())
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default();
+    let mut problems = ProblemCollector::new(
+        &problem_matchers::load_settings(session.project_root.as_deref()),
+        &base_dir,
+        None,
+    );
+    problem_matchers::publish(&app, &problems_owner, Vec::new(), true);
+    std::thread::spawn(move || {
+        let mut buffer = [0u8; 8192];
+        let mut decoder = Utf8StreamDecoder::default();
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(n) if n > 0 => {
+                    let events = {
+                        let mut screen = reader_screen.lock().unwrap();
+                        screen.process(&buffer[..n]);
+                        screen.take_events()
+                    };
+                    for event in events {
+                        match &event {
+                            ShellEvent::CwdChanged(cwd) => problems.set_base_dir(std::path::Path::new(cwd)),
+                            // Новая команда - прежние проблемы терминала больше не актуальны
+                            ShellEvent::Command(command) if command.finished_at.is_none() => {
+                                problems.reset();
+                                problem_matchers::publish(&reader_app, &problems_owner, Vec::new(), true);
+                            }
+                            ShellEvent::Command(_) => {}
+                        }
+                        emit_shell_event(&reader_app, &reader_session_id, event);
+                    }
+                    let text = decoder.decode(&buffer[..n]);
+                    if let Some(recorder) = reader_recording.lock().unwrap().as_mut() {
+                        recorder.output(&text);
+                    }
+                    let diagnostics = problems.feed(&text);
+                    problem_matchers::publish(&reader_app, &problems_owner, diagnostics, false);
+                    if chunk_tx.send(buffer[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => break,
+                Err(e) => {
+                    eprintln!("[{}] Read error: {}", reader_session_id, e);
+                    break;
+                }
+            }
+        }
+        let text = decoder.finish();
+        if let Some(recorder) = reader_recording.lock().unwrap().as_mut() {
+            recorder.output(&text);
+        }
+        let mut diagnostics = problems.feed(&text);
+        diagnostics.extend(problems.finish());
+        problem_matchers::publish(&reader_app, &problems_owner, diagnostics, false);
+    });
+
+    // Отдельный поток склеивает блоки в события pty-output.
+    // Он не держит саму сессию, иначе master PTY не освободится до EOF.
+    let (reader_done_tx, reader_done_rx) = mpsc::channel::<()>();
+    let output_session_id = session_id.clone();
+    let output_flow = session.flow.clone();
+    let output_app = app.clone();
+    std::thread::spawn(move || {
+        output::pump_output(chunk_rx, &output_flow, |data| {
+            let _ = output_app.emit(
+                "pty-output",
+                PtyOutput {
+                    session_id: output_session_id.clone(),
+                    bytes: data.len(),
+                    data,
+                },
+            );
+        });
+        let _ = reader_done_tx.send(());
+    });
+
+    let sessions = state.sessions.clone();
+    std::thread::spawn(move || {
+        let (exit_code, signal) = match child.wait() {
+            Ok(status) => (Some(status.exit_code()), status.signal().map(|s| s.to_string())),
+            Err(e) => {
+                eprintln!("[{}] Failed to wait for child: {}", session_id, e);
+                (None, None)
+            }
+        };
+
+        // Даем читателю дочитать остаток вывода, чтобы pty-exit пришел последним.
+        // На Windows ConPTY не отдает EOF, пока открыт master, поэтому ждем ограниченно.
+        let _ = reader_done_rx.recv_timeout(Duration::from_millis(500));
+
+        // Запись заканчивается вместе с сессией
+        session.recording.lock().unwrap().take();
+        *session.status.lock().unwrap() = SessionStatus::Exited {
+            exit_code,
+            signal: signal.clone(),
+            ended_at: chrono::Local::now().timestamp_millis(),
+        };
+
+        // Сессия могла быть перезапущена под тем же id - тогда событие устарело
+        let is_current = sessions
+            .blocking_lock()
+            .get(&session_id)
+            .is_some_and(|current| Arc::ptr_eq(current, &session));
+        if is_current {
+            let _ = app.emit(
+                "pty-exit",
+                PtyExit {
+                    session_id,
+                    success: exit_code == Some(0) && signal.is_none(),
+                    exit_code,
+                    signal,
+                },
+            );
+        }
+    });
+
+    Ok(info)
+}
+
+#[tauri::command]
+pub async fn resize_pty(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+    rows: u16,
+    cols: u16,
+) -> Result<(), String> {
+    let session = state.get(session_id.as_deref()).await?;
+    let master = session.master.lock().await;
+    master
+        .resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| e.to_string())?;
+    session.screen.lock().unwrap().resize(rows, cols);
+    if let Some(recorder) = session.recording.lock().unwrap().as_mut() {
+        recorder.resize(cols, rows);
+    }
+    Ok(())
+}
+
+/// Начинает запись вывода сессии в файл asciicast v2 (по умолчанию - в каталог
+/// данных приложения). С `record_input` записываются и нажатия клавиш - в том
+/// числе вводимые пароли, поэтому по умолчанию ввод не пишется.
+#[tauri::command]
+pub async fn start_terminal_recording(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+    path: Option<String>,
+    record_input: Option<bool>,
+) -> Result<RecordingInfo, String> {
+    let session = state.get(session_id.as_deref()).await?;
+    let size = session.master.lock().await.get_size().map_err(|e| e.to_string())?;
+    let mut recording = session.recording.lock().unwrap();
+    if let Some(recorder) = recording.as_ref() {
+        return Err(format!("Сессия уже записывается в {}", recorder.info().path));
+    }
+    let path = path
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| recording::default_path(&session.id));
+    let recorder = Recorder::create(
+        &session.id,
+        &path,
+        size.cols,
+        size.rows,
+        &session.title,
+        &session.profile.path,
+        record_input.unwrap_or(false),
+    )?;
+    let info = recorder.info();
+    *recording = Some(recorder);
+    Ok(info)
+}
+
+#[tauri::command]
+pub async fn stop_terminal_recording(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+) -> Result<RecordingInfo, String> {
+    let session = state.get(session_id.as_deref()).await?;
+    let recorder = session.recording.lock().unwrap().take();
+    recorder
+        .map(|recorder| recorder.info())
+        .ok_or_else(|| "Сессия не записывается".to_string())
+}
+
+/// Текущее содержимое экрана и scrollback сессии - для фронтенда,
+/// который переподключается к уже работающему терминалу
+#[tauri::command]
+pub async fn get_terminal_snapshot(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+) -> Result<TerminalSnapshot, String> {
+    let session = state.get(session_id.as_deref()).await?;
+    session.flow.reset();
+    let snapshot = session.screen.lock().unwrap().snapshot();
+    Ok(snapshot)
+}
+
+/// Поиск по выводу сессии, включая строки, которые фронтенд уже не хранит
+#[tauri::command]
+pub async fn search_terminal_output(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+    query: String,
+    case_sensitive: Option<bool>,
+    max_results: Option<usize>,
+) -> Result<Vec<ScrollbackMatch>, String> {
+    let session = state.get(session_id.as_deref()).await?;
+    let matches = session
+        .screen
+        .lock()
+        .unwrap()
+        .search(&query, case_sensitive.unwrap_or(false), max_results.unwrap_or(1000));
+    Ok(matches)
+}
+
+/// Запускает оболочку в сессии `session_id` (по умолчанию - "default"),
+/// заменяя уже запущенную в этой сессии оболочку. Без `profile_id`
+/// используется профиль по умолчанию проекта. Возвращает id сессии.
+#[tauri::command]
+pub async fn start_process(
+    state: State<'_, PtyState>,
+    app: AppHandle,
+    session_id: Option<String>,
+    profile_id: Option<String>,
+    project_root: Option<String>,
+    cwd: Option<String>,
+) -> Result<String, String> {
+    let profile = shell::resolve_profile(project_root.as_deref(), profile_id.as_deref())?;
+    let session_id = session_id.unwrap_or_else(|| DEFAULT_SESSION_ID.to_string());
+    let options = SpawnOptions {
+        title: session_id.clone(),
+        session_id,
+        profile,
+        cwd: cwd.or_else(|| project_root.clone()),
+        rows: 24,
+        cols: 80,
+        command: None,
+        project_root,
+    };
+    spawn_session(&state, app, options)
+        .await
+        .map(|info| info.session_id)
+}
+
+/// Создает новую терминальную сессию со сгенерированным id
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_terminal_session(
+    state: State<'_, PtyState>,
+    app: AppHandle,
+    title: Option<String>,
+    profile_id: Option<String>,
+    project_root: Option<String>,
+    cwd: Option<String>,
+    rows: Option<u16>,
+    cols: Option<u16>,
+) -> Result<TerminalSessionInfo, String> {
+    let profile = shell::resolve_profile(project_root.as_deref(), profile_id.as_deref())?;
+    let session_id = state.generate_id();
+    let options = SpawnOptions {
+        title: title.unwrap_or_else(|| profile.name.clone()),
+        session_id,
+        profile,
+        cwd: cwd.or_else(|| project_root.clone()),
+        rows: rows.unwrap_or(24),
+        cols: cols.unwrap_or(80),
+        command: None,
+        project_root,
+    };
+    spawn_session(&state, app, options).await
+}
+
+#[tauri::command]
+pub async fn list_terminal_sessions(
+    state: State<'_, PtyState>,
+) -> Result<Vec<TerminalSessionInfo>, String> {
+    let mut sessions: Vec<TerminalSessionInfo> = state
+        .sessions
+        .lock()
+        .await
+        .values()
+        .map(|session| session.info())
+        .collect();
+    sessions.sort_by_key(|info| info.created_at);
+    Ok(sessions)
+}
+
+/// Закрывает сессию: завершает оболочку и освобождает PTY
+#[tauri::command]
+pub async fn close_terminal_session(
+    state: State<'_, PtyState>,
+    session_id: String,
+) -> Result<(), String> {
+    let session = state
+        .sessions
+        .lock()
+        .await
+        .remove(&session_id)
+        .ok_or_else(|| format!("Терминальная сессия '{}' не найдена", session_id))?;
+    session.kill_tree().await;
+    Ok(())
+}
+
+/// Подтверждает, что фронтенд отрисовал `bytes` байт вывода сессии.
+/// После первого вызова включается управление потоком для этой сессии;
+/// get_terminal_snapshot при переподключении его сбрасывает.
+#[tauri::command]
+pub async fn ack_pty_output(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+    bytes: usize,
+) -> Result<(), String> {
+    state.get(session_id.as_deref()).await?.flow.ack(bytes);
+    Ok(())
+}
+
+/// Перезапускает сессию с теми же профилем, директорией, командой и размером
+#[tauri::command]
+pub async fn restart_terminal_session(
+    state: State<'_, PtyState>,
+    app: AppHandle,
+    session_id: String,
+) -> Result<TerminalSessionInfo, String> {
+    let session = state.get(Some(&session_id)).await?;
+    let size = session.master.lock().await.get_size().ok();
+    let options = SpawnOptions {
+        session_id,
+        title: session.title.clone(),
+        profile: session.profile.clone(),
+        cwd: session.cwd.clone(),
+        rows: size.map_or(24, |s| s.rows),
+        cols: size.map_or(80, |s| s.cols),
+        command: session.command.clone(),
+        project_root: session.project_root.clone(),
+    };
+    spawn_session(&state, app, options).await
+}
+
+#[tauri::command]
+pub async fn get_terminal_session_status(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+) -> Result<TerminalSessionInfo, String> {
+    Ok(state.get(session_id.as_deref()).await?.info())
+}
+
+#[tauri::command]
+pub async fn send_input(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+    input: String,
+) -> Result<(), String> {
+    let session = state.get(session_id.as_deref()).await?;
+    session.write_input(input.as_bytes()).await
+}
+
+/// Переходит в директорию `path`, если оболочка ждет ввода в приглашении.
+/// С интеграцией оболочки известна текущая директория и выполняется ли команда,
+/// поэтому cd не печатается поверх запущенной программы и не повторяется впустую.
+#[tauri::command]
+pub async fn change_directory(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+    path: String,
+) -> Result<(), String> {
+    let session = state.get(session_id.as_deref()).await?;
+    {
+        let screen = session.screen.lock().unwrap();
+        if screen.cwd().is_some_and(|cwd| std::path::Path::new(&cwd) == std::path::Path::new(&path)) {
+            return Ok(());
+        }
+        if screen.is_running_command() {
+            return Err("В терминале выполняется команда, смена директории невозможна".to_string());
+        }
+    }
+    let command = session.shell.cd_command(&path);
+
+    session
+        .write_command(&command)
+        .await
+        .map_err(|e| format!("Failed to change directory: {}", e))
+}
+
+#[tauri::command]
+pub async fn clear_terminal(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+) -> Result<(), String> {
+    let session = state.get(session_id.as_deref()).await?;
+    session
+        .write_command(session.shell.clear_command())
+        .await
+        .map_err(|e| format!("Failed to clear terminal: {}", e))
+}
+
+/// Текущая директория оболочки сессии
+#[tauri::command]
+pub async fn get_terminal_cwd(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+) -> Result<Option<String>, String> {
+    Ok(state.get(session_id.as_deref()).await?.current_dir())
+}
+
+/// Ссылки на существующие файлы в строке вывода: `src/foo.rs:12:5`,
+/// `File "x.py", line 3`, `file:///...`. Относительные пути ищутся в `cwd`
+/// (по умолчанию - текущая директория оболочки), затем в корне проекта.
+#[tauri::command]
+pub async fn resolve_terminal_links(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+    line: String,
+    cwd: Option<String>,
+) -> Result<Vec<TerminalLink>, String> {
+    let session = state.get(session_id.as_deref()).await?;
+    let base_dirs: Vec<std::path::PathBuf> = [cwd.or_else(|| session.current_dir()), session.project_root.clone()]
+        .into_iter()
+        .flatten()
+        .map(std::path::PathBuf::from)
+        .collect();
+    Ok(links::find_links(&line, &base_dirs))
+}
+
+/// Команды, выполненные в сессии (нужна интеграция оболочки)
+#[tauri::command]
+pub async fn get_terminal_commands(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+) -> Result<Vec<CommandRecord>, String> {
+    let session = state.get(session_id.as_deref()).await?;
+    let commands = session.screen.lock().unwrap().commands();
+    Ok(commands)
+}
+
+/// Текст вывода одной команды из истории сессии
+#[tauri::command]
+pub async fn get_terminal_command_output(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+    command_id: u64,
+) -> Result<String, String> {
+    let session = state.get(session_id.as_deref()).await?;
+    let output = session.screen.lock().unwrap().command_output(command_id);
+    output.ok_or_else(|| format!("Команда {} не найдена в истории терминала", command_id))
+}
+
+/// Прерывает (Ctrl+C), завершает или убивает процесс переднего плана сессии.
+/// Оболочка при этом продолжает работать.
+#[tauri::command]
+pub async fn kill_process(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+    signal: Option<TerminalSignal>,
+) -> Result<(), String> {
+    let session = state.get(session_id.as_deref()).await?;
+    session.send_signal(signal.unwrap_or_default()).await
+}
+
+/// Список процессов, запущенных в терминальной сессии
+#[tauri::command]
+pub async fn list_terminal_processes(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+) -> Result<Vec<TerminalProcess>, String> {
+    state.get(session_id.as_deref()).await?.processes().await
+}
+
+/// Отправляет сигнал конкретному процессу сессии (например, зависшему dev-серверу)
+#[tauri::command]
+pub async fn signal_terminal_process(
+    state: State<'_, PtyState>,
+    session_id: Option<String>,
+    pid: u32,
+    signal: TerminalSignal,
+) -> Result<(), String> {
+    let session = state.get(session_id.as_deref()).await?;
+    // Не даем трогать процессы, которые не относятся к этому терминалу
+    if !session.processes().await?.iter().any(|p| p.pid == pid) {
+        return Err(format!("Процесс {} не запущен в этом терминале", pid));
+    }
+
+    #[cfg(unix)]
+    {
+        process::signal_pid(pid, signal)
+    }
+    #[cfg(windows)]
+    {
+        if signal == TerminalSignal::Interrupt {
+            return Err("Прерывание отдельного процесса не поддерживается в Windows".to_string());
+        }
+        process::terminate_pid(pid)
+    }
+}
@@ -0,0 +1,170 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    mpsc::{Receiver, RecvTimeoutError},
+};
+use std::time::{Duration, Instant};
+
+// Вывод копится не дольше этого интервала (примерно полкадра при 60 Гц)
+pub const FLUSH_INTERVAL: Duration = Duration::from_millis(8);
+// ...и не больше этого объема в одном событии
+pub const MAX_BATCH_BYTES: usize = 64 * 1024;
+// Количество прочитанных, но еще не обработанных блоков; при переполнении
+// поток чтения блокируется, и процесс в PTY упирается в полный буфер терминала
+pub const READ_QUEUE_CHUNKS: usize = 16;
+// Пороги неподтвержденного фронтендом вывода для управления потоком
+const HIGH_WATERMARK: usize = 512 * 1024;
+const LOW_WATERMARK: usize = 128 * 1024;
+// Фронтенд, который столько не подтверждает вывод, считается потерявшим события:
+// управление потоком выключается до следующего подтверждения
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Инкрементальный UTF-8 декодер: неполная последовательность в конце блока
+/// переносится в следующий вызов вместо превращения в U+FFFD
+#[derive(Default)]
+pub struct Utf8StreamDecoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8StreamDecoder {
+    pub fn decode(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+
+        let mut output = String::with_capacity(self.pending.len());
+        let mut start = 0;
+        loop {
+            match std::str::from_utf8(&self.pending[start..]) {
+                Ok(valid) => {
+                    output.push_str(valid);
+                    start = self.pending.len();
+                    break;
+                }
+                Err(e) => {
+                    let valid_end = start + e.valid_up_to();
+                    if let Ok(valid) = std::str::from_utf8(&self.pending[start..valid_end]) {
+                        output.push_str(valid);
+                    }
+                    match e.error_len() {
+                        // Действительно некорректные байты - заменяем и идем дальше
+                        Some(len) => {
+                            output.push(char::REPLACEMENT_CHARACTER);
+                            start = valid_end + len;
+                        }
+                        // Последовательность обрезана границей блока - ждем продолжения
+                        None => {
+                            start = valid_end;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.pending.drain(..start);
+        output
+    }
+
+    /// Выдает остаток при завершении потока
+    pub fn finish(&mut self) -> String {
+        let rest = String::from_utf8_lossy(&self.pending).to_string();
+        self.pending.clear();
+        rest
+    }
+}
+
+/// Учет вывода, который фронтенд еще не отрисовал.
+/// Включается при первом подтверждении, чтобы старый фронтенд без ack не зависал.
+#[derive(Default)]
+pub struct FlowControl {
+    enabled: AtomicBool,
+    unacked: AtomicUsize,
+}
+
+impl FlowControl {
+    pub fn sent(&self, bytes: usize) {
+        self.unacked.fetch_add(bytes, Ordering::AcqRel);
+    }
+
+    pub fn ack(&self, bytes: usize) {
+        self.enabled.store(true, Ordering::Release);
+        let _ = self
+            .unacked
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |value| Some(value.saturating_sub(bytes)));
+    }
+
+    /// Отключает ожидание (например, при закрытии сессии)
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::Release);
+    }
+
+    /// Начинает учет заново: переподключившийся фронтенд (перезагрузка webview)
+    /// не подтвердит вывод, отправленный до него
+    pub fn reset(&self) {
+        self.enabled.store(false, Ordering::Release);
+        self.unacked.store(0, Ordering::Release);
+    }
+
+    /// Блокирует поток вывода, пока фронтенд не догонит, но не дольше DRAIN_TIMEOUT
+    fn wait_for_drain(&self) {
+        if self.unacked.load(Ordering::Acquire) < HIGH_WATERMARK {
+            return;
+        }
+        let started = Instant::now();
+        while self.enabled.load(Ordering::Acquire) && self.unacked.load(Ordering::Acquire) > LOW_WATERMARK {
+            if started.elapsed() >= DRAIN_TIMEOUT {
+                eprintln!("[terminal] Фронтенд не подтверждает вывод, управление потоком отключено");
+                self.reset();
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
+
+/// Склеивает прочитанные из PTY блоки в события: по времени и по объему.
+/// Возвращается, когда поток чтения закрыл канал и весь вывод отправлен.
+pub fn pump_output<F>(chunks: Receiver<Vec<u8>>, flow: &FlowControl, mut emit: F)
+where
+    F: FnMut(String),
+{
+    let mut decoder = Utf8StreamDecoder::default();
+    let mut batch = String::new();
+    let mut batch_started: Option<Instant> = None;
+
+    let mut flush = |batch: &mut String, batch_started: &mut Option<Instant>| {
+        if !batch.is_empty() {
+            flow.wait_for_drain();
+            flow.sent(batch.len());
+            emit(std::mem::take(batch));
+        }
+        *batch_started = None;
+    };
+
+    loop {
+        let timeout = match batch_started {
+            Some(started) => FLUSH_INTERVAL.saturating_sub(started.elapsed()),
+            None => Duration::from_secs(3600),
+        };
+
+        match chunks.recv_timeout(timeout) {
+            Ok(chunk) => {
+                batch.push_str(&decoder.decode(&chunk));
+                if batch_started.is_none() && !batch.is_empty() {
+                    batch_started = Some(Instant::now());
+                }
+                if batch.len() >= MAX_BATCH_BYTES {
+                    flush(&mut batch, &mut batch_started);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => flush(&mut batch, &mut batch_started),
+            Err(RecvTimeoutError::Disconnected) => {
+                batch.push_str(&decoder.finish());
+                flush(&mut batch, &mut batch_started);
+                break;
+            }
+        }
+
+        if batch_started.is_some_and(|started| started.elapsed() >= FLUSH_INTERVAL) {
+            flush(&mut batch, &mut batch_started);
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Сигнал, который пользователь отправляет процессу в терминале
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TerminalSignal {
+    #[default]
+    Interrupt,
+    Terminate,
+    Kill,
+}
+
+#[cfg(unix)]
+impl TerminalSignal {
+    fn as_libc(&self) -> libc::c_int {
+        match self {
+            TerminalSignal::Interrupt => libc::SIGINT,
+            TerminalSignal::Terminate => libc::SIGTERM,
+            TerminalSignal::Kill => libc::SIGKILL,
+        }
+    }
+}
+
+/// Процесс, запущенный внутри терминальной сессии
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalProcess {
+    pub pid: u32,
+    pub parent_pid: u32,
+    pub name: String,
+    pub command: String,
+    // Процесс входит в группу переднего плана терминала (только Unix)
+    pub foreground: bool,
+}
+
+struct ProcessEntry {
+    pid: u32,
+    parent_pid: u32,
+    group_id: Option<u32>,
+    name: String,
+    command: String,
+}
+
+#[cfg(unix)]
+fn snapshot() -> Result<Vec<ProcessEntry>, String> {
+    // ps есть и на Linux, и на macOS, в отличие от /proc
+    let output = std::process::Command::new("ps")
+        .args(["-A", "-o", "pid=,ppid=,pgid=,args="])
+        .output()
+        .map_err(|e| format!("Не удалось получить список процессов: {}", e))?;
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(pid), Some(parent_pid), Some(group_id)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let (Ok(pid), Ok(parent_pid), Ok(group_id)) = (pid.parse(), parent_pid.parse(), group_id.parse()) else {
+            continue;
+        };
+        let command = parts.collect::<Vec<_>>().join(" ");
+        let name = command
+            .split_whitespace()
+            .next()
+            .map(|program| {
+                std::path::Path::new(program)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| program.to_string())
+            })
+            .unwrap_or_default();
+        entries.push(ProcessEntry {
+            pid,
+            parent_pid,
+            group_id: Some(group_id),
+            name,
+            command,
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(windows)]
+fn snapshot() -> Result<Vec<ProcessEntry>, String> {
+    use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+    use winapi::um::tlhelp32::{CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS};
+
+    let mut entries = Vec::new();
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            return Err("Не удалось получить список процессов".to_string());
+        }
+
+        let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+        let mut ok = Process32FirstW(snapshot, &mut entry);
+        while ok != 0 {
+            let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+            let name = String::from_utf16_lossy(&entry.szExeFile[..len]);
+            entries.push(ProcessEntry {
+                pid: entry.th32ProcessID,
+                parent_pid: entry.th32ParentProcessID,
+                group_id: None,
+                command: name.clone(),
+                name,
+            });
+            ok = Process32NextW(snapshot, &mut entry);
+        }
+        CloseHandle(snapshot);
+    }
+    Ok(entries)
+}
+
+/// Все потомки процесса `root_pid` (без него самого) в порядке обхода в ширину
+pub fn descendants(root_pid: u32, foreground_group: Option<u32>) -> Result<Vec<TerminalProcess>, String> {
+    let entries = snapshot()?;
+    let mut children: HashMap<u32, Vec<&ProcessEntry>> = HashMap::new();
+    for entry in &entries {
+        // На Windows pid переиспользуются, и процесс может оказаться "родителем" самого себя
+        if entry.pid != entry.parent_pid {
+            children.entry(entry.parent_pid).or_default().push(entry);
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut queue = VecDeque::from([root_pid]);
+    while let Some(pid) = queue.pop_front() {
+        for child in children.get(&pid).map(|c| c.as_slice()).unwrap_or_default() {
+            if child.pid == root_pid || result.iter().any(|p: &TerminalProcess| p.pid == child.pid) {
+                continue;
+            }
+            result.push(TerminalProcess {
+                pid: child.pid,
+                parent_pid: child.parent_pid,
+                name: child.name.clone(),
+                command: child.command.clone(),
+                foreground: foreground_group.is_some() && child.group_id == foreground_group,
+            });
+            queue.push_back(child.pid);
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(unix)]
+pub fn signal_group(group_id: u32, signal: TerminalSignal) -> Result<(), String> {
+    if unsafe { libc::killpg(group_id as libc::pid_t, signal.as_libc()) } != 0 {
+        return Err(format!(
+            "Не удалось отправить сигнал группе {}: {}",
+            group_id,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+pub fn signal_pid(pid: u32, signal: TerminalSignal) -> Result<(), String> {
+    if unsafe { libc::kill(pid as libc::pid_t, signal.as_libc()) } != 0 {
+        return Err(format!(
+            "Не удалось отправить сигнал процессу {}: {}",
+            pid,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn terminate_pid(pid: u32) -> Result<(), String> {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::{OpenProcess, TerminateProcess};
+    use winapi::um::winnt::PROCESS_TERMINATE;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_TERMINATE, 0, pid);
+        if handle.is_null() {
+            return Err(format!(
+                "Не удалось открыть процесс {}: {}",
+                pid,
+                std::io::Error::last_os_error()
+            ));
+        }
+        let ok = TerminateProcess(handle, 1);
+        CloseHandle(handle);
+        if ok == 0 {
+            return Err(format!(
+                "Не удалось завершить процесс {}: {}",
+                pid,
+                std::io::Error::last_os_error()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Job Object с оболочкой сессии: все процессы, запущенные из нее,
+/// попадают в job и завершаются вместе с ним
+#[cfg(windows)]
+pub struct JobObject {
+    handle: winapi::um::winnt::HANDLE,
+}
+
+// HANDLE job-объекта можно использовать из любого потока
+#[cfg(windows)]
+unsafe impl Send for JobObject {}
+#[cfg(windows)]
+unsafe impl Sync for JobObject {}
+
+#[cfg(windows)]
+impl JobObject {
+    pub fn for_process(pid: u32) -> Result<JobObject, String> {
+        use winapi::um::handleapi::CloseHandle;
+        use winapi::um::jobapi2::{AssignProcessToJobObject, CreateJobObjectW, SetInformationJobObject};
+        use winapi::um::processthreadsapi::OpenProcess;
+        use winapi::um::winnt::{
+            JobObjectExtendedLimitInformation, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+            JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE, PROCESS_SET_QUOTA, PROCESS_TERMINATE,
+        };
+
+        unsafe {
+            let handle = CreateJobObjectW(std::ptr::null_mut(), std::ptr::null());
+            if handle.is_null() {
+                return Err(format!("Не удалось создать Job Object: {}", std::io::Error::last_os_error()));
+            }
+            let job = JobObject { handle };
+
+            // Если редактор упадет, закрытие handle завершит все процессы терминала
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            SetInformationJobObject(
+                job.handle,
+                JobObjectExtendedLimitInformation,
+                &mut info as *mut _ as *mut _,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            );
+
+            let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid);
+            if process.is_null() {
+                return Err(format!("Не удалось открыть процесс {}: {}", pid, std::io::Error::last_os_error()));
+            }
+            let assigned = AssignProcessToJobObject(job.handle, process);
+            CloseHandle(process);
+            if assigned == 0 {
+                return Err(format!(
+                    "Не удалось добавить процесс {} в Job Object: {}",
+                    pid,
+                    std::io::Error::last_os_error()
+                ));
+            }
+            Ok(job)
+        }
+    }
+
+    /// Завершает все процессы job-а, включая саму оболочку
+    pub fn terminate(&self) {
+        unsafe {
+            winapi::um::jobapi2::TerminateJobObject(self.handle, 1);
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for JobObject {
+    fn drop(&mut self) {
+        unsafe {
+            winapi::um::handleapi::CloseHandle(self.handle);
+        }
+    }
+}
@@ -0,0 +1,412 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::emulator::TerminalEmulator;
+
+// Шаг, с которым поток воспроизведения проверяет остановку и смену скорости
+const REPLAY_TICK: Duration = Duration::from_millis(50);
+
+/// Заголовок файла asciicast v2 (первая строка)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CastHeader {
+    pub version: u32,
+    pub width: u16,
+    pub height: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_time_limit: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+}
+
+/// Событие записи: время от начала в секундах, тип ("o", "i", "r") и данные
+type CastEvent = (f64, String, String);
+
+/// Сведения о записи сессии
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingInfo {
+    pub session_id: String,
+    pub path: String,
+    pub started_at: i64,
+    pub duration_ms: u64,
+    pub events: u64,
+    pub record_input: bool,
+}
+
+/// Запись вывода (и, по желанию, ввода) сессии в файл .cast
+pub struct Recorder {
+    session_id: String,
+    path: PathBuf,
+    writer: BufWriter<File>,
+    started: Instant,
+    started_at: i64,
+    events: u64,
+    record_input: bool,
+}
+
+impl Recorder {
+    pub fn create(
+        session_id: &str,
+        path: &Path,
+        cols: u16,
+        rows: u16,
+        title: &str,
+        shell: &str,
+        record_input: bool,
+    ) -> Result<Recorder, String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Не удалось создать каталог {}: {}", dir.display(), e))?;
+        }
+        let file = File::create(path)
+            .map_err(|e| format!("Не удалось создать файл записи {}: {}", path.display(), e))?;
+
+        let started_at = chrono::Local::now().timestamp();
+        let header = CastHeader {
+            version: 2,
+            width: cols,
+            height: rows,
+            timestamp: Some(started_at),
+            idle_time_limit: None,
+            title: Some(title.to_string()),
+            env: HashMap::from([
+                ("SHELL".to_string(), shell.to_string()),
+                ("TERM".to_string(), "xterm-256color".to_string()),
+            ]),
+        };
+        let mut recorder = Recorder {
+            session_id: session_id.to_string(),
+            path: path.to_path_buf(),
+            writer: BufWriter::new(file),
+            started: Instant::now(),
+            started_at: started_at * 1000,
+            events: 0,
+            record_input,
+        };
+        let line = serde_json::to_string(&header).map_err(|e| e.to_string())?;
+        recorder.write_line(&line)?;
+        Ok(recorder)
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), String> {
+        // Сбрасываем каждую строку: запись нужна и тогда, когда редактор упал
+        writeln!(self.writer, "{}", line)
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| format!("Не удалось записать в {}: {}", self.path.display(), e))
+    }
+
+    fn write_event(&mut self, kind: &str, data: &str) {
+        let time = (self.started.elapsed().as_secs_f64() * 1_000_000.0).round() / 1_000_000.0;
+        let Ok(line) = serde_json::to_string(&(time, kind, data)) else {
+            return;
+        };
+        match self.write_line(&line) {
+            Ok(()) => self.events += 1,
+            Err(e) => eprintln!("[{}] {}", self.session_id, e),
+        }
+    }
+
+    pub fn output(&mut self, data: &str) {
+        if !data.is_empty() {
+            self.write_event("o", data);
+        }
+    }
+
+    pub fn input(&mut self, data: &str) {
+        if self.record_input && !data.is_empty() {
+            self.write_event("i", data);
+        }
+    }
+
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        self.write_event("r", &format!("{}x{}", cols, rows));
+    }
+
+    pub fn info(&self) -> RecordingInfo {
+        RecordingInfo {
+            session_id: self.session_id.clone(),
+            path: self.path.to_string_lossy().to_string(),
+            started_at: self.started_at,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            events: self.events,
+            record_input: self.record_input,
+        }
+    }
+}
+
+/// Путь для записи по умолчанию: <данные приложения>/xeditor/recordings/<сессия>-<время>.cast
+pub fn default_path(session_id: &str) -> PathBuf {
+    let name = format!("{}-{}.cast", session_id, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("xeditor")
+        .join("recordings")
+        .join(name)
+}
+
+/// Читает файл asciicast v2; неизвестные и поврежденные события пропускаются
+fn load_cast(path: &str) -> Result<(CastHeader, Vec<CastEvent>), String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Не удалось прочитать запись {}: {}", path, e))?;
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let header: CastHeader = lines
+        .next()
+        .and_then(|line| serde_json::from_str(line).ok())
+        .ok_or_else(|| format!("{} не является файлом asciicast", path))?;
+    if header.version != 2 {
+        return Err(format!("Версия asciicast {} не поддерживается", header.version));
+    }
+    let events = lines
+        .filter_map(|line| serde_json::from_str::<CastEvent>(line).ok())
+        .collect();
+    Ok((header, events))
+}
+
+fn parse_size(data: &str) -> Option<(u16, u16)> {
+    let (cols, rows) = data.split_once('x')?;
+    Some((cols.trim().parse().ok()?, rows.trim().parse().ok()?))
+}
+
+/// Управление одним воспроизведением
+struct Replay {
+    // f64 в битах: скорость можно менять во время воспроизведения
+    speed: AtomicU64,
+    stopped: AtomicBool,
+}
+
+impl Replay {
+    fn speed(&self) -> f64 {
+        f64::from_bits(self.speed.load(Ordering::Relaxed))
+    }
+}
+
+/// Активные воспроизведения записей, ключ - идентификатор воспроизведения
+pub struct ReplayState {
+    replays: std::sync::Mutex<HashMap<String, Arc<Replay>>>,
+    next_id: AtomicU64,
+}
+
+impl Default for ReplayState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReplayState {
+    pub fn new() -> Self {
+        ReplayState {
+            replays: std::sync::Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    fn get(&self, replay_id: &str) -> Result<Arc<Replay>, String> {
+        self.replays
+            .lock()
+            .unwrap()
+            .get(replay_id)
+            .cloned()
+            .ok_or_else(|| format!("Воспроизведение '{}' не найдено", replay_id))
+    }
+}
+
+/// Ответ replay_recording: размеры нужны фронтенду, чтобы создать терминал до первого вывода
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayInfo {
+    pub replay_id: String,
+    pub width: u16,
+    pub height: u16,
+    pub title: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Payload события `replay-output`
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayOutput {
+    pub replay_id: String,
+    pub data: String,
+}
+
+/// Payload события `replay-resize`
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayResize {
+    pub replay_id: String,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// Payload события `replay-finished`
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayFinished {
+    pub replay_id: String,
+    // true - остановлено командой stop_replay, а не доиграно до конца
+    pub stopped: bool,
+}
+
+fn validate_speed(speed: f64) -> Result<f64, String> {
+    if speed.is_finite() && speed > 0.0 {
+        Ok(speed)
+    } else {
+        Err(format!("Некорректная скорость воспроизведения: {}", speed))
+    }
+}
+
+/// Проигрывает запись событиями `replay-output` и `replay-resize` в реальном времени,
+/// деленном на `speed`. Паузы длиннее `idle_time_limit` секунд сокращаются.
+#[tauri::command]
+pub fn replay_recording(
+    state: State<'_, ReplayState>,
+    app: AppHandle,
+    path: String,
+    speed: Option<f64>,
+    idle_time_limit: Option<f64>,
+) -> Result<ReplayInfo, String> {
+    let (header, events) = load_cast(&path)?;
+    let speed = validate_speed(speed.unwrap_or(1.0))?;
+    let idle_limit = idle_time_limit.or(header.idle_time_limit).unwrap_or(f64::INFINITY);
+
+    let replay_id = format!("replay-{}", state.next_id.fetch_add(1, Ordering::Relaxed));
+    let replay = Arc::new(Replay {
+        speed: AtomicU64::new(speed.to_bits()),
+        stopped: AtomicBool::new(false),
+    });
+    state.replays.lock().unwrap().insert(replay_id.clone(), replay.clone());
+
+    let info = ReplayInfo {
+        replay_id: replay_id.clone(),
+        width: header.width,
+        height: header.height,
+        title: header.title.clone(),
+        duration_ms: events.last().map_or(0, |(time, _, _)| (time * 1000.0) as u64),
+    };
+
+    std::thread::spawn(move || {
+        let mut previous = 0.0;
+        for (time, kind, data) in events {
+            // Ожидание делим на короткие шаги, чтобы смена скорости и остановка действовали сразу
+            let mut remaining = (time - previous).clamp(0.0, idle_limit);
+            previous = time;
+            while remaining > 0.0 && !replay.stopped.load(Ordering::Relaxed) {
+                let step = (remaining / replay.speed()).min(REPLAY_TICK.as_secs_f64());
+                std::thread::sleep(Duration::from_secs_f64(step));
+                remaining -= step * replay.speed();
+            }
+            if replay.stopped.load(Ordering::Relaxed) {
+                break;
+            }
+
+            let replay_id = replay_id.clone();
+            let _ = match kind.as_str() {
+                "o" => app.emit("replay-output", ReplayOutput { replay_id, data }),
+                "r" => match parse_size(&data) {
+                    Some((cols, rows)) => app.emit("replay-resize", ReplayResize { replay_id, cols, rows }),
+                    None => Ok(()),
+                },
+                // Ввод в терминал только для чтения не воспроизводится
+                _ => Ok(()),
+            };
+        }
+
+        let stopped = replay.stopped.load(Ordering::Relaxed);
+        if let Some(state) = app.try_state::<ReplayState>() {
+            state.replays.lock().unwrap().remove(&replay_id);
+        }
+        let _ = app.emit("replay-finished", ReplayFinished { replay_id, stopped });
+    });
+
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn set_replay_speed(state: State<'_, ReplayState>, replay_id: String, speed: f64) -> Result<(), String> {
+    let speed = validate_speed(speed)?;
+    state.get(&replay_id)?.speed.store(speed.to_bits(), Ordering::Relaxed);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_replay(state: State<'_, ReplayState>, replay_id: String) -> Result<(), String> {
+    state.get(&replay_id)?.stopped.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Формат расшифровки записи
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    Text,
+    Html,
+}
+
+/// Расшифровка записи: вывод прогоняется через эмулятор, поэтому
+/// перерисовки строк, прогресс-бары и очистки экрана дают итоговый текст
+#[tauri::command]
+pub fn export_recording(
+    path: String,
+    format: TranscriptFormat,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let (header, events) = load_cast(&path)?;
+    let mut emulator = TerminalEmulator::with_scrollback(header.height, header.width, usize::MAX);
+    for (_, kind, data) in &events {
+        match kind.as_str() {
+            "o" => emulator.process(data.as_bytes()),
+            "r" => {
+                if let Some((cols, rows)) = parse_size(data) {
+                    emulator.resize(rows, cols);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let transcript = match format {
+        TranscriptFormat::Text => emulator.transcript_text(),
+        TranscriptFormat::Html => {
+            let title = header.title.as_deref().unwrap_or("Terminal recording");
+            let title: String = title
+                .chars()
+                .map(|c| match c {
+                    '<' => "&lt;".to_string(),
+                    '>' => "&gt;".to_string(),
+                    '&' => "&amp;".to_string(),
+                    c => c.to_string(),
+                })
+                .collect();
+            format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n\
+                 <body style=\"margin:0;background:#1e1e1e\">\n\
+                 <pre style=\"margin:0;padding:12px;color:#d4d4d4;background:#1e1e1e;\
+                 font-family:Menlo,Consolas,'DejaVu Sans Mono',monospace;font-size:13px\">{}</pre>\n\
+                 </body>\n</html>\n",
+                title,
+                emulator.transcript_html()
+            )
+        }
+    };
+
+    if let Some(output_path) = output_path {
+        std::fs::write(&output_path, &transcript)
+            .map_err(|e| format!("Не удалось сохранить расшифровку {}: {}", output_path, e))?;
+    }
+    Ok(transcript)
+}
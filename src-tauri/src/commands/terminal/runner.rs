@@ -0,0 +1,380 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+
+use super::shell::{self, ShellKind};
+use super::{spawn_session, PtyState, SpawnOptions, DEFAULT_SESSION_ID};
+use crate::commands::workspace_settings;
+
+// Раздел .xeditor/settings.json с настройками запуска файлов
+const SETTINGS_SECTION: &str = "runner";
+// Сессия, в которой выполняется "Запустить текущий файл"
+pub const RUN_SESSION_ID: &str = "run";
+
+/// Программа с аргументами. Поддерживаются подстановки ${file}, ${fileDirname},
+/// ${fileBasename}, ${fileBasenameNoExtension}, ${workspaceFolder} и ${outputFile}.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RunStep {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// Как запускать файлы одного языка
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RunConfig {
+    // Сборка перед запуском (gcc, rustc); запуск выполняется, только если она успешна
+    #[serde(default)]
+    pub build: Option<RunStep>,
+    pub run: RunStep,
+    // По умолчанию - директория файла
+    #[serde(default)]
+    pub cwd: Option<String>,
+}
+
+/// Настройки запуска рабочей области
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RunnerSettings {
+    // Переопределения по языку: "python", "javascript", "typescript", "rust", "go", "shellscript", "c"
+    #[serde(default)]
+    pub languages: HashMap<String, RunConfig>,
+    // Дополнительные расширения, например "pyw" -> "python"
+    #[serde(default)]
+    pub extensions: HashMap<String, String>,
+    // Профиль оболочки для сессии запуска; по умолчанию - профиль проекта
+    #[serde(default)]
+    pub profile_id: Option<String>,
+}
+
+/// Payload события `run-started` и результат run_current_file
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RunInfo {
+    pub session_id: String,
+    pub file: String,
+    pub language: String,
+    pub command: String,
+    pub cwd: String,
+}
+
+/// Значения подстановок для одного запуска
+struct RunContext {
+    file: String,
+    file_dirname: String,
+    file_basename: String,
+    file_basename_no_extension: String,
+    workspace_folder: String,
+    output_file: String,
+}
+
+impl RunContext {
+    fn new(file: &Path, project_root: Option<&Path>) -> RunContext {
+        let file_dirname = file.parent().unwrap_or(Path::new("."));
+        let stem = file
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "main".to_string());
+        // Собранные программы кладем во временный каталог, а не рядом с исходником
+        let output_file = std::env::temp_dir()
+            .join("xeditor-run")
+            .join(format!("{}{}", stem, std::env::consts::EXE_SUFFIX));
+
+        RunContext {
+            file: file.to_string_lossy().to_string(),
+            file_dirname: file_dirname.to_string_lossy().to_string(),
+            file_basename: file
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            file_basename_no_extension: stem,
+            workspace_folder: project_root
+                .unwrap_or(file_dirname)
+                .to_string_lossy()
+                .to_string(),
+            output_file: output_file.to_string_lossy().to_string(),
+        }
+    }
+
+    fn substitute(&self, template: &str) -> String {
+        template
+            .replace("${fileBasenameNoExtension}", &self.file_basename_no_extension)
+            .replace("${fileBasename}", &self.file_basename)
+            .replace("${fileDirname}", &self.file_dirname)
+            .replace("${file}", &self.file)
+            .replace("${workspaceFolder}", &self.workspace_folder)
+            .replace("${outputFile}", &self.output_file)
+    }
+
+    fn step_command(&self, shell: ShellKind, step: &RunStep) -> String {
+        let args: Vec<String> = step.args.iter().map(|arg| self.substitute(arg)).collect();
+        shell.command_line(&self.substitute(&step.program), &args)
+    }
+}
+
+fn step(program: &str, args: &[&str]) -> RunStep {
+    RunStep {
+        program: program.to_string(),
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+    }
+}
+
+fn load_settings(project_root: Option<&str>) -> RunnerSettings {
+    match project_root {
+        Some(root) => workspace_settings::load_section(root, SETTINGS_SECTION).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            RunnerSettings::default()
+        }),
+        None => RunnerSettings::default(),
+    }
+}
+
+fn language_for(file: &Path, settings: &RunnerSettings) -> Option<String> {
+    let extension = file.extension()?.to_string_lossy().to_lowercase();
+    if let Some(language) = settings.extensions.get(&extension) {
+        return Some(language.clone());
+    }
+    let language = match extension.as_str() {
+        "py" | "pyw" => "python",
+        "js" | "mjs" | "cjs" => "javascript",
+        "ts" | "tsx" | "mts" | "cts" => "typescript",
+        "rs" => "rust",
+        "go" => "go",
+        "sh" | "bash" => "shellscript",
+        "c" => "c",
+        _ => return None,
+    };
+    Some(language.to_string())
+}
+
+/// Директории от файла вверх до корня проекта включительно
+fn ancestors_within<'a>(file: &'a Path, project_root: Option<&'a Path>) -> impl Iterator<Item = &'a Path> {
+    let mut reached_root = false;
+    file.ancestors().skip(1).take_while(move |dir| {
+        if reached_root {
+            return false;
+        }
+        reached_root = project_root.is_some_and(|root| *dir == root);
+        true
+    })
+}
+
+// Где обычно создают виртуальное окружение Python
+const VENV_DIRS: [&str; 3] = [".venv", "venv", "env"];
+
+/// Каталог исполняемых файлов виртуального окружения
+pub fn venv_bin(venv: &Path) -> PathBuf {
+    venv.join(if cfg!(windows) { "Scripts" } else { "bin" })
+}
+
+fn venv_python(venv: &Path) -> PathBuf {
+    venv_bin(venv).join(if cfg!(windows) { "python.exe" } else { "python" })
+}
+
+/// Виртуальное окружение Python прямо в директории `dir`
+pub fn venv_in(dir: &Path) -> Option<PathBuf> {
+    VENV_DIRS
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|venv| venv_python(venv).is_file())
+}
+
+/// Python из виртуального окружения проекта, иначе из PATH
+pub fn find_python(file: &Path, project_root: Option<&Path>) -> String {
+    if let Some(venv) = ancestors_within(file, project_root).find_map(venv_in) {
+        return venv_python(&venv).to_string_lossy().to_string();
+    }
+    if let Some(venv) = std::env::var_os("VIRTUAL_ENV") {
+        let python = venv_python(Path::new(&venv));
+        if python.is_file() {
+            return python.to_string_lossy().to_string();
+        }
+    }
+
+    if !cfg!(windows) && shell::find_in_path("python3").is_some() {
+        "python3".to_string()
+    } else {
+        "python".to_string()
+    }
+}
+
+/// tsx из node_modules проекта, иначе через npx
+fn typescript_config(file: &Path, project_root: Option<&Path>) -> RunConfig {
+    let tsx = if cfg!(windows) { "tsx.cmd" } else { "tsx" };
+    for dir in ancestors_within(file, project_root) {
+        let local = dir.join("node_modules").join(".bin").join(tsx);
+        if local.is_file() {
+            return RunConfig {
+                build: None,
+                run: step(&local.to_string_lossy(), &["${file}"]),
+                cwd: None,
+            };
+        }
+    }
+    RunConfig {
+        build: None,
+        run: step("npx", &["--yes", "tsx", "${file}"]),
+        cwd: None,
+    }
+}
+
+/// Имя пакета из [package] в Cargo.toml
+fn package_name(package_dir: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(package_dir.join("Cargo.toml")).ok()?;
+    let manifest: toml::Value = toml::from_str(&content).ok()?;
+    Some(manifest.get("package")?.get("name")?.as_str()?.to_string())
+}
+
+/// cargo run для файла внутри пакета (с --bin/--example по расположению файла),
+/// rustc для отдельного файла
+fn rust_config(file: &Path, project_root: Option<&Path>) -> RunConfig {
+    let package_dir = ancestors_within(file, project_root).find(|dir| dir.join("Cargo.toml").is_file());
+    let Some(package_dir) = package_dir else {
+        return RunConfig {
+            build: Some(step("rustc", &["${file}", "-o", "${outputFile}"])),
+            run: step("${outputFile}", &[]),
+            cwd: None,
+        };
+    };
+
+    let manifest = package_dir.join("Cargo.toml").to_string_lossy().to_string();
+    let mut args = vec!["run".to_string(), "--manifest-path".to_string(), manifest];
+    let relative: Vec<String> = file
+        .strip_prefix(package_dir)
+        .map(|p| p.iter().map(|part| part.to_string_lossy().to_string()).collect())
+        .unwrap_or_default();
+    let stem = file
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let target = match relative.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        // Бинарник src/main.rs называется как пакет; без --bin cargo run не выберет его,
+        // если в пакете есть и другие бинарники
+        ["src", "main.rs"] => package_name(package_dir).map(|name| ("--bin", name)),
+        ["src", "bin", _] => Some(("--bin", stem)),
+        ["src", "bin", name, "main.rs"] => Some(("--bin", name.to_string())),
+        ["examples", _] => Some(("--example", stem)),
+        ["examples", name, "main.rs"] => Some(("--example", name.to_string())),
+        _ => None,
+    };
+    if let Some((kind, name)) = target {
+        args.push(kind.to_string());
+        args.push(name);
+    }
+
+    RunConfig {
+        build: None,
+        run: RunStep {
+            program: "cargo".to_string(),
+            args,
+        },
+        cwd: Some(package_dir.to_string_lossy().to_string()),
+    }
+}
+
+fn default_config(language: &str, file: &Path, project_root: Option<&Path>) -> Option<RunConfig> {
+    let simple = |run: RunStep| RunConfig {
+        build: None,
+        run,
+        cwd: None,
+    };
+    let config = match language {
+        "python" => simple(step(&find_python(file, project_root), &["${file}"])),
+        "javascript" => simple(step("node", &["${file}"])),
+        "typescript" => typescript_config(file, project_root),
+        "rust" => rust_config(file, project_root),
+        "go" => simple(step("go", &["run", "${file}"])),
+        "shellscript" => simple(step("bash", &["${file}"])),
+        "c" => RunConfig {
+            build: Some(step("gcc", &["${file}", "-o", "${outputFile}"])),
+            run: step("${outputFile}", &[]),
+            cwd: None,
+        },
+        _ => return None,
+    };
+    Some(config)
+}
+
+/// Запускает файл в отдельной сессии `run`, заменяя предыдущий запуск.
+/// Код завершения приходит событием `pty-exit` этой сессии.
+#[tauri::command]
+pub async fn run_current_file(
+    state: State<'_, PtyState>,
+    app: AppHandle,
+    file_path: String,
+    project_root: Option<String>,
+) -> Result<RunInfo, String> {
+    let file = Path::new(&file_path);
+    if !file.is_file() {
+        return Err(format!("Файл не найден: {}", file_path));
+    }
+    let root = project_root.as_deref().map(Path::new);
+
+    let settings = load_settings(project_root.as_deref());
+    let language = language_for(file, &settings)
+        .ok_or_else(|| format!("Не известно, как запускать файл {}", file_path))?;
+    let config = match settings.languages.get(&language) {
+        Some(config) => config.clone(),
+        None => default_config(&language, file, root)
+            .ok_or_else(|| format!("Для языка '{}' не настроена команда запуска", language))?,
+    };
+
+    let profile = shell::resolve_profile(project_root.as_deref(), settings.profile_id.as_deref())?;
+    let shell = profile.kind;
+    let context = RunContext::new(file, root);
+
+    let mut command = context.step_command(shell, &config.run);
+    if let Some(build) = &config.build {
+        if let Some(dir) = Path::new(&context.output_file).parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Не удалось создать каталог {}: {}", dir.display(), e))?;
+        }
+        command = shell.and_then(&context.step_command(shell, build), &command);
+    }
+    let cwd = config
+        .cwd
+        .map(|cwd| context.substitute(&cwd))
+        .unwrap_or_else(|| context.file_dirname.clone());
+
+    // Размер берем у основного терминала, чтобы вывод не переформатировался
+    let size = match state.get(Some(DEFAULT_SESSION_ID)).await {
+        Ok(session) => session.master.lock().await.get_size().ok(),
+        Err(_) => None,
+    };
+
+    let info = RunInfo {
+        session_id: RUN_SESSION_ID.to_string(),
+        file: file_path.clone(),
+        language,
+        command: command.clone(),
+        cwd: cwd.clone(),
+    };
+    // Событие отправляем до запуска, чтобы фронтенд успел переключиться на сессию
+    let _ = app.emit("run-started", info.clone());
+
+    let options = SpawnOptions {
+        session_id: RUN_SESSION_ID.to_string(),
+        title: context.file_basename.clone(),
+        profile,
+        cwd: Some(cwd),
+        rows: size.map_or(24, |s| s.rows),
+        cols: size.map_or(80, |s| s.cols),
+        command: Some(command),
+        project_root,
+    };
+    spawn_session(&state, app, options).await?;
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn get_runner_settings(project_root: String) -> Result<RunnerSettings, String> {
+    workspace_settings::load_section(&project_root, SETTINGS_SECTION)
+}
+
+#[tauri::command]
+pub fn save_runner_settings(project_root: String, settings: RunnerSettings) -> Result<(), String> {
+    workspace_settings::save_section(&project_root, SETTINGS_SECTION, &settings)
+}
@@ -0,0 +1,46 @@
+# Интеграция оболочки X-Editor для bash.
+# Сообщает текущую директорию (OSC 7) и границы команд (OSC 133).
+
+# --init-file заменяет ~/.bashrc, поэтому загружаем конфигурацию пользователя сами
+if [ -n "$XEDITOR_SHELL_LOGIN" ]; then
+    unset XEDITOR_SHELL_LOGIN
+    [ -r /etc/profile ] && . /etc/profile
+    if [ -r ~/.bash_profile ]; then
+        . ~/.bash_profile
+    elif [ -r ~/.bash_login ]; then
+        . ~/.bash_login
+    elif [ -r ~/.profile ]; then
+        . ~/.profile
+    fi
+else
+    [ -r ~/.bashrc ] && . ~/.bashrc
+fi
+
+if [ -z "$__xeditor_integration" ]; then
+    __xeditor_integration=1
+
+    __xeditor_precmd() {
+        local ret=$?
+        printf '\033]133;D;%s\007' "$ret"
+        printf '\033]7;file://%s%s\007' "$HOSTNAME" "$PWD"
+        return $ret
+    }
+
+    # Вызывается последним: PROMPT_COMMAND пользователя (starship и т.п.) может переписать PS1
+    __xeditor_update_prompt() {
+        if [[ "$PS1" != *'133;A'* ]]; then
+            PS1="\[\033]133;A\007\]${PS1}\[\033]133;B\007\]"
+        fi
+    }
+
+    # PS0 выводится после ввода команды, перед ее выполнением (bash 4.4+)
+    PS0="${PS0}\033]133;C\007"
+
+    if [[ "$(declare -p PROMPT_COMMAND 2>/dev/null)" == "declare -a"* ]]; then
+        PROMPT_COMMAND=(__xeditor_precmd "${PROMPT_COMMAND[@]}" __xeditor_update_prompt)
+    else
+        __xeditor_user_prompt_command="${PROMPT_COMMAND%;}"
+        PROMPT_COMMAND="__xeditor_precmd${__xeditor_user_prompt_command:+; $__xeditor_user_prompt_command}; __xeditor_update_prompt"
+        unset __xeditor_user_prompt_command
+    fi
+fi
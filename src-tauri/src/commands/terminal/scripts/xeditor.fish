@@ -0,0 +1,29 @@
+# Интеграция оболочки X-Editor для fish.
+# Сообщает текущую директорию (OSC 7) и границы команд (OSC 133).
+
+if not set -q __xeditor_integration
+    set -g __xeditor_integration 1
+
+    function __xeditor_preexec --on-event fish_preexec
+        printf '\e]133;C\a'
+    end
+
+    function __xeditor_postexec --on-event fish_postexec
+        printf '\e]133;D;%s\a' $status
+    end
+
+    function __xeditor_report_cwd --on-variable PWD
+        printf '\e]7;file://%s%s\a' $hostname $PWD
+    end
+
+    if functions -q fish_prompt
+        functions -c fish_prompt __xeditor_original_prompt
+        function fish_prompt
+            printf '\e]133;A\a'
+            __xeditor_original_prompt
+            printf '\e]133;B\a'
+        end
+    end
+
+    __xeditor_report_cwd
+end
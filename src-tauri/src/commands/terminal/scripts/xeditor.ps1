@@ -0,0 +1,42 @@
+# Интеграция оболочки X-Editor для PowerShell.
+# Сообщает текущую директорию (OSC 7) и границы команд (OSC 133).
+
+if (-not $Global:__XEditorIntegration) {
+    $Global:__XEditorIntegration = $true
+    $Global:__XEditorOriginalPrompt = $function:Prompt
+
+    function Global:Prompt {
+        # $? нужно прочитать до любой другой команды
+        $success = $?
+        $exitCode = $Global:LASTEXITCODE
+        $esc = [char]0x1b
+        $bel = [char]0x07
+
+        $code = if ($success) { 0 } elseif ($exitCode) { $exitCode } else { 1 }
+        $result = "$esc]133;D;$code$bel"
+
+        $location = $executionContext.SessionState.Path.CurrentLocation
+        if ($location.Provider.Name -eq 'FileSystem') {
+            $path = $location.ProviderPath.Replace('\', '/')
+            if (-not $path.StartsWith('/')) {
+                $path = "/$path"
+            }
+            $result += "$esc]7;file://$([System.Net.Dns]::GetHostName())$path$bel"
+        }
+
+        $result += "$esc]133;A$bel"
+        $result += & $Global:__XEditorOriginalPrompt
+        $result += "$esc]133;B$bel"
+
+        $Global:LASTEXITCODE = $exitCode
+        $result
+    }
+
+    # Без PSReadLine начало вывода команды не отмечается, но директория и коды завершения работают
+    if (Get-Module -Name PSReadLine) {
+        Set-PSReadLineKeyHandler -Chord Enter -ScriptBlock {
+            [Microsoft.PowerShell.PSConsoleReadLine]::AcceptLine()
+            [Console]::Write("$([char]0x1b)]133;C$([char]0x07)")
+        }
+    }
+}
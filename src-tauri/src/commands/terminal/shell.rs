@@ -0,0 +1,443 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::commands::workspace_settings;
+
+// Раздел .xeditor/settings.json с настройками терминала
+const SETTINGS_SECTION: &str = "terminal";
+
+/// Семейство оболочки - определяет синтаксис команд, которые мы печатаем в PTY
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+    Sh,
+    PowerShell,
+    Cmd,
+}
+
+impl ShellKind {
+    /// Определяет семейство по имени исполняемого файла
+    pub fn from_path(path: &str) -> ShellKind {
+        let name = Path::new(path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match name.as_str() {
+            "bash" => ShellKind::Bash,
+            "zsh" => ShellKind::Zsh,
+            "fish" => ShellKind::Fish,
+            "pwsh" | "powershell" => ShellKind::PowerShell,
+            "cmd" => ShellKind::Cmd,
+            _ => ShellKind::Sh,
+        }
+    }
+
+    pub fn is_windows_shell(&self) -> bool {
+        matches!(self, ShellKind::PowerShell | ShellKind::Cmd)
+    }
+
+    /// Окончание строки, которое имитирует нажатие Enter
+    pub fn newline(&self) -> &'static str {
+        if self.is_windows_shell() {
+            "\r\n"
+        } else {
+            "\r"
+        }
+    }
+
+    /// Аргументы запуска для login/interactive режима
+    pub fn mode_args(&self, login: bool, interactive: bool) -> Vec<String> {
+        let mut args = Vec::new();
+        match self {
+            ShellKind::Bash | ShellKind::Zsh | ShellKind::Sh => {
+                if login {
+                    args.push("-l".to_string());
+                }
+                if interactive {
+                    args.push("-i".to_string());
+                }
+            }
+            ShellKind::Fish => {
+                if login {
+                    args.push("--login".to_string());
+                }
+                if interactive {
+                    args.push("--interactive".to_string());
+                }
+            }
+            ShellKind::PowerShell => {
+                if login && !cfg!(windows) {
+                    // -Login поддерживается только pwsh на Unix и должен идти первым
+                    args.push("-Login".to_string());
+                }
+                if interactive {
+                    args.push("-NoExit".to_string());
+                }
+            }
+            ShellKind::Cmd => {
+                if interactive {
+                    args.push("/K".to_string());
+                }
+            }
+        }
+        args
+    }
+
+    /// Экранирует аргумент для вставки в командную строку этой оболочки
+    pub fn quote(&self, arg: &str) -> String {
+        match self {
+            ShellKind::Bash | ShellKind::Zsh | ShellKind::Sh => {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            }
+            ShellKind::Fish => {
+                format!("'{}'", arg.replace('\\', "\\\\").replace('\'', "\\'"))
+            }
+            ShellKind::PowerShell => format!("'{}'", arg.replace('\'', "''")),
+            // В cmd двойные кавычки внутри аргумента не экранируются, поэтому просто убираем их
+            ShellKind::Cmd => format!("\"{}\"", arg.replace('"', "")),
+        }
+    }
+
+    /// Путь в формате, который ожидает оболочка
+    pub fn native_path(&self, path: &str) -> String {
+        if self.is_windows_shell() && cfg!(windows) {
+            path.replace('/', "\\")
+        } else {
+            path.to_string()
+        }
+    }
+
+    pub fn cd_command(&self, path: &str) -> String {
+        let path = self.quote(&self.native_path(path));
+        match self {
+            ShellKind::PowerShell => format!("Set-Location -LiteralPath {}", path),
+            ShellKind::Cmd => format!("cd /d {}", path),
+            _ => format!("cd -- {}", path),
+        }
+    }
+
+    pub fn clear_command(&self) -> &'static str {
+        match self {
+            ShellKind::PowerShell => "Clear-Host",
+            ShellKind::Cmd => "cls",
+            _ => "clear",
+        }
+    }
+
+    /// Командная строка запуска программы с аргументами
+    pub fn command_line(&self, program: &str, args: &[String]) -> String {
+        let mut parts = vec![self.quote(&self.native_path(program))];
+        parts.extend(args.iter().map(|arg| self.quote(arg)));
+        let line = parts.join(" ");
+        // Без & PowerShell считает строку в кавычках значением, а не командой
+        if *self == ShellKind::PowerShell {
+            format!("& {}", line)
+        } else {
+            line
+        }
+    }
+
+    /// Выполняет `second`, только если `first` завершилась успешно
+    pub fn and_then(&self, first: &str, second: &str) -> String {
+        match self {
+            // В Windows PowerShell 5.1 нет &&
+            ShellKind::PowerShell => format!("{}; if ($LASTEXITCODE -eq 0) {{ {} }}", first, second),
+            ShellKind::Fish => format!("{}; and {}", first, second),
+            _ => format!("{} && {}", first, second),
+        }
+    }
+
+    /// Аргументы для неинтерактивного выполнения команды: оболочка завершится
+    /// вместе с командой и вернет ее код завершения
+    pub fn run_args(&self, login: bool, command: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        match self {
+            ShellKind::Bash | ShellKind::Zsh | ShellKind::Sh | ShellKind::Fish => {
+                // login-оболочка подхватит PATH из профиля пользователя (nvm, pyenv и т.п.)
+                if login {
+                    args.push("-l".to_string());
+                }
+                args.push("-c".to_string());
+                args.push(command.to_string());
+            }
+            ShellKind::PowerShell => {
+                args.push("-NoLogo".to_string());
+                args.push("-Command".to_string());
+                args.push(format!(
+                    "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; {}; exit $LASTEXITCODE",
+                    command
+                ));
+            }
+            ShellKind::Cmd => {
+                args.push("/C".to_string());
+                args.push(format!("chcp 65001 >nul & {}", command));
+            }
+        }
+        args
+    }
+}
+
+/// Профиль оболочки: исполняемый файл и параметры запуска
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellProfile {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub kind: ShellKind,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub login: bool,
+    #[serde(default = "default_true")]
+    pub interactive: bool,
+    // Подключать скрипты интеграции (текущая директория, границы команд)
+    #[serde(default = "default_true")]
+    pub shell_integration: bool,
+    // true для профилей, найденных в системе (их нельзя удалить)
+    #[serde(default)]
+    pub detected: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl ShellProfile {
+    fn detected(id: &str, name: &str, path: &Path, args: Vec<String>) -> ShellProfile {
+        let path_str = path.to_string_lossy().to_string();
+        ShellProfile {
+            id: id.to_string(),
+            name: name.to_string(),
+            kind: ShellKind::from_path(&path_str),
+            path: path_str,
+            args,
+            env: HashMap::new(),
+            cwd: None,
+            login: false,
+            interactive: true,
+            shell_integration: true,
+            detected: true,
+        }
+    }
+
+    /// Полный список аргументов: флаги режима, затем аргументы профиля
+    pub fn command_args(&self) -> Vec<String> {
+        let mut args = self.kind.mode_args(self.login, self.interactive);
+        args.extend(self.args.iter().cloned());
+        args
+    }
+}
+
+/// Настройки терминала рабочей области
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalSettings {
+    #[serde(default)]
+    pub profiles: Vec<ShellProfile>,
+    #[serde(default)]
+    pub default_profile: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellProfilesResponse {
+    pub profiles: Vec<ShellProfile>,
+    pub default_profile_id: Option<String>,
+}
+
+/// Ищет исполняемый файл в PATH
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(not(windows))]
+fn profile_id_for(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "shell".to_string())
+}
+
+#[cfg(windows)]
+fn detect_system_profiles() -> Vec<ShellProfile> {
+    let mut profiles = Vec::new();
+
+    // Переключаем кодовую страницу консоли на UTF-8
+    if let Some(path) = find_in_path("pwsh.exe") {
+        let args = vec!["-Command".to_string(), "chcp 65001".to_string()];
+        profiles.push(ShellProfile::detected("pwsh", "PowerShell 7", &path, args));
+    }
+    let powershell = find_in_path("powershell.exe").unwrap_or_else(|| PathBuf::from("powershell.exe"));
+    let args = vec!["-Command".to_string(), "chcp 65001".to_string()];
+    profiles.push(ShellProfile::detected("powershell", "Windows PowerShell", &powershell, args));
+
+    let cmd = std::env::var_os("ComSpec")
+        .map(PathBuf::from)
+        .or_else(|| find_in_path("cmd.exe"))
+        .unwrap_or_else(|| PathBuf::from("cmd.exe"));
+    profiles.push(ShellProfile::detected("cmd", "Command Prompt", &cmd, vec!["chcp 65001".to_string()]));
+
+    profiles
+}
+
+#[cfg(not(windows))]
+fn detect_system_profiles() -> Vec<ShellProfile> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+
+    // Оболочка пользователя идет первой - она же профиль по умолчанию
+    if let Some(shell) = std::env::var_os("SHELL") {
+        candidates.push(PathBuf::from(shell));
+    }
+
+    if let Ok(content) = std::fs::read_to_string("/etc/shells") {
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            candidates.push(PathBuf::from(line));
+        }
+    }
+
+    // Без /etc/shells (например, в контейнерах) ищем известные оболочки в PATH
+    if candidates.is_empty() {
+        candidates.extend(["bash", "zsh", "fish", "sh"].iter().filter_map(|name| find_in_path(name)));
+    }
+    if candidates.is_empty() {
+        candidates.push(PathBuf::from("/bin/sh"));
+    }
+
+    let mut profiles: Vec<ShellProfile> = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for path in candidates {
+        let name = profile_id_for(&path);
+        // nologin/false в /etc/shells - не интерактивные оболочки
+        if name == "nologin" || name == "false" || !path.is_file() {
+            continue;
+        }
+        // /bin/bash и /usr/bin/bash часто указывают на один и тот же файл
+        let canonical = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if !seen.insert(canonical) {
+            continue;
+        }
+
+        let mut id = name.clone();
+        let mut suffix = 2;
+        while profiles.iter().any(|p| p.id == id) {
+            id = format!("{}-{}", name, suffix);
+            suffix += 1;
+        }
+        profiles.push(ShellProfile::detected(&id, &name, &path, Vec::new()));
+    }
+
+    profiles
+}
+
+fn load_settings(project_root: Option<&str>) -> TerminalSettings {
+    match project_root {
+        Some(root) => workspace_settings::load_section(root, SETTINGS_SECTION).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            TerminalSettings::default()
+        }),
+        None => TerminalSettings::default(),
+    }
+}
+
+/// Системные профили плюс пользовательские профили рабочей области.
+/// Пользовательский профиль с тем же id заменяет системный.
+pub fn all_profiles(project_root: Option<&str>) -> ShellProfilesResponse {
+    let settings = load_settings(project_root);
+    let mut profiles = detect_system_profiles();
+
+    for custom in settings.profiles {
+        match profiles.iter_mut().find(|p| p.id == custom.id) {
+            Some(existing) => *existing = ShellProfile { detected: false, ..custom },
+            None => profiles.push(ShellProfile { detected: false, ..custom }),
+        }
+    }
+
+    let default_profile_id = settings
+        .default_profile
+        .filter(|id| profiles.iter().any(|p| &p.id == id))
+        .or_else(|| profiles.first().map(|p| p.id.clone()));
+
+    ShellProfilesResponse {
+        profiles,
+        default_profile_id,
+    }
+}
+
+/// Находит профиль по id или профиль по умолчанию для проекта
+pub fn resolve_profile(project_root: Option<&str>, profile_id: Option<&str>) -> Result<ShellProfile, String> {
+    let response = all_profiles(project_root);
+    let id = profile_id
+        .map(|s| s.to_string())
+        .or(response.default_profile_id)
+        .ok_or_else(|| "Не найдено ни одной оболочки".to_string())?;
+
+    response
+        .profiles
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("Профиль оболочки '{}' не найден", id))
+}
+
+#[tauri::command]
+pub fn list_shell_profiles(project_root: Option<String>) -> ShellProfilesResponse {
+    all_profiles(project_root.as_deref())
+}
+
+/// Добавляет или обновляет пользовательский профиль рабочей области
+#[tauri::command]
+pub fn save_shell_profile(project_root: String, profile: ShellProfile) -> Result<(), String> {
+    if profile.id.trim().is_empty() {
+        return Err("Идентификатор профиля не может быть пустым".to_string());
+    }
+
+    let mut settings: TerminalSettings = workspace_settings::load_section(&project_root, SETTINGS_SECTION)?;
+    let profile = ShellProfile { detected: false, ..profile };
+    match settings.profiles.iter_mut().find(|p| p.id == profile.id) {
+        Some(existing) => *existing = profile,
+        None => settings.profiles.push(profile),
+    }
+    workspace_settings::save_section(&project_root, SETTINGS_SECTION, &settings)
+}
+
+#[tauri::command]
+pub fn delete_shell_profile(project_root: String, profile_id: String) -> Result<(), String> {
+    let mut settings: TerminalSettings = workspace_settings::load_section(&project_root, SETTINGS_SECTION)?;
+    let before = settings.profiles.len();
+    settings.profiles.retain(|p| p.id != profile_id);
+    if settings.profiles.len() == before {
+        return Err(format!("Пользовательский профиль '{}' не найден", profile_id));
+    }
+    if settings.default_profile.as_deref() == Some(profile_id.as_str()) {
+        settings.default_profile = None;
+    }
+    workspace_settings::save_section(&project_root, SETTINGS_SECTION, &settings)
+}
+
+#[tauri::command]
+pub fn set_default_shell_profile(project_root: String, profile_id: Option<String>) -> Result<(), String> {
+    if let Some(id) = &profile_id {
+        if !all_profiles(Some(&project_root)).profiles.iter().any(|p| &p.id == id) {
+            return Err(format!("Профиль оболочки '{}' не найден", id));
+        }
+    }
+
+    let mut settings: TerminalSettings = workspace_settings::load_section(&project_root, SETTINGS_SECTION)?;
+    settings.default_profile = profile_id;
+    workspace_settings::save_section(&project_root, SETTINGS_SECTION, &settings)
+}
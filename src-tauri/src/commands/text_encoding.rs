@@ -0,0 +1,447 @@
+use encoding_rs::{EncoderResult, Encoding};
+use serde::Serialize;
+
+// Сколько байт смотреть, чтобы узнать UTF-16 без BOM
+const UTF16_SAMPLE_BYTES: usize = 4096;
+// Сколько непредставимых символов перечислять в отчете
+const UNMAPPABLE_REPORT_LIMIT: usize = 20;
+
+// Вторая половина кодовых страниц DOS, которых нет в encoding_rs
+const CP437_HIGH: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+const CP850_HIGH: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜø£Ø×ƒáíóúñÑªº¿®¬½¼¡«»░▒▓│┤ÁÂÀ©╣║╗╝¢¥┐└┴┬├─┼ãÃ╚╔╩╦╠═╬¤ðÐÊËÈıÍÎÏ┘┌█▄¦Ì▀ÓßÔÒõÕµþÞÚÛÙýÝ¯´\u{ad}±‗¾¶§÷¸°¨·¹³²■\u{a0}";
+
+/// Названия из выбора кодировки в нижней панели (ModalEncoding.tsx) и их идентификаторы
+const ENCODINGS: [(&str, &str); 42] = [
+    ("UTF-8", "utf-8"),
+    ("UTF-8 with BOM", "utf-8-bom"),
+    ("UTF-16 LE", "utf-16le"),
+    ("UTF-16 BE", "utf-16be"),
+    ("UTF-32 LE", "utf-32le"),
+    ("UTF-32 BE", "utf-32be"),
+    ("Windows 1252 (CP1252)", "windows-1252"),
+    ("Windows 1251 (CP1251)", "windows-1251"),
+    ("Windows 1250 (CP1250)", "windows-1250"),
+    ("Windows 1253 (CP1253)", "windows-1253"),
+    ("Windows 1254 (CP1254)", "windows-1254"),
+    ("Windows 1255 (CP1255)", "windows-1255"),
+    ("Windows 1256 (CP1256)", "windows-1256"),
+    ("Windows 1257 (CP1257)", "windows-1257"),
+    ("Windows 1258 (CP1258)", "windows-1258"),
+    ("ISO 8859-1 (Latin-1)", "iso-8859-1"),
+    ("ISO 8859-2 (Latin-2)", "iso-8859-2"),
+    ("ISO 8859-3 (Latin-3)", "iso-8859-3"),
+    ("ISO 8859-4 (Latin-4)", "iso-8859-4"),
+    ("ISO 8859-5 (Cyrillic)", "iso-8859-5"),
+    ("ISO 8859-6 (Arabic)", "iso-8859-6"),
+    ("ISO 8859-7 (Greek)", "iso-8859-7"),
+    ("ISO 8859-8 (Hebrew)", "iso-8859-8"),
+    ("ISO 8859-9 (Turkish)", "iso-8859-9"),
+    ("ISO 8859-10 (Nordic)", "iso-8859-10"),
+    ("ISO 8859-13 (Baltic)", "iso-8859-13"),
+    ("ISO 8859-14 (Celtic)", "iso-8859-14"),
+    ("ISO 8859-15 (Latin-9)", "iso-8859-15"),
+    ("ISO 8859-16 (Latin-10)", "iso-8859-16"),
+    ("KOI8-R (Russian)", "koi8-r"),
+    ("KOI8-U (Ukrainian)", "koi8-u"),
+    ("IBM437", "ibm437"),
+    ("IBM850", "ibm850"),
+    ("IBM866", "ibm866"),
+    ("GB2312", "gb2312"),
+    ("GB18030", "gb18030"),
+    ("Big5", "big5"),
+    ("Big5-HKSCS", "big5-hkscs"),
+    ("Shift JIS", "shift_jis"),
+    ("EUC-JP", "euc-jp"),
+    ("EUC-KR", "euc-kr"),
+    ("ISO-2022-JP", "iso-2022-jp"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Utf8,
+    Utf16 { big_endian: bool },
+    Utf32 { big_endian: bool },
+    // Настоящий ISO 8859-1: в encoding_rs (как в браузерах) это windows-1252
+    Latin1,
+    // Однобайтовая кодировка DOS: ASCII и таблица для 0x80..0xFF
+    Dos(&'static str),
+    Web(&'static Encoding),
+}
+
+/// Кодировка текстового файла
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextEncoding {
+    // Название, как в выборе кодировки
+    pub label: &'static str,
+    kind: Kind,
+    // Писать BOM при сохранении
+    pub bom: bool,
+}
+
+/// Символ, который нельзя записать в выбранной кодировке
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmappableChar {
+    pub line: usize,
+    pub column: usize,
+    pub character: String,
+}
+
+/// Результат кодирования текста
+pub struct Encoded {
+    pub bytes: Vec<u8>,
+    // Замененные на '?' символы (первые UNMAPPABLE_REPORT_LIMIT)
+    pub unmappable: Vec<UnmappableChar>,
+    pub unmappable_count: usize,
+}
+
+fn kind_for(id: &str) -> Option<Kind> {
+    let id = id.trim().to_ascii_lowercase();
+    let kind = match id.as_str() {
+        "utf-8" | "utf8" | "utf-8-bom" | "utf8bom" => Kind::Utf8,
+        "utf-16le" | "utf16le" | "utf-16" => Kind::Utf16 { big_endian: false },
+        "utf-16be" | "utf16be" => Kind::Utf16 { big_endian: true },
+        "utf-32le" | "utf32le" | "utf-32" => Kind::Utf32 { big_endian: false },
+        "utf-32be" | "utf32be" => Kind::Utf32 { big_endian: true },
+        "iso-8859-1" | "latin1" | "iso8859-1" => Kind::Latin1,
+        "ibm437" | "cp437" => Kind::Dos(CP437_HIGH),
+        "ibm850" | "cp850" => Kind::Dos(CP850_HIGH),
+        _ => Kind::Web(Encoding::for_label(id.as_bytes())?),
+    };
+    Some(kind)
+}
+
+impl TextEncoding {
+    pub const UTF8: TextEncoding = TextEncoding {
+        label: "UTF-8",
+        kind: Kind::Utf8,
+        bom: false,
+    };
+
+    /// Кодировка по названию из выбора кодировки, идентификатору ("cp1251") или метке WHATWG
+    pub fn from_name(name: &str) -> Result<TextEncoding, String> {
+        let name = name.trim();
+        let entry = ENCODINGS
+            .iter()
+            .find(|(label, id)| label.eq_ignore_ascii_case(name) || id.eq_ignore_ascii_case(name));
+        if let Some((label, id)) = entry {
+            return Ok(TextEncoding {
+                label,
+                kind: kind_for(id).ok_or_else(|| format!("Неизвестная кодировка: {}", name))?,
+                bom: id.starts_with("utf-") && *id != "utf-8",
+            });
+        }
+
+        let kind = kind_for(name).ok_or_else(|| format!("Неизвестная кодировка: {}", name))?;
+        Ok(Self::from_kind(kind, matches!(kind, Kind::Utf16 { .. } | Kind::Utf32 { .. })))
+    }
+
+    fn from_kind(kind: Kind, bom: bool) -> TextEncoding {
+        let label = ENCODINGS
+            .iter()
+            .find(|(_, id)| kind_for(id) == Some(kind) && id.ends_with("-bom") == (bom && kind == Kind::Utf8))
+            .map(|(label, _)| *label)
+            .unwrap_or(match kind {
+                Kind::Web(encoding) => encoding.name(),
+                _ => "UTF-8",
+            });
+        TextEncoding { label, kind, bom }
+    }
+
+    /// Та же кодировка с BOM или без; у однобайтовых кодировок BOM не бывает
+    pub fn with_bom(self, bom: bool) -> TextEncoding {
+        if self.bom_bytes().is_empty() {
+            self
+        } else {
+            Self::from_kind(self.kind, bom)
+        }
+    }
+
+    /// Начинаются ли байты с BOM этой кодировки
+    pub fn has_bom(&self, bytes: &[u8]) -> bool {
+        let bom = self.bom_bytes();
+        !bom.is_empty() && bytes.starts_with(bom)
+    }
+
+    /// BOM этой кодировки; у однобайтовых кодировок пустой
+    pub fn bom_bytes(&self) -> &'static [u8] {
+        match self.kind {
+            Kind::Utf8 => &[0xEF, 0xBB, 0xBF],
+            Kind::Utf16 { big_endian: false } => &[0xFF, 0xFE],
+            Kind::Utf16 { big_endian: true } => &[0xFE, 0xFF],
+            Kind::Utf32 { big_endian: false } => &[0xFF, 0xFE, 0x00, 0x00],
+            Kind::Utf32 { big_endian: true } => &[0x00, 0x00, 0xFE, 0xFF],
+            _ => &[],
+        }
+    }
+
+    /// Декодирует байты; BOM этой кодировки отбрасывается. true - были некорректные последовательности
+    pub fn decode(&self, bytes: &[u8]) -> (String, bool) {
+        let bom = self.bom_bytes();
+        let bytes = if !bom.is_empty() && bytes.starts_with(bom) {
+            &bytes[bom.len()..]
+        } else {
+            bytes
+        };
+
+        match self.kind {
+            Kind::Utf8 => match String::from_utf8(bytes.to_vec()) {
+                Ok(text) => (text, false),
+                Err(_) => (String::from_utf8_lossy(bytes).to_string(), true),
+            },
+            Kind::Utf16 { big_endian } => {
+                let units = bytes.chunks(2).map(|pair| match (pair, big_endian) {
+                    ([high, low], true) => u16::from_be_bytes([*high, *low]),
+                    ([low, high], false) => u16::from_le_bytes([*low, *high]),
+                    // Нечетный последний байт
+                    _ => 0xFFFD,
+                });
+                let mut malformed = bytes.len() % 2 != 0;
+                let text = char::decode_utf16(units)
+                    .map(|c| {
+                        c.unwrap_or_else(|_| {
+                            malformed = true;
+                            char::REPLACEMENT_CHARACTER
+                        })
+                    })
+                    .collect();
+                (text, malformed)
+            }
+            Kind::Utf32 { big_endian } => {
+                let mut malformed = bytes.len() % 4 != 0;
+                let text = bytes
+                    .chunks_exact(4)
+                    .map(|quad| {
+                        let quad = [quad[0], quad[1], quad[2], quad[3]];
+                        let code = if big_endian {
+                            u32::from_be_bytes(quad)
+                        } else {
+                            u32::from_le_bytes(quad)
+                        };
+                        char::from_u32(code).unwrap_or_else(|| {
+                            malformed = true;
+                            char::REPLACEMENT_CHARACTER
+                        })
+                    })
+                    .collect();
+                (text, malformed)
+            }
+            Kind::Latin1 => (bytes.iter().map(|&b| b as char).collect(), false),
+            Kind::Dos(table) => {
+                let high: Vec<char> = table.chars().collect();
+                let text = bytes
+                    .iter()
+                    .map(|&b| if b < 0x80 { b as char } else { high[(b - 0x80) as usize] })
+                    .collect();
+                (text, false)
+            }
+            Kind::Web(encoding) => {
+                let (text, malformed) = encoding.decode_without_bom_handling(bytes);
+                (text.into_owned(), malformed)
+            }
+        }
+    }
+
+    /// Кодирует текст; непредставимые символы заменяются на '?' и перечисляются в результате
+    pub fn encode(&self, text: &str) -> Encoded {
+        let mut bytes = if self.bom { self.bom_bytes().to_vec() } else { Vec::new() };
+        // Позиции (в байтах text) непредставимых символов
+        let mut unmappable: Vec<usize> = Vec::new();
+
+        match self.kind {
+            Kind::Utf8 => bytes.extend_from_slice(text.as_bytes()),
+            Kind::Utf16 { big_endian } => {
+                for unit in text.encode_utf16() {
+                    bytes.extend_from_slice(&if big_endian { unit.to_be_bytes() } else { unit.to_le_bytes() });
+                }
+            }
+            Kind::Utf32 { big_endian } => {
+                for c in text.chars() {
+                    let code = c as u32;
+                    bytes.extend_from_slice(&if big_endian { code.to_be_bytes() } else { code.to_le_bytes() });
+                }
+            }
+            Kind::Latin1 => {
+                for (index, c) in text.char_indices() {
+                    match u8::try_from(c as u32) {
+                        Ok(b) => bytes.push(b),
+                        Err(_) => {
+                            unmappable.push(index);
+                            bytes.push(b'?');
+                        }
+                    }
+                }
+            }
+            Kind::Dos(table) => {
+                for (index, c) in text.char_indices() {
+                    if c.is_ascii() {
+                        bytes.push(c as u8);
+                    } else if let Some(position) = table.chars().position(|t| t == c) {
+                        bytes.push(0x80 + position as u8);
+                    } else {
+                        unmappable.push(index);
+                        bytes.push(b'?');
+                    }
+                }
+            }
+            Kind::Web(encoding) => encode_web(encoding, text, &mut bytes, &mut unmappable),
+        }
+
+        Encoded {
+            bytes,
+            unmappable_count: unmappable.len(),
+            unmappable: unmappable
+                .iter()
+                .take(UNMAPPABLE_REPORT_LIMIT)
+                .map(|&offset| unmappable_at(text, offset))
+                .collect(),
+        }
+    }
+}
+
+fn encode_web(encoding: &'static Encoding, text: &str, bytes: &mut Vec<u8>, unmappable: &mut Vec<usize>) {
+    let mut encoder = encoding.new_encoder();
+    let mut buffer = [0u8; 8192];
+    let mut consumed = 0;
+    loop {
+        let (result, read, written) =
+            encoder.encode_from_utf8_without_replacement(&text[consumed..], &mut buffer, true);
+        bytes.extend_from_slice(&buffer[..written]);
+        consumed += read;
+        match result {
+            EncoderResult::InputEmpty => break,
+            EncoderResult::OutputFull => {}
+            EncoderResult::Unmappable(c) => {
+                unmappable.push(consumed - c.len_utf8());
+                // Замену пропускаем через кодировщик: у ISO-2022-JP есть состояние
+                let (_, _, written) = encoder.encode_from_utf8_without_replacement("?", &mut buffer, false);
+                bytes.extend_from_slice(&buffer[..written]);
+            }
+        }
+    }
+}
+
+fn unmappable_at(text: &str, offset: usize) -> UnmappableChar {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    UnmappableChar {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        character: text[offset..].chars().next().map(|c| c.to_string()).unwrap_or_default(),
+    }
+}
+
+fn is_cyrillic(c: char) -> bool {
+    matches!(c, 'а'..='я' | 'А'..='Я' | 'ё' | 'Ё')
+}
+
+/// Баллы за "похожесть на русский текст" по парам соседних букв. В верной кодировке
+/// русские слова - это строчные буквы подряд; в неверной внутри слов появляются
+/// заглавные, а кириллица перемешивается с латиницей.
+fn cyrillic_score(text: &str) -> i64 {
+    let mut score = 0;
+    let mut previous = ' ';
+    for c in text.chars() {
+        match (is_cyrillic(previous), is_cyrillic(c)) {
+            (true, true) if c.is_lowercase() => score += 1,
+            (true, true) => score -= 1,
+            (true, false) if c.is_ascii_alphabetic() => score -= 1,
+            (false, true) if previous.is_ascii_alphabetic() => score -= 1,
+            _ => {}
+        }
+        previous = c;
+    }
+    score
+}
+
+/// UTF-16 без BOM: в тексте на латинице каждый второй байт нулевой
+fn guess_utf16(bytes: &[u8]) -> Option<Kind> {
+    let sample = &bytes[..bytes.len().min(UTF16_SAMPLE_BYTES) & !1];
+    if sample.len() < 4 {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let zeros_even = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+    let zeros_odd = sample.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+    if zeros_odd * 10 > pairs * 3 && zeros_even * 20 < pairs {
+        Some(Kind::Utf16 { big_endian: false })
+    } else if zeros_even * 10 > pairs * 3 && zeros_odd * 20 < pairs {
+        Some(Kind::Utf16 { big_endian: true })
+    } else {
+        None
+    }
+}
+
+/// Определяет кодировку: BOM, UTF-16 без BOM, UTF-8, затем русские однобайтовые
+/// кодировки по частоте букв; если ничего не подошло - windows-1252
+pub fn detect(bytes: &[u8]) -> Result<TextEncoding, String> {
+    let boms = [
+        ([0xFF, 0xFE, 0x00, 0x00].as_slice(), Kind::Utf32 { big_endian: false }),
+        ([0x00, 0x00, 0xFE, 0xFF].as_slice(), Kind::Utf32 { big_endian: true }),
+        ([0xEF, 0xBB, 0xBF].as_slice(), Kind::Utf8),
+        ([0xFF, 0xFE].as_slice(), Kind::Utf16 { big_endian: false }),
+        ([0xFE, 0xFF].as_slice(), Kind::Utf16 { big_endian: true }),
+    ];
+    for (bom, kind) in boms {
+        if bytes.starts_with(bom) {
+            return Ok(TextEncoding::from_kind(kind, true));
+        }
+    }
+    if let Some(kind) = guess_utf16(bytes) {
+        return Ok(TextEncoding::from_kind(kind, false));
+    }
+
+    if bytes.contains(&0) {
+        return Err("Файл является двоичным".to_string());
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return Ok(TextEncoding::UTF8);
+    }
+
+    let candidates = ["windows-1251", "koi8-r", "ibm866"];
+    let best = candidates
+        .iter()
+        .filter_map(|id| TextEncoding::from_name(id).ok())
+        .map(|encoding| (cyrillic_score(&encoding.decode(bytes).0), encoding))
+        .max_by_key(|(score, _)| *score);
+    match best {
+        Some((score, encoding)) if score > 0 => Ok(encoding),
+        _ => TextEncoding::from_name("windows-1252"),
+    }
+}
+
+/// Кодировка по началу файла: символ UTF-8, разрезанный концом выборки, не мешает определению
+pub fn detect_sample(head: &[u8]) -> Result<TextEncoding, String> {
+    let sample = match std::str::from_utf8(head) {
+        Err(error) if error.error_len().is_none() => &head[..error.valid_up_to()],
+        _ => head,
+    };
+    detect(sample)
+}
+
+/// Результат проверки текста перед сохранением в кодировке
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodingCheck {
+    pub encoding: String,
+    pub unmappable_count: usize,
+    pub unmappable: Vec<UnmappableChar>,
+}
+
+/// Кодировки, в которых можно открыть и сохранить файл
+#[tauri::command]
+pub fn get_supported_encodings() -> Vec<String> {
+    ENCODINGS.iter().map(|(label, _)| label.to_string()).collect()
+}
+
+/// Какие символы текста не представимы в кодировке - для предупреждения до сохранения
+#[tauri::command]
+pub fn check_encoding(content: String, encoding: String) -> Result<EncodingCheck, String> {
+    let encoding = TextEncoding::from_name(&encoding)?;
+    let encoded = encoding.encode(&content);
+    Ok(EncodingCheck {
+        encoding: encoding.label.to_string(),
+        unmappable_count: encoded.unmappable_count,
+        unmappable: encoded.unmappable,
+    })
+}
@@ -0,0 +1,447 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+// Журнал удаленных в корзину элементов читается и переписывается целиком
+static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Элемент, который редактор переместил в корзину
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedItem {
+    pub id: String,
+    pub original_path: String,
+    // Где элемент лежит в корзине
+    pub trashed_path: String,
+    // .trashinfo (freedesktop) или $I-файл (Windows)
+    pub info_path: Option<String>,
+    // мс с начала эпохи Unix
+    pub deleted_at: i64,
+    pub is_directory: bool,
+}
+
+fn journal_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("xeditor")
+        .join("trash.json")
+}
+
+fn load_journal() -> Vec<TrashedItem> {
+    std::fs::read_to_string(journal_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_journal(items: &[TrashedItem]) -> Result<(), String> {
+    let path = journal_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Не удалось создать {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(items).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("Не удалось сохранить {}: {}", path.display(), e))
+}
+
+/// "name.txt" -> "name.2.txt": свободное имя в корзине
+fn numbered_name(name: &str, number: usize) -> String {
+    if number < 2 {
+        return name.to_string();
+    }
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => format!("{}.{}.{}", stem, number, extension),
+        _ => format!("{}.{}", name, number),
+    }
+}
+
+/// Корзина по спецификации freedesktop.org (Linux, BSD)
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::fs::{self, OpenOptions};
+    use std::io::{ErrorKind, Write};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+    use std::path::{Path, PathBuf};
+
+    use super::numbered_name;
+
+    pub struct Trashed {
+        pub trashed_path: PathBuf,
+        pub info_path: Option<PathBuf>,
+    }
+
+    // Символы, которые не нужно кодировать в Path= (RFC 2396, как у URL)
+    fn is_unreserved(byte: u8) -> bool {
+        byte.is_ascii_alphanumeric() || b"-_.!~*'()/".contains(&byte)
+    }
+
+    fn encode_path(path: &Path) -> String {
+        path.as_os_str()
+            .as_bytes()
+            .iter()
+            .map(|&byte| {
+                if is_unreserved(byte) {
+                    (byte as char).to_string()
+                } else {
+                    format!("%{:02X}", byte)
+                }
+            })
+            .collect()
+    }
+
+    fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+        fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)
+    }
+
+    /// Верхний каталог файловой системы, на которой лежит `path`
+    fn topdir(path: &Path, device: u64) -> PathBuf {
+        let mut top = path.parent().unwrap_or(Path::new("/")).to_path_buf();
+        while let Some(parent) = top.parent() {
+            if fs::metadata(parent).map_or(true, |metadata| metadata.dev() != device) {
+                break;
+            }
+            top = parent.to_path_buf();
+        }
+        top
+    }
+
+    /// Корзина для файла и путь, который записывается в .trashinfo
+    fn trash_dir_for(path: &Path) -> Result<(PathBuf, PathBuf), String> {
+        let device = fs::symlink_metadata(path).map_err(|e| e.to_string())?.dev();
+        let data_dir = dirs::data_dir().ok_or("Не найден каталог данных пользователя")?;
+        let home_trash = data_dir.join("Trash");
+        create_private_dir(&home_trash).map_err(|e| format!("Не удалось создать корзину: {}", e))?;
+        if fs::metadata(&home_trash).is_ok_and(|metadata| metadata.dev() == device) {
+            return Ok((home_trash, path.to_path_buf()));
+        }
+
+        // Файл на другом разделе: корзина в его корне, чтобы не копировать данные
+        let top = topdir(path, device);
+        let uid = unsafe { libc::getuid() };
+        let shared = top.join(".Trash");
+        let shared_ok = fs::symlink_metadata(&shared).is_ok_and(|metadata| {
+            metadata.is_dir() && !metadata.file_type().is_symlink() && metadata.permissions().mode() & 0o1000 != 0
+        });
+        let candidates = [
+            shared_ok.then(|| shared.join(uid.to_string())),
+            Some(top.join(format!(".Trash-{}", uid))),
+        ];
+        for dir in candidates.into_iter().flatten() {
+            if create_private_dir(&dir).is_ok() {
+                let relative = path.strip_prefix(&top).map(Path::to_path_buf).unwrap_or_else(|_| path.to_path_buf());
+                return Ok((dir, relative));
+            }
+        }
+        Err(format!("Для раздела {} нет доступной корзины", top.display()))
+    }
+
+    pub fn move_to_trash(path: &Path) -> Result<Trashed, String> {
+        let (trash, info_name) = trash_dir_for(path)?;
+        let files = trash.join("files");
+        let info = trash.join("info");
+        for dir in [&files, &info] {
+            create_private_dir(dir).map_err(|e| format!("Не удалось создать {}: {}", dir.display(), e))?;
+        }
+
+        let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let deletion_date = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S");
+        for number in 1.. {
+            let candidate = numbered_name(&name, number);
+            // Файл .trashinfo создается первым и с O_EXCL: так имя резервируется атомарно
+            let info_path = info.join(format!("{}.trashinfo", candidate));
+            let mut info_file = match OpenOptions::new().write(true).create_new(true).open(&info_path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(format!("Не удалось записать {}: {}", info_path.display(), e)),
+            };
+            let trashed_path = files.join(&candidate);
+            if trashed_path.symlink_metadata().is_ok() {
+                let _ = fs::remove_file(&info_path);
+                continue;
+            }
+            let written = write!(
+                info_file,
+                "[Trash Info]\nPath={}\nDeletionDate={}\n",
+                encode_path(&info_name),
+                deletion_date
+            );
+            let moved = written.and_then(|_| fs::rename(path, &trashed_path));
+            if let Err(e) = moved {
+                let _ = fs::remove_file(&info_path);
+                return Err(format!("Не удалось переместить в корзину: {}", e));
+            }
+            return Ok(Trashed {
+                trashed_path,
+                info_path: Some(info_path),
+            });
+        }
+        unreachable!()
+    }
+
+    pub fn restore(trashed_path: &Path, info_path: Option<&Path>, original: &Path) -> Result<(), String> {
+        fs::rename(trashed_path, original).map_err(|e| format!("Не удалось восстановить из корзины: {}", e))?;
+        if let Some(info_path) = info_path {
+            let _ = fs::remove_file(info_path);
+        }
+        Ok(())
+    }
+}
+
+/// Корзина macOS (~/.Trash)
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::path::{Path, PathBuf};
+
+    use super::numbered_name;
+
+    pub struct Trashed {
+        pub trashed_path: PathBuf,
+        pub info_path: Option<PathBuf>,
+    }
+
+    pub fn move_to_trash(path: &Path) -> Result<Trashed, String> {
+        let trash = dirs::home_dir().ok_or("Не найден домашний каталог")?.join(".Trash");
+        std::fs::create_dir_all(&trash).map_err(|e| format!("Не удалось создать корзину: {}", e))?;
+        let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let trashed_path = (1..)
+            .map(|number| trash.join(numbered_name(&name, number)))
+            .find(|candidate| candidate.symlink_metadata().is_err())
+            .unwrap_or_default();
+        std::fs::rename(path, &trashed_path).map_err(|e| format!("Не удалось переместить в корзину: {}", e))?;
+        Ok(Trashed {
+            trashed_path,
+            info_path: None,
+        })
+    }
+
+    pub fn restore(trashed_path: &Path, _info_path: Option<&Path>, original: &Path) -> Result<(), String> {
+        std::fs::rename(trashed_path, original).map_err(|e| format!("Не удалось восстановить из корзины: {}", e))
+    }
+}
+
+/// Корзина Windows: удаление через оболочку с FOF_ALLOWUNDO, затем поиск $I/$R-файлов
+#[cfg(windows)]
+mod platform {
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+
+    pub struct Trashed {
+        pub trashed_path: PathBuf,
+        pub info_path: Option<PathBuf>,
+    }
+
+    /// Исходный путь и время удаления (FILETIME) из $I-файла
+    fn parse_info(bytes: &[u8]) -> Option<(String, u64)> {
+        let version = u64::from_le_bytes(bytes.get(0..8)?.try_into().ok()?);
+        let deleted = u64::from_le_bytes(bytes.get(16..24)?.try_into().ok()?);
+        let name = match version {
+            // Vista - 8.1: путь фиксированной длины MAX_PATH
+            1 => bytes.get(24..24 + 520)?,
+            _ => {
+                let length = u32::from_le_bytes(bytes.get(24..28)?.try_into().ok()?) as usize;
+                bytes.get(28..28 + length * 2)?
+            }
+        };
+        let units: Vec<u16> = name
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .take_while(|&unit| unit != 0)
+            .collect();
+        Some((String::from_utf16_lossy(&units), deleted))
+    }
+
+    /// Самая свежая запись корзины диска для `original`
+    fn find_entry(original: &Path) -> Option<(PathBuf, PathBuf)> {
+        let root = original.ancestors().last()?.join("$Recycle.Bin");
+        let original = original.to_string_lossy().to_lowercase();
+        let mut best: Option<(u64, PathBuf)> = None;
+        for sid in std::fs::read_dir(root).ok()?.flatten() {
+            let Ok(entries) = std::fs::read_dir(sid.path()) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                if !entry.file_name().to_string_lossy().starts_with("$I") {
+                    continue;
+                }
+                let Some((path, deleted)) = std::fs::read(entry.path()).ok().and_then(|bytes| parse_info(&bytes)) else {
+                    continue;
+                };
+                if path.to_lowercase() == original && best.as_ref().map_or(true, |(time, _)| deleted > *time) {
+                    best = Some((deleted, entry.path()));
+                }
+            }
+        }
+        let info = best?.1;
+        let name = info.file_name()?.to_string_lossy().replacen("$I", "$R", 1);
+        Some((info.with_file_name(name), info))
+    }
+
+    /// Есть ли корзина на диске `path` (на съемных и сетевых дисках ее обычно нет)
+    fn has_recycle_bin(path: &Path) -> bool {
+        use winapi::um::shellapi::{SHQueryRecycleBinW, SHQUERYRBINFO};
+
+        let Some(root) = path.ancestors().last() else {
+            return false;
+        };
+        let mut root: Vec<u16> = root.as_os_str().encode_wide().collect();
+        root.push(0);
+        let mut info: SHQUERYRBINFO = unsafe { std::mem::zeroed() };
+        info.cbSize = std::mem::size_of::<SHQUERYRBINFO>() as u32;
+        unsafe { SHQueryRecycleBinW(root.as_ptr(), &mut info) >= 0 }
+    }
+
+    pub fn move_to_trash(path: &Path) -> Result<Trashed, String> {
+        use winapi::um::shellapi::{
+            SHFileOperationW, FOF_ALLOWUNDO, FOF_NOCONFIRMATION, FOF_NOERRORUI, FOF_SILENT, FOF_WANTNUKEWARNING, FO_DELETE,
+            SHFILEOPSTRUCTW,
+        };
+
+        let absolute = std::path::absolute(path).map_err(|e| e.to_string())?;
+        if !has_recycle_bin(&absolute) {
+            return Err(format!(
+                "На диске нет корзины, {} можно только удалить безвозвратно",
+                absolute.display()
+            ));
+        }
+        // Список путей, завершенный двумя нулями
+        let mut from: Vec<u16> = absolute.as_os_str().encode_wide().collect();
+        from.extend([0, 0]);
+        let mut operation: SHFILEOPSTRUCTW = unsafe { std::mem::zeroed() };
+        operation.wFunc = FO_DELETE as u32;
+        operation.pFrom = from.as_ptr();
+        // Без FOF_WANTNUKEWARNING оболочка молча удаляет безвозвратно то, что не помещается в корзину;
+        // с ним спрашивает пользователя, и отказ приходит как прерванная операция
+        operation.fFlags = (FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT | FOF_WANTNUKEWARNING) as u16;
+        let result = unsafe { SHFileOperationW(&mut operation) };
+        if operation.fAnyOperationsAborted != 0 {
+            return Err(format!(
+                "{} не помещается в корзину, его можно только удалить безвозвратно",
+                absolute.display()
+            ));
+        }
+        if result != 0 {
+            return Err(format!("Не удалось переместить в корзину (код {})", result));
+        }
+
+        // Пользователь согласился на безвозвратное удаление в окне оболочки
+        let (trashed_path, info_path) =
+            find_entry(&absolute).ok_or("Элемент удален безвозвратно: корзина не смогла его принять")?;
+        Ok(Trashed {
+            trashed_path,
+            info_path: Some(info_path),
+        })
+    }
+
+    pub fn restore(trashed_path: &Path, info_path: Option<&Path>, original: &Path) -> Result<(), String> {
+        std::fs::rename(trashed_path, original).map_err(|e| format!("Не удалось восстановить из корзины: {}", e))?;
+        if let Some(info_path) = info_path {
+            let _ = std::fs::remove_file(info_path);
+        }
+        Ok(())
+    }
+}
+
+/// Перемещает файл или папку в корзину ОС и запоминает, откуда
+pub fn move_to_trash(path: &Path) -> Result<TrashedItem, String> {
+    let metadata = path
+        .symlink_metadata()
+        .map_err(|_| format!("Путь не является файлом или директорией: {}", path.display()))?;
+    let original = std::path::absolute(path).map_err(|e| e.to_string())?;
+    let trashed = platform::move_to_trash(&original)?;
+
+    let item = TrashedItem {
+        id: format!(
+            "{}-{}",
+            chrono::Utc::now().timestamp_millis(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ),
+        original_path: original.to_string_lossy().to_string(),
+        trashed_path: trashed.trashed_path.to_string_lossy().to_string(),
+        info_path: trashed.info_path.map(|path| path.to_string_lossy().to_string()),
+        deleted_at: chrono::Utc::now().timestamp_millis(),
+        is_directory: metadata.is_dir(),
+    };
+    let _lock = JOURNAL_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut journal = load_journal();
+    journal.push(item.clone());
+    // Элемент уже в корзине: ошибка журнала не должна выглядеть как неудачное удаление
+    if let Err(e) = save_journal(&journal) {
+        eprintln!("[trash] {}", e);
+    }
+    Ok(item)
+}
+
+/// Возвращает элемент из корзины на прежнее место
+pub fn restore(id: &str) -> Result<TrashedItem, String> {
+    let _lock = JOURNAL_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut journal = load_journal();
+    let index = journal
+        .iter()
+        .position(|item| item.id == id)
+        .ok_or_else(|| format!("Элемент корзины не найден: {}", id))?;
+    let item = journal[index].clone();
+
+    let trashed = Path::new(&item.trashed_path);
+    if trashed.symlink_metadata().is_err() {
+        journal.remove(index);
+        save_journal(&journal)?;
+        return Err(format!("'{}' больше нет в корзине", item.original_path));
+    }
+    let original = Path::new(&item.original_path);
+    if original.symlink_metadata().is_ok() {
+        return Err(format!("На месте '{}' уже есть другой файл", item.original_path));
+    }
+    if let Some(parent) = original.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Не удалось создать {}: {}", parent.display(), e))?;
+    }
+    platform::restore(trashed, item.info_path.as_deref().map(Path::new), original)?;
+
+    journal.remove(index);
+    save_journal(&journal)?;
+    Ok(item)
+}
+
+/// Удаляет файл или папку с диска безвозвратно
+pub fn remove_permanently(path: &Path) -> Result<(), String> {
+    let metadata = path
+        .symlink_metadata()
+        .map_err(|_| format!("Путь не является файлом или директорией: {}", path.display()))?;
+    if metadata.is_dir() {
+        std::fs::remove_dir_all(path).map_err(|e| format!("Ошибка при удалении директории: {}", e))
+    } else {
+        std::fs::remove_file(path).map_err(|e| format!("Ошибка при удалении файла: {}", e))
+    }
+}
+
+/// Элементы, удаленные редактором в корзину и еще не удаленные из нее, новые первыми
+#[tauri::command]
+pub fn list_trashed_items() -> Result<Vec<TrashedItem>, String> {
+    let _lock = JOURNAL_LOCK.lock().map_err(|e| e.to_string())?;
+    let journal = load_journal();
+    // Корзину могли очистить средствами ОС
+    let (present, gone): (Vec<TrashedItem>, Vec<TrashedItem>) = journal
+        .into_iter()
+        .partition(|item| Path::new(&item.trashed_path).symlink_metadata().is_ok());
+    if !gone.is_empty() {
+        save_journal(&present)?;
+    }
+    let mut items = present;
+    items.reverse();
+    Ok(items)
+}
+
+#[tauri::command]
+pub fn restore_trashed_item(id: String) -> Result<TrashedItem, String> {
+    restore(&id)
+}
+
+/// Безвозвратное удаление. `confirmed` - пользователь явно подтвердил, что корзина не нужна
+#[tauri::command]
+pub fn delete_permanently(path: String, confirmed: bool) -> Result<(), String> {
+    if !confirmed {
+        return Err("Безвозвратное удаление требует подтверждения".to_string());
+    }
+    remove_permanently(Path::new(&path))
+}
@@ -0,0 +1,52 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Настройки рабочей области хранятся в корне проекта, по аналогии с .vscode/settings.json
+pub const SETTINGS_DIR: &str = ".xeditor";
+pub const SETTINGS_FILE: &str = "settings.json";
+
+pub fn settings_path(project_root: &str) -> PathBuf {
+    Path::new(project_root).join(SETTINGS_DIR).join(SETTINGS_FILE)
+}
+
+fn read_settings(project_root: &str) -> Result<serde_json::Value, String> {
+    let path = settings_path(project_root);
+    if !path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Не удалось прочитать {}: {}", path.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Некорректный JSON в {}: {}", path.display(), e))
+}
+
+/// Загружает раздел настроек рабочей области (например, "terminal").
+/// Отсутствующий файл или раздел дает значение по умолчанию.
+pub fn load_section<T: DeserializeOwned + Default>(project_root: &str, section: &str) -> Result<T, String> {
+    let settings = read_settings(project_root)?;
+    match settings.get(section) {
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| format!("Некорректный раздел '{}' в настройках: {}", section, e)),
+        None => Ok(T::default()),
+    }
+}
+
+/// Сохраняет раздел настроек, не трогая остальные разделы файла
+pub fn save_section<T: Serialize>(project_root: &str, section: &str, value: &T) -> Result<(), String> {
+    let mut settings = read_settings(project_root)?;
+    if !settings.is_object() {
+        settings = serde_json::json!({});
+    }
+    settings[section] = serde_json::to_value(value).map_err(|e| e.to_string())?;
+
+    let path = settings_path(project_root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Не удалось создать директорию настроек: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(&path, content)
+        .map_err(|e| format!("Не удалось сохранить {}: {}", path.display(), e))
+}
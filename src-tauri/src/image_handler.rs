@@ -1,9 +1,11 @@
 use base64::{engine::general_purpose, Engine as _};
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use tauri::command;
 
+use crate::atomic_write::{write_atomic, WriteError};
+
 // Функция для определения MIME-типа по расширению файла
 fn get_mime_type(path: &Path) -> String {
     let extension = match path.extension() {
@@ -164,7 +166,7 @@ pub fn get_temp_dir() -> Result<String, String> {
 
 // Функция для записи текстового файла
 #[command]
-pub fn write_text_file(path: &str, content: &str) -> Result<(), String> {
+pub fn write_text_file(path: &str, content: &str) -> Result<(), WriteError> {
     println!("Rust: write_text_file called with path: {}", path);
     
     // Нормализуем путь
@@ -178,25 +180,15 @@ pub fn write_text_file(path: &str, content: &str) -> Result<(), String> {
             if let Err(e) = std::fs::create_dir_all(parent) {
                 let error_msg = format!("Ошибка при создании директории: {}", e);
                 println!("Rust: Error - {}", error_msg);
-                return Err(error_msg);
+                return Err(error_msg.into());
             }
         }
     }
     
-    // Записываем файл
-    let mut file = match File::create(&path_buf) {
-        Ok(f) => f,
-        Err(e) => {
-            let error_msg = format!("Ошибка при создании файла: {}", e);
-            println!("Rust: Error - {}", error_msg);
-            return Err(error_msg);
-        }
-    };
-    
-    if let Err(e) = file.write_all(content.as_bytes()) {
-        let error_msg = format!("Ошибка при записи в файл: {}", e);
-        println!("Rust: Error - {}", error_msg);
-        return Err(error_msg);
+    // Записываем файл атомарно: при сбое на диске остается прежняя версия
+    if let Err(e) = write_atomic(&path_buf, content.as_bytes()) {
+        println!("Rust: Error - {}", e.message());
+        return Err(e);
     }
     
     println!("Rust: Successfully wrote {} bytes to file", content.len());
@@ -1,3 +1,4 @@
+mod atomic_write;
 mod image_handler;
 
 #[tauri::command]
@@ -8,13 +8,20 @@ use tauri::Manager;
 use serde_json;
 
 use tauri::command;
+mod atomic_write;
 mod commands;
 mod types;
 mod reading;
 mod modules;
 
-use std::sync::Arc;
+use commands::tasks::TaskState;
 use commands::terminal::PtyState;
+use commands::terminal::recording::ReplayState;
+use commands::file_watcher::WatcherState;
+use commands::file_transfer::TransferState;
+use commands::directory_listing::DirectoryState;
+use commands::large_file::LargeFileState;
+use commands::fs_journal::{FsJournalState, FsOperation};
 
 // Используем функции из модуля modules.rs
 use crate::modules::{
@@ -108,23 +115,25 @@ fn create_simple_file(path: String) -> Result<(), String> {
     Ok(())
 }
 
+/// Удаление из проводника - в корзину ОС; безвозвратно удаляет trash::delete_permanently
 #[tauri::command]
-fn delete_file(path: String) -> Result<(), String> {
-    let path_obj = std::path::Path::new(&path);
-    
-    if path_obj.is_file() {
-        std::fs::remove_file(path)
-            .map_err(|e| format!("Ошибка при удалении файла: {}", e))
-    } else if path_obj.is_dir() {
-        std::fs::remove_dir_all(path)
-            .map_err(|e| format!("Ошибка при удалении директории: {}", e))
-    } else {
-        Err(format!("Путь не является файлом или директорией: {}", path))
-    }
+fn delete_file(
+    journal: tauri::State<'_, FsJournalState>,
+    path: String,
+    workspace_root: Option<String>,
+) -> Result<commands::trash::TrashedItem, String> {
+    let item = commands::trash::move_to_trash(std::path::Path::new(&path))?;
+    journal.record(workspace_root.as_deref(), FsOperation::trashed(item.clone()));
+    Ok(item)
 }
 
 #[tauri::command]
-fn rename_file(old_path: String, new_path: String) -> Result<(), String> {
+fn rename_file(
+    journal: tauri::State<'_, FsJournalState>,
+    old_path: String,
+    new_path: String,
+    workspace_root: Option<String>,
+) -> Result<(), String> {
     // Normalize paths to use the correct path separators for the current OS
     let old_path_normalized = std::path::Path::new(&old_path);
     let new_path_normalized = std::path::Path::new(&new_path);
@@ -143,6 +152,10 @@ fn rename_file(old_path: String, new_path: String) -> Result<(), String> {
     match std::fs::rename(old_path_normalized, new_path_normalized) {
         Ok(_) => {
             println!("Successfully renamed file");
+            journal.record(
+                workspace_root.as_deref(),
+                FsOperation::renamed(old_path_normalized, new_path_normalized),
+            );
             Ok(())
         },
         Err(e) => {
@@ -241,10 +254,14 @@ fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
-        .manage(PtyState {
-            master: Arc::new(tauri::async_runtime::Mutex::new(None)),
-            writer: Arc::new(tauri::async_runtime::Mutex::new(None)),
-        })
+        .manage(PtyState::new())
+        .manage(TaskState::new())
+        .manage(ReplayState::new())
+        .manage(WatcherState::new())
+        .manage(FsJournalState::new())
+        .manage(TransferState::new())
+        .manage(DirectoryState::new())
+        .manage(LargeFileState::new())
         .invoke_handler(tauri::generate_handler![
             open_in_explorer,
             get_args,
@@ -263,14 +280,75 @@ fn main() {
             commands::file_operations::create_file,
             types::get_directory_tree,
             types::get_subdirectory,
+            commands::directory_listing::list_directory,
+            commands::directory_listing::cancel_directory_listing,
+            commands::directory_listing::invalidate_directory_cache,
             reading::read_text_file,
+            reading::read_text_document,
+            commands::text_encoding::get_supported_encodings,
+            commands::text_encoding::check_encoding,
+            commands::line_endings::convert_line_endings,
+            commands::line_endings::convert_folder_line_endings,
+            commands::file_watcher::watch_workspace,
+            commands::file_watcher::unwatch_workspace,
+            commands::trash::list_trashed_items,
+            commands::trash::restore_trashed_item,
+            commands::trash::delete_permanently,
+            commands::fs_journal::get_file_operation_history,
+            commands::fs_journal::undo_file_operation,
+            commands::fs_journal::redo_file_operation,
+            commands::file_transfer::check_transfer_conflicts,
+            commands::file_transfer::copy_files,
+            commands::file_transfer::move_files,
+            commands::file_transfer::duplicate_file,
+            commands::file_transfer::cancel_file_transfer,
+            commands::file_info::fs_get_file_info,
+            commands::file_info::fs_is_directory,
+            commands::file_info::check_directory_exists,
+            commands::ignore_rules::get_files_settings,
+            commands::ignore_rules::save_files_settings,
+            commands::ignore_rules::get_ignore_status,
             reading::read_binary_file,
+            commands::large_file::open_large_file,
+            commands::large_file::get_large_file_status,
+            commands::large_file::read_large_file_lines,
+            commands::large_file::read_file_range,
+            commands::large_file::search_large_file,
+            commands::large_file::cancel_large_file_search,
+            commands::large_file::follow_large_file,
+            commands::large_file::unfollow_large_file,
+            commands::large_file::close_large_file,
             reading::stream_video,
             commands::terminal::start_process,
             commands::terminal::send_input,
             commands::terminal::kill_process,
             commands::terminal::resize_pty,
             commands::terminal::change_directory,
+            commands::terminal::create_terminal_session,
+            commands::terminal::list_terminal_sessions,
+            commands::terminal::close_terminal_session,
+            commands::terminal::clear_terminal,
+            commands::terminal::restart_terminal_session,
+            commands::terminal::get_terminal_session_status,
+            commands::terminal::ack_pty_output,
+            commands::terminal::list_terminal_processes,
+            commands::terminal::signal_terminal_process,
+            commands::terminal::get_terminal_snapshot,
+            commands::terminal::search_terminal_output,
+            commands::terminal::get_terminal_cwd,
+            commands::terminal::get_terminal_commands,
+            commands::terminal::get_terminal_command_output,
+            commands::terminal::resolve_terminal_links,
+            commands::terminal::start_terminal_recording,
+            commands::terminal::stop_terminal_recording,
+            commands::terminal::recording::replay_recording,
+            commands::terminal::recording::set_replay_speed,
+            commands::terminal::recording::stop_replay,
+            commands::terminal::recording::export_recording,
+            commands::terminal::shell::list_shell_profiles,
+            commands::terminal::shell::save_shell_profile,
+            commands::terminal::shell::delete_shell_profile,
+            commands::terminal::shell::set_default_shell_profile,
             resolve_module_path,
             get_project_root,
             file_exists,
@@ -293,7 +371,20 @@ fn main() {
             commands::fs_commands::editor_get_current_file_path,
             commands::fs_commands::get_all_files_in_directory,
             commands::fonts::get_system_fonts,
-            commands::terminal::run_python_file,
+            commands::terminal::runner::run_current_file,
+            commands::terminal::runner::get_runner_settings,
+            commands::terminal::runner::save_runner_settings,
+            commands::tasks::list_tasks,
+            commands::tasks::run_task,
+            commands::tasks::cancel_task,
+            commands::tasks::list_task_runs,
+            commands::tasks::get_user_tasks,
+            commands::tasks::save_user_tasks,
+            commands::problem_matchers::get_problem_matcher_settings,
+            commands::problem_matchers::save_problem_matcher_settings,
+            commands::project_env::get_project_environment,
+            commands::project_env::get_environment_settings,
+            commands::project_env::save_environment_settings,
         ])
         .setup(|app| {
             let args = std::env::args().collect::<Vec<String>>();
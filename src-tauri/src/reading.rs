@@ -1,25 +1,124 @@
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
 use std::path::Path;
+use std::time::UNIX_EPOCH;
 use tauri::AppHandle;
 
-#[tauri::command]
-pub fn read_text_file(path: String) -> Result<String, String> {
+use crate::commands::line_endings::{self, LineEnding};
+use crate::commands::text_encoding::{self, TextEncoding};
+
+/// Версия файла на диске: по ней сохранение узнает, что файл изменили извне
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileVersion {
+    // Время изменения, мс с начала эпохи Unix
+    pub mtime: u64,
+    pub size: u64,
+    // Хеш содержимого, hex
+    pub hash: String,
+}
+
+impl FileVersion {
+    /// Версия файла с содержимым `bytes`
+    pub fn of(path: &Path, bytes: &[u8]) -> FileVersion {
+        let mtime = std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_millis() as u64);
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        FileVersion {
+            mtime,
+            size: bytes.len() as u64,
+            hash: format!("{:016x}", hasher.finish()),
+        }
+    }
+
+    /// Содержимое то же: время изменения меняется и без правок (touch, git checkout того же)
+    pub fn same_content(&self, other: &FileVersion) -> bool {
+        self.size == other.size && self.hash == other.hash
+    }
+}
+
+/// Текст файла вместе с тем, как он хранится на диске
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocument {
+    pub content: String,
+    // Название кодировки, как в выборе кодировки нижней панели
+    pub encoding: String,
+    // Кодировка определена автоматически, а не задана явно
+    pub detected: bool,
+    // При декодировании встретились некорректные последовательности (заменены на U+FFFD)
+    pub malformed: bool,
+    // Переводы строк файла; у файла из одной строки - системные
+    pub line_ending: LineEnding,
+    // Файл начинается с BOM
+    pub bom: bool,
+    // Передается в save_file, чтобы не затереть изменения, сделанные извне
+    pub version: FileVersion,
+}
+
+// Файлы больше открываются только в режиме больших файлов (commands::large_file)
+const MAX_IN_MEMORY_SIZE: u64 = 256 << 20;
+
+/// Не дает загрузить в память и передать во фронтенд многогигабайтный файл
+fn ensure_not_too_large(path: &Path) -> Result<(), String> {
+    let size = std::fs::metadata(path).map_or(0, |metadata| metadata.len());
+    if size > MAX_IN_MEMORY_SIZE {
+        return Err(format!(
+            "Файл '{}' слишком большой ({} МБ): откройте его в режиме больших файлов",
+            path.display(),
+            size >> 20
+        ));
+    }
+    Ok(())
+}
+
+fn read_bytes(path: &Path) -> Result<Vec<u8>, String> {
     // Проверяем, существует ли файл
-    let path = Path::new(&path);
     if !path.exists() {
         return Err(format!("Файл '{}' не существует", path.display()));
     }
-    
-    // Читаем файл с явным указанием UTF-8 кодировки
-    let content = std::fs::read(path)
-        .map_err(|err| format!("Не удалось открыть файл: {}", err))?;
-    
-    // Декодируем содержимое как UTF-8
-    String::from_utf8(content)
-        .map_err(|err| format!("Файл содержит некорректные UTF-8 символы: {}", err))
+    ensure_not_too_large(path)?;
+    std::fs::read(path).map_err(|err| format!("Не удалось открыть файл: {}", err))
+}
+
+/// Читает файл в кодировке `encoding` или в определенной автоматически
+pub fn read_document(path: &Path, encoding: Option<&str>) -> Result<TextDocument, String> {
+    let bytes = read_bytes(path)?;
+    let (encoding, detected) = match encoding {
+        Some(name) => (TextEncoding::from_name(name)?, false),
+        None => (text_encoding::detect(&bytes)?, true),
+    };
+    let (content, malformed) = encoding.decode(&bytes);
+    Ok(TextDocument {
+        line_ending: line_endings::detect(&content).unwrap_or_else(LineEnding::native),
+        bom: encoding.has_bom(&bytes),
+        version: FileVersion::of(path, &bytes),
+        content,
+        encoding: encoding.label.to_string(),
+        detected,
+        malformed,
+    })
+}
+
+#[tauri::command]
+pub fn read_text_file(path: String) -> Result<String, String> {
+    read_document(Path::new(&path), None).map(|document| document.content)
+}
+
+/// Читает текстовый файл с определением кодировки. С `encoding` - "открыть заново
+/// в кодировке": файл декодируется в указанной кодировке без попытки угадать.
+#[tauri::command]
+pub fn read_text_document(path: String, encoding: Option<String>) -> Result<TextDocument, String> {
+    read_document(Path::new(&path), encoding.as_deref())
 }
 
 #[tauri::command]
 pub fn read_binary_file(path: String) -> Result<Vec<u8>, String> {
+    ensure_not_too_large(Path::new(&path))?;
     match std::fs::read(path) {
         Ok(data) => Ok(data),
         Err(e) => Err(e.to_string()),
@@ -1,63 +1,58 @@
 use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
 use tokio::fs;
-use futures::future::join_all;
+use tauri::State;
 
-#[derive(Serialize, Deserialize, Debug)]
+use crate::commands::directory_listing::{self, DirectoryState};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FileItem {
     pub name: String,
     pub is_directory: bool,
     pub path: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub children: Option<Vec<FileItem>>,
+    // Игнорируется по .gitignore: показывается приглушенным
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignored: bool,
+    // Всего элементов в папке, если в children только страница
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_children: Option<usize>,
 }
 
-async fn read_directory(path: PathBuf, shallow: bool) -> std::io::Result<FileItem> {
-    let metadata = fs::metadata(&path).await?;
+/// Папка с отсортированной страницей содержимого: папки первыми, имена в естественном порядке
+async fn read_directory(
+    state: &DirectoryState,
+    path: PathBuf,
+    workspace_root: Option<String>,
+    show_ignored: Option<bool>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<FileItem, String> {
+    let metadata = fs::metadata(&path).await.map_err(|e| e.to_string())?;
     let is_directory = metadata.is_dir();
-    let mut children = None;
-
-    if is_directory && !shallow {
-        let mut dir_entries = Vec::new();
-        let mut read_dir = fs::read_dir(&path).await?;
-
-        // Собираем все записи в директории
-        while let Some(entry) = read_dir.next_entry().await? {
-            dir_entries.push(entry);
-        }
-
-        println!("Found {} entries in directory: {:?}", dir_entries.len(), path);
-
-        // Обрабатываем только первый уровень вложенности
-        let futures = dir_entries.into_iter().map(|entry| {
-            let child_path = entry.path();
-            let child_path_clone = child_path.clone();
-            println!("Processing entry: {:?}", child_path);
-            async move {
-                let result = read_directory(child_path_clone, true).await; // shallow = true для вложенных
-                if let Err(e) = &result {
-                    eprintln!("Error processing entry {:?}: {}", child_path, e);
-                }
-                result
-            }
-        });
-
-        let results: Vec<std::io::Result<FileItem>> = join_all(futures).await;
-        let mut processed_children = Vec::new();
-        for result in results {
-            match result {
-                Ok(child) => processed_children.push(child),
-                Err(e) => eprintln!("Error reading directory entry: {}", e),
-            }
-        }
-        children = Some(processed_children);
-    }
+    let (children, total_children) = if is_directory {
+        let (page, total) = directory_listing::list_page(
+            state,
+            path.clone(),
+            workspace_root,
+            show_ignored.unwrap_or(true),
+            offset.unwrap_or(0),
+            limit,
+        )
+        .await?;
+        (Some(page), Some(total))
+    } else {
+        (None, None)
+    };
 
     Ok(FileItem {
         name: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
         is_directory,
         path: path.to_string_lossy().into_owned(),
         children,
+        ignored: false,
+        total_children,
     })
 }
 
@@ -77,29 +72,41 @@ pub async fn create_new_file(path: String, name: String) -> Result<FileItem, Str
         is_directory: false,
         path: file_path.to_string_lossy().into_owned(),
         children: None,
+        ignored: false,
+        total_children: None,
     })
 }
 
+/// Игнорируемые по .gitignore элементы приходят с пометкой `ignored` (дерево показывает их приглушенными);
+/// `show_ignored: false` скрывает их совсем. Исключенные настройками files.exclude скрываются всегда.
+/// `offset` и `limit` задают страницу содержимого, `total_children` - сколько элементов всего
 #[tauri::command]
-pub async fn get_directory_tree(path: String) -> Result<FileItem, String> {
-    let root_path = PathBuf::from(path);
+pub async fn get_directory_tree(
+    state: State<'_, DirectoryState>,
+    path: String,
+    show_ignored: Option<bool>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<FileItem, String> {
+    let root_path = PathBuf::from(&path);
     println!("Starting get_directory_tree for {:?}", root_path);
-    let result = read_directory(root_path, false).await.map_err(|e| {
-        println!("Error in get_directory_tree: {}", e);
-        e.to_string()
-    });
-    println!("Finished get_directory_tree");
-    result
+    read_directory(&state, root_path, Some(path), show_ignored, offset, limit)
+        .await
+        .inspect_err(|e| println!("Error in get_directory_tree: {}", e))
 }
 
 #[tauri::command]
-pub async fn get_subdirectory(path: String) -> Result<FileItem, String> {
+pub async fn get_subdirectory(
+    state: State<'_, DirectoryState>,
+    path: String,
+    workspace_root: Option<String>,
+    show_ignored: Option<bool>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<FileItem, String> {
     let subdir_path = PathBuf::from(path);
     println!("Loading subdirectory for {:?}", subdir_path);
-    let result = read_directory(subdir_path, false).await.map_err(|e| {
-        println!("Error loading subdirectory: {}", e);
-        e.to_string()
-    });
-    println!("Finished loading subdirectory");
-    result
-}
\ No newline at end of file
+    read_directory(&state, subdir_path, workspace_root, show_ignored, offset, limit)
+        .await
+        .inspect_err(|e| println!("Error loading subdirectory: {}", e))
+}
@@ -1,5 +1,6 @@
 import React, { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { fileErrorMessage } from '../utils/fileErrors';
 
 interface VideoPlayerProps {
   filePath: string;
@@ -570,7 +571,7 @@ const VideoPlayer: React.FC<VideoPlayerProps> = ({ filePath, onLoad, onError })
                   
                   console.log('Opened video in external player');
                 } catch (err) {
-                  console.error('Failed to open video in external player:', err);
+                  console.error('Failed to open video in external player:', fileErrorMessage(err));
                 }
               }}
             >
@@ -9,6 +9,8 @@ import { configureMonaco, setCurrentProject, setOpenedFilesList } from './monaco
 import { getLanguageFromExtension } from '../../utils/languageDetector';
 import { fileFilters } from '../../utils/fileFilters';
 import { supportedTextExtensions, supportedImageExtensions, supportedVideoExtensions } from '../../utils/fileExtensions';
+import { fileErrorMessage, isReadOnlyError } from '../../utils/fileErrors';
+import { readTextDocument, rememberVersion, saveEditorFile, saveTextFile } from '../../utils/fileVersions';
 import { FontSizeContext } from '../../App';
 import { initializeTypeScriptTypes, registerTypeScriptFile } from './monaco-types-loader';
 import { setupMonacoTheme } from './monaco-theme-loader';
@@ -229,7 +231,8 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
               } else {
                 // Load content from disk since there's no cached version or file is not modified locally
                 console.log(`Загрузка файла с диска: ${selectedFile}`);
-                const content = await invoke('read_text_file', { path: selectedFile });
+                const textDocument = await readTextDocument(selectedFile);
+                const content = textDocument.content;
                 
                 // Проверяем, отличается ли новое содержимое от оригинального
                 const originalContent = originalFileContents.get(selectedFile);
@@ -247,6 +250,7 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
                   if (!modifiedFiles.has(selectedFile)) {
                     setFileContent(content);
                     setCode(content);
+                    rememberVersion(selectedFile, textDocument.version);
                   } else {
                     // Если файл был модифицирован, спрашиваем пользователя, что делать
                     const userChoice = confirm(
@@ -259,6 +263,7 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
                       // Пользователь выбрал загрузить новую версию
                       setFileContent(content);
                       setCode(content);
+                      rememberVersion(selectedFile, textDocument.version);
                       
                       // Удаляем файл из списка модифицированных
                       setModifiedFiles(prev => {
@@ -279,6 +284,7 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
                   // Файл не изменился или это первая загрузка
                   setFileContent(content);
                   setCode(content);
+                  rememberVersion(selectedFile, textDocument.version);
                 }
                 
                 setImageSrc(null);
@@ -685,11 +691,10 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
       }
 
       if (targetPath) {
-        // Сохраняем файл на диск
-        await invoke('save_file', {
-          path: targetPath,
-          content: code
-        });
+        // Сохраняем файл на диск; файл, измененный извне, перезаписывается только с согласия пользователя
+        if (!(await saveEditorFile(targetPath, code))) {
+          return;
+        }
         
         console.log(`Файл сохранён: ${targetPath}`);
         
@@ -727,7 +732,15 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
         }
       }
     } catch (error) {
-      console.error('Ошибка сохранения:', error);
+      console.error('Ошибка сохранения:', fileErrorMessage(error));
+      // Файл только для чтения - предлагаем сохранить копию в другое место
+      if (isReadOnlyError(error) && !saveAs) {
+        if (confirm(`${fileErrorMessage(error)}\n\nСохранить в другой файл?`)) {
+          await handleSaveFile(true);
+        }
+      } else {
+        alert(`Ошибка сохранения: ${fileErrorMessage(error)}`);
+      }
     }
   };
 
@@ -993,10 +1006,7 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
       
       if (filePath) {
         // Создаем пустой файл на диске
-        await invoke('save_file', {
-          path: filePath,
-          content: '' // Создаем пустой файл
-        });
+        await saveTextFile(filePath, '');
         
         console.log(`Создан новый файл: ${filePath}`);
         
@@ -1025,7 +1035,7 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
         });
       }
     } catch (error) {
-      console.error('Ошибка создания файла:', error);
+      console.error('Ошибка создания файла:', fileErrorMessage(error));
     }
   };
 
@@ -1924,10 +1934,8 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
         console.log(`Auto-saving previous file: ${previousFile}`);
         
         // Save directly to disk using the previous file's path
-        invoke('save_file', {
-          path: previousFile,
-          content: contentToSave
-        })
+        // Файл, измененный извне после чтения, не перезаписывается: остается в списке измененных
+        saveTextFile(previousFile, contentToSave)
         .then(() => {
           console.log(`Auto-saved previous file: ${previousFile}`);
           
@@ -1939,7 +1947,7 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
           });
         })
         .catch(error => {
-          console.error(`Error auto-saving previous file: ${previousFile}`, error);
+          console.error(`Error auto-saving previous file: ${previousFile}`, fileErrorMessage(error));
         });
       }
     }
@@ -2032,7 +2040,8 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
       }
 
       // Читаем содержимое файла
-      const diskContent = await invoke('read_text_file', { path: filePath });
+      const diskDocument = await readTextDocument(filePath);
+      const diskContent = diskDocument.content;
       
       // Получаем оригинальное содержимое
       const originalContent = originalFileContents.get(filePath);
@@ -2056,6 +2065,7 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
           console.log(`Обновление содержимого для неизмененного файла ${filePath}`);
           setFileContent(diskContent);
           setCode(diskContent);
+          rememberVersion(filePath, diskDocument.version);
           
           // Обновляем оригинальное содержимое
           setOriginalFileContents(prev => {
@@ -2075,6 +2085,7 @@ const CenterContainer: React.FC<CenterContainerProps> = ({
             // Пользователь выбрал загрузить новую версию
             setFileContent(diskContent);
             setCode(diskContent);
+            rememberVersion(filePath, diskDocument.version);
             
             // Удаляем файл из списка модифицированных
             setModifiedFiles(prev => {
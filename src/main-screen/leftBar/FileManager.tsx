@@ -19,6 +19,8 @@ interface FileItem {
   loaded?: boolean;
   icon?: React.ReactNode;
   gitStatus?: string;
+  // Игнорируется по .gitignore: показывается приглушенным
+  ignored?: boolean;
   hasChanges?: boolean;
   changesCount?: number;
   fileIssue?: {
@@ -815,7 +817,7 @@ const FileManager: React.FC<FileManagerProps> = ({
                     <span className={`icon ${gitStatusClass}`}>
                       {item.expanded ? <FolderOpen width={14} height={14} /> : <FolderIcon width={14} height={14} />}
                     </span>
-                    <span className="item-name">{item.name}</span>
+                    <span className={`item-name ${item.ignored ? 'ignored' : ''}`}>{item.name}</span>
                     {item.changesCount && item.changesCount > 0 && <span className="changes-badge">{item.changesCount}</span>}
                   </div>
                   
@@ -838,7 +840,7 @@ const FileManager: React.FC<FileManagerProps> = ({
                   <span className="icon">
                     {getFileIcon(item.name)}
                   </span>
-                  <span className="item-name">{item.name}</span>
+                  <span className={`item-name ${item.ignored ? 'ignored' : ''}`}>{item.name}</span>
                   {item.fileIssue && (item.fileIssue.errors > 0 || item.fileIssue.warnings > 0) && (
                     <span className="issues-badge">
                       {item.fileIssue.errors > 0 ? `E:${item.fileIssue.errors}` : ''}
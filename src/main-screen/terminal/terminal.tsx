@@ -123,6 +123,9 @@ const Terminal: React.FC<XTermTerminalProps> = (props) => {
   const terminal = useRef<XTerm | null>(null);
   const fitAddon = useRef<FitAddon | null>(null);
   const unlistenRef = useRef<(() => void) | null>(null);
+  // Сессия, которая сейчас отображается: основная или запуск файла ("run")
+  const activeSessionRef = useRef<string>("default");
+  const runFinishedRef = useRef(false);
   const [isProcessRunning, setIsProcessRunning] = useState(false);
   const [issueSearch, setIssueSearch] = useState("");
   const [showIssueFilters, setShowIssueFilters] = useState(false);
@@ -211,7 +214,7 @@ const Terminal: React.FC<XTermTerminalProps> = (props) => {
       try {
         fitAddon.current.fit();
         const { rows, cols } = terminal.current;
-        await invoke("resize_pty", { rows, cols }).catch((err: Error) => {
+        await invoke("resize_pty", { sessionId: activeSessionRef.current, rows, cols }).catch((err: Error) => {
           console.error("Failed to resize PTY:", err);
         });
       } catch (e) {
@@ -220,6 +223,21 @@ const Terminal: React.FC<XTermTerminalProps> = (props) => {
     }
   };
 
+  // Возврат из сессии запуска к основному терминалу с восстановлением его содержимого
+  const showDefaultSession = async () => {
+    if (!terminal.current) return;
+    activeSessionRef.current = "default";
+    runFinishedRef.current = false;
+    terminal.current.reset();
+    try {
+      const snapshot = await invoke<{ ansi: string }>("get_terminal_snapshot", { sessionId: "default" });
+      terminal.current?.write(snapshot.ansi);
+    } catch (err) {
+      console.error("Failed to restore terminal:", err);
+    }
+    await resizeTerminal();
+  };
+
   // Запуск процесса в терминале
   const startTerminalProcess = async () => {
     if (!terminal.current || isProcessRunning) return;
@@ -234,8 +252,9 @@ const Terminal: React.FC<XTermTerminalProps> = (props) => {
         
         terminal.current.write("\r\n\x1b[33mStarting terminal process...\x1b[0m\r\n");
         
-        await invoke("start_process");
-        await invoke("resize_pty", { rows, cols }).catch((err: Error) => {
+        const sessionId = activeSessionRef.current;
+        await invoke("start_process", { sessionId, projectRoot: selectedFolder ?? undefined });
+        await invoke("resize_pty", { sessionId, rows, cols }).catch((err: Error) => {
           console.error("Failed to resize PTY:", err);
         });
         
@@ -314,7 +333,12 @@ const Terminal: React.FC<XTermTerminalProps> = (props) => {
         fit.fit();
 
         term.onData((data) => {
-          invoke("send_input", { input: data }).catch((err: Error) => {
+          // После завершения запуска любая клавиша возвращает основной терминал
+          if (activeSessionRef.current === "run" && runFinishedRef.current) {
+            showDefaultSession().catch(console.error);
+            return;
+          }
+          invoke("send_input", { sessionId: activeSessionRef.current, input: data }).catch((err: Error) => {
             console.error("Failed to send input:", err);
             term.write(`\r\n\x1b[31mError: ${err}\x1b[0m\r\n`);
           });
@@ -332,13 +356,42 @@ const Terminal: React.FC<XTermTerminalProps> = (props) => {
         }
 
         // Слушатель вывода процесса
-        const unlisten = await listen<string>("pty-output", (event) => {
-          if (terminal.current) {
-            terminal.current.write(event.payload);
+        const unlisten = await listen<{ sessionId: string; data: string; bytes: number }>("pty-output", (event) => {
+          const { sessionId, data, bytes } = event.payload;
+          const ack = () => {
+            invoke("ack_pty_output", { sessionId, bytes }).catch(() => {});
+          };
+          if (terminal.current && sessionId === activeSessionRef.current) {
+            // Подтверждаем отрисовку, чтобы бэкенд мог притормозить поток вывода
+            terminal.current.write(data, ack);
+          } else {
+            // Вывод скрытой сессии сохраняется на бэкенде и восстановится из снимка
+            ack();
           }
         });
 
-        unlistenRef.current = unlisten;
+        const unlistenRunStarted = await listen<{ command: string }>("run-started", (event) => {
+          if (!terminal.current) return;
+          activeSessionRef.current = "run";
+          runFinishedRef.current = false;
+          terminal.current.reset();
+          terminal.current.write(`\x1b[33m> ${event.payload.command}\x1b[0m\r\n`);
+        });
+
+        const unlistenExit = await listen<{ sessionId: string; exitCode: number | null; success: boolean }>("pty-exit", (event) => {
+          if (!terminal.current || event.payload.sessionId !== "run" || activeSessionRef.current !== "run") return;
+          runFinishedRef.current = true;
+          const status = event.payload.success
+            ? "\x1b[32mПрограмма успешно завершена\x1b[0m"
+            : `\x1b[31mПрограмма завершилась с ошибкой (код ${event.payload.exitCode ?? "?"})\x1b[0m`;
+          terminal.current.write(`\r\n${status}\r\n\x1b[90mНажмите любую клавишу, чтобы вернуться в терминал\x1b[0m`);
+        });
+
+        unlistenRef.current = () => {
+          unlisten();
+          unlistenRunStarted();
+          unlistenExit();
+        };
 
         // Запускаем процесс после инициализации терминала
         await startTerminalProcess();
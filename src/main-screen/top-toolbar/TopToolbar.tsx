@@ -90,7 +90,7 @@ const TopToolbar: React.FC<TopToolbarProps> = ({
       }
       
       // Запускаем Python файл
-      await invoke('run_python_file', { filePath: currentFile });
+      await invoke('run_current_file', { filePath: currentFile, projectRoot: selectedFolder });
       
     } catch (error) {
       console.error('Ошибка при запуске Python файла:', error);
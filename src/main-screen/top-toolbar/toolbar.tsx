@@ -23,6 +23,8 @@ import Settings from '../lefttoolbar/settings';
 import './style.css';
 import { FaPython } from "react-icons/fa";
 import { updateAllPythonDiagnostics, forcePythonDiagnosticsUpdate } from "../centerContainer/python-lsp-starter";
+import { fileErrorMessage, isReadOnlyError } from '../../utils/fileErrors';
+import { saveEditorFile } from '../../utils/fileVersions';
 import "./toolbar.css";
 
 export interface TopToolbarProps {
@@ -170,17 +172,18 @@ const TopToolbar: React.FC<TopToolbarProps> = ({
       }
 
       if (targetPath && currentContent !== undefined) {
-        await invoke('save_file', {
-          path: targetPath,
-          content: currentContent
-        });
+        if (!(await saveEditorFile(targetPath, currentContent))) {
+          return;
+        }
         
         onFileSaved?.(targetPath);
         alert(`Файл сохранён: ${targetPath}`);
       }
     } catch (error) {
-      console.error('Ошибка сохранения:', error);
-      alert(`Ошибка сохранения: ${error}`);
+      console.error('Ошибка сохранения:', fileErrorMessage(error));
+      alert(isReadOnlyError(error)
+        ? `${fileErrorMessage(error)}\n\nИспользуйте "Сохранить как", чтобы записать копию в другое место.`
+        : `Ошибка сохранения: ${fileErrorMessage(error)}`);
     }
   };
 
@@ -553,36 +556,30 @@ const TopToolbar: React.FC<TopToolbarProps> = ({
     }
   };
 
-  // Функция для запуска Python кода в терминале
+  // Функция для запуска текущего файла в терминале
   const handleRunCode = async () => {
     if (!selectedFile) {
       console.warn('No file selected to run');
       return;
     }
 
-    // Проверяем, что файл - Python файл
-    if (!selectedFile.toLowerCase().endsWith('.py')) {
-      console.warn('Selected file is not a Python file');
-      return;
-    }
-
     try {
-      console.log(`Running Python file: ${selectedFile}`);
+      console.log(`Running file: ${selectedFile}`);
       
       // Сначала открываем терминал, если он не открыт
       if (onOpenConsole) {
         onOpenConsole();
       }
       
-      // Запускаем Python файл через команду run_python_file
-      await invoke('run_python_file', { filePath: selectedFile });
+      // Команда запуска выбирается по языку файла и настройкам проекта
+      await invoke('run_current_file', { filePath: selectedFile, projectRoot: selectedFolder ?? null });
       
     } catch (error) {
-      console.error('Error running Python file:', error);
+      console.error('Error running file:', error);
       // Показываем ошибку в терминале
       document.dispatchEvent(new CustomEvent('terminal-write-text', { 
         detail: { 
-          text: `\r\nОшибка при запуске Python файла: ${error}\r\n` 
+          text: `\r\nОшибка при запуске файла: ${error}\r\n` 
         } 
       }));
     }
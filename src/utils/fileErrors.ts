@@ -0,0 +1,26 @@
+import type { FileVersion } from './fileVersions';
+
+// Ошибки записи файлов из бэкенда (write_text_file, save_file) приходят объектом с полем kind.
+// conflict - файл изменили на диске после чтения (save_file с expectedVersion)
+export type FileWriteError =
+  | { kind: 'readOnly'; path: string; message: string }
+  | { kind: 'failed'; message: string }
+  | { kind: 'conflict'; path: string; message: string; diskContent: string; diskEncoding: string; diskVersion: FileVersion };
+
+const isErrorObject = (error: unknown): error is { kind?: unknown; message?: unknown } =>
+  typeof error === 'object' && error !== null;
+
+// Текст ошибки для показа: у объектов ошибок - message, остальное как есть
+export const fileErrorMessage = (error: unknown): string => {
+  if (isErrorObject(error) && typeof error.message === 'string') {
+    return error.message;
+  }
+  return String(error);
+};
+
+// Файл доступен только для чтения: можно предложить "Сохранить как"
+export const isReadOnlyError = (error: unknown): error is Extract<FileWriteError, { kind: 'readOnly' }> =>
+  isErrorObject(error) && error.kind === 'readOnly';
+
+export const isConflictError = (error: unknown): error is Extract<FileWriteError, { kind: 'conflict' }> =>
+  isErrorObject(error) && error.kind === 'conflict';
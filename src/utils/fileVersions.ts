@@ -0,0 +1,68 @@
+import { invoke } from '@tauri-apps/api/core';
+import { isConflictError } from './fileErrors';
+
+// Версия файла на диске (reading::FileVersion)
+export interface FileVersion {
+  mtime: number;
+  size: number;
+  hash: string;
+}
+
+// Ответ read_text_document
+export interface TextDocument {
+  content: string;
+  encoding: string;
+  detected: boolean;
+  malformed: boolean;
+  lineEnding: string;
+  bom: boolean;
+  version: FileVersion;
+}
+
+// Ответ save_file (поля, которые нужны редактору)
+export interface SaveResult {
+  encoding: string;
+  bom: boolean;
+  version: FileVersion;
+}
+
+// Версия, с которой начинается содержимое редактора: прочитанная с диска или записанная последним сохранением
+const versions = new Map<string, FileVersion>();
+
+export const readTextDocument = (path: string) =>
+  invoke<TextDocument>('read_text_document', { path });
+
+// Редактор показал содержимое файла этой версии
+export const rememberVersion = (path: string, version: FileVersion) => {
+  versions.set(path, version);
+};
+
+// Сохраняет текст. Если файл изменили на диске после чтения, save_file возвращает ошибку kind: 'conflict';
+// overwrite сохраняет без проверки
+export const saveTextFile = async (path: string, content: string, overwrite = false) => {
+  const result = await invoke<SaveResult>('save_file', {
+    path,
+    content,
+    expectedVersion: overwrite ? undefined : versions.get(path)
+  });
+  versions.set(path, result.version);
+  return result;
+};
+
+// Сохранение по команде пользователя: при конфликте спрашивает, перезаписать ли изменения на диске.
+// false - пользователь отказался, файл не сохранен
+export const saveEditorFile = async (path: string, content: string) => {
+  try {
+    await saveTextFile(path, content);
+    return true;
+  } catch (error) {
+    if (!isConflictError(error)) {
+      throw error;
+    }
+    if (!confirm(`${error.message}\n\nФайл изменили вне редактора. Перезаписать его вашей версией?`)) {
+      return false;
+    }
+    await saveTextFile(path, content, true);
+    return true;
+  }
+};